@@ -4,6 +4,7 @@ use axum::response::IntoResponse;
 use axum::routing::post;
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
+use shared::changes::{self, SettingsChange};
 
 pub fn set_log_channel() -> Router {
 	Router::new().route("/{guild_id}/settings/log-channel", post(handle_request))
@@ -22,39 +23,48 @@ struct Response {
 	reason:  Option<String>,
 }
 
-// todo: have some enum for all possible log types for checking
 // todo: check if channel id is a valid id, and maybe if the guil has that channel id
 // todo: auth and check if person making request has permission to do so
 async fn handle_request(
 	Path(guild_id): Path<String>,
 	Json(payload): Json<SetLogChannel>,
 ) -> impl IntoResponse {
-	match set_channel_id(&payload.log_type, payload.channel_id, guild_id).await {
+	let Ok(guild_id) = guild_id.parse::<u64>() else {
+		let response = Response {
+			success: false,
+			reason:  Some(format!("`{}` isn't a guild id", guild_id)),
+		};
+		return (StatusCode::BAD_REQUEST, Json(response));
+	};
+
+	let change = SettingsChange::LogChannel {
+		guild_id,
+		log_type: payload.log_type,
+		channel_id: payload.channel_id,
+	};
+	if let Err(reason) = change.validate() {
+		let response = Response {
+			success: false,
+			reason:  Some(reason),
+		};
+		return (StatusCode::BAD_REQUEST, Json(response));
+	}
+
+	// applied by the bot within a few seconds
+	match changes::submit(&change) {
 		| Ok(()) => {
 			let response = Response {
 				success: true,
 				reason:  None,
 			};
-			(StatusCode::OK, Json(response))
+			(StatusCode::ACCEPTED, Json(response))
 		},
 		| Err(e) => {
 			let response = Response {
 				success: false,
 				reason:  Some(e.to_string()),
 			};
-			(StatusCode::BAD_REQUEST, Json(response))
+			(StatusCode::INTERNAL_SERVER_ERROR, Json(response))
 		},
 	}
 }
-
-async fn set_channel_id(
-	key: &str,
-	channel_id: u64,
-	guild_id: String,
-) -> Result<(), sled::Error> {
-	let db = sled::open("data/guild_settings/log_channels")?;
-	let tree = db.open_tree(guild_id)?;
-	tree.insert(key.as_bytes(), &channel_id.to_be_bytes())?;
-	tree.flush()?;
-	Ok(())
-}
@@ -0,0 +1,4 @@
+//! This file is autogenerated by build.rs, do not edit.
+#![cfg_attr(rustfmt, rustfmt_skip)]
+
+pub mod set_log_ignore;
\ No newline at end of file
@@ -0,0 +1,108 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use shared::changes::{self, LogIgnoreRules, SettingsChange};
+
+pub fn set_log_ignore() -> Router {
+	Router::new().route("/{guild_id}/settings/log-ignore", post(handle_request))
+}
+
+/// replaces every ignore rule of one log type
+#[derive(Deserialize, Debug)]
+struct SetLogIgnore {
+	log_type:   String,
+	#[serde(default)]
+	channels:   Vec<String>,
+	#[serde(default)]
+	categories: Vec<String>,
+	#[serde(default)]
+	users:      Vec<String>,
+	#[serde(default)]
+	roles:      Vec<String>,
+	#[serde(default)]
+	bots:       bool,
+	#[serde(default = "default_commands")]
+	commands:   bool,
+}
+
+fn default_commands() -> bool {
+	true
+}
+
+#[derive(Serialize)]
+struct Response {
+	success: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	reason:  Option<String>,
+}
+
+// todo: auth and check if person making request has permission to do so
+async fn handle_request(
+	Path(guild_id): Path<String>,
+	Json(payload): Json<SetLogIgnore>,
+) -> impl IntoResponse {
+	let change = match validate(&guild_id, payload) {
+		| Ok(change) => change,
+		| Err(reason) => {
+			let response = Response {
+				success: false,
+				reason:  Some(reason),
+			};
+			return (StatusCode::BAD_REQUEST, Json(response));
+		},
+	};
+
+	// applied by the bot within a few seconds
+	match changes::submit(&change) {
+		| Ok(()) => {
+			let response = Response {
+				success: true,
+				reason:  None,
+			};
+			(StatusCode::ACCEPTED, Json(response))
+		},
+		| Err(e) => {
+			let response = Response {
+				success: false,
+				reason:  Some(e.to_string()),
+			};
+			(StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+		},
+	}
+}
+
+fn validate(
+	guild_id: &str,
+	payload: SetLogIgnore,
+) -> Result<SettingsChange, String> {
+	let guild_id = guild_id
+		.parse::<u64>()
+		.map_err(|_| format!("`{}` isn't a guild id", guild_id))?;
+
+	let ids = |ids: &[String]| {
+		ids.iter()
+			.map(|id| match id.parse::<u64>() {
+				| Ok(parsed) if parsed != 0 => Ok(parsed),
+				| _ => Err(format!("`{}` isn't a discord id", id)),
+			})
+			.collect::<Result<Vec<_>, _>>()
+	};
+
+	let change = SettingsChange::LogIgnore {
+		guild_id,
+		rules: LogIgnoreRules {
+			channels:   ids(&payload.channels)?,
+			categories: ids(&payload.categories)?,
+			users:      ids(&payload.users)?,
+			roles:      ids(&payload.roles)?,
+			bots:       payload.bots,
+			commands:   payload.commands,
+		},
+		log_type: payload.log_type,
+	};
+	change.validate()?;
+	Ok(change)
+}
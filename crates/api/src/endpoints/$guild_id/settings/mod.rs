@@ -1,4 +1,6 @@
 //! This file is autogenerated by build.rs, do not edit.
 #![cfg_attr(rustfmt, rustfmt_skip)]
 
-pub mod log_channel;
\ No newline at end of file
+pub mod log_channel;
+pub mod log_ignore;
+pub mod prefix;
\ No newline at end of file
@@ -0,0 +1,69 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use shared::changes::{self, SettingsChange};
+
+pub fn set_prefix() -> Router {
+	Router::new().route("/{guild_id}/settings/prefix", post(handle_request))
+}
+
+#[derive(Deserialize, Debug)]
+struct SetPrefix {
+	prefixes:          Vec<String>,
+	mention_as_prefix: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct Response {
+	success: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	reason:  Option<String>,
+}
+
+// todo: auth and check if person making request has permission to do so
+// the bot applies the change within a few seconds, hence accepted rather than ok
+async fn handle_request(
+	Path(guild_id): Path<String>,
+	Json(payload): Json<SetPrefix>,
+) -> impl IntoResponse {
+	let Ok(guild_id) = guild_id.parse::<u64>() else {
+		let response = Response {
+			success: false,
+			reason:  Some(format!("`{}` isn't a guild id", guild_id)),
+		};
+		return (StatusCode::BAD_REQUEST, Json(response));
+	};
+
+	let change = SettingsChange::Prefix {
+		guild_id,
+		prefixes: payload.prefixes,
+		mention: payload.mention_as_prefix,
+	};
+	if let Err(reason) = change.validate() {
+		let response = Response {
+			success: false,
+			reason:  Some(reason),
+		};
+		return (StatusCode::BAD_REQUEST, Json(response));
+	}
+
+	match changes::submit(&change) {
+		| Ok(()) => {
+			let response = Response {
+				success: true,
+				reason:  None,
+			};
+			(StatusCode::ACCEPTED, Json(response))
+		},
+		| Err(e) => {
+			let response = Response {
+				success: false,
+				reason:  Some(e.to_string()),
+			};
+			(StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+		},
+	}
+}
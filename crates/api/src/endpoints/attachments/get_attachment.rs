@@ -0,0 +1,112 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::Router;
+use axum::extract::{Path, Query};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use ring::hmac;
+use serde::Deserialize;
+use shared::attachment_links;
+
+/// written by the bot's message logs, files are named by their sha256
+const ATTACHMENTS_DIR: &str = "data/attachments";
+
+pub fn get_attachment() -> Router {
+	Router::new().route("/attachments/{hash}/{name}", get(handle_request))
+}
+
+#[derive(Deserialize)]
+struct Signature {
+	expires: u64,
+	sig:     String,
+}
+
+// links are signed by the bot with `ATTACHMENT_URL_SECRET`, so anyone with one can view it until
+// it expires
+async fn handle_request(
+	Path((hash, name)): Path<(String, String)>,
+	Query(signature): Query<Signature>,
+) -> Response {
+	// also keeps the path from leaving the attachments dir
+	if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+		return StatusCode::NOT_FOUND.into_response();
+	}
+
+	if !verify(&hash, &name, &signature) {
+		return StatusCode::FORBIDDEN.into_response();
+	}
+
+	let bytes = match tokio::fs::read(format!("{}/{}", ATTACHMENTS_DIR, hash)).await {
+		| Ok(bytes) => bytes,
+		// deleted to stay under the bot's quota
+		| Err(_) => return StatusCode::NOT_FOUND.into_response(),
+	};
+
+	(
+		[
+			(header::CONTENT_TYPE, content_type(&name).to_string()),
+			(
+				header::CONTENT_DISPOSITION,
+				format!("inline; filename=\"{}\"", name),
+			),
+			(header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+		],
+		bytes,
+	)
+		.into_response()
+}
+
+fn verify(
+	hash: &str,
+	name: &str,
+	signature: &Signature,
+) -> bool {
+	// the same check the bot makes, so a placeholder never verifies anything
+	let Ok(Some(secret)) = attachment_links::secret() else {
+		return false;
+	};
+
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(u64::MAX);
+	if signature.expires < now {
+		return false;
+	}
+
+	let Some(sig) = decode_hex(&signature.sig) else {
+		return false;
+	};
+
+	let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+	let message = format!("{}/{}/{}", hash, name, signature.expires);
+	hmac::verify(&key, message.as_bytes(), &sig).is_ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+	if !hex.len().is_multiple_of(2) {
+		return None;
+	}
+	(0..hex.len())
+		.step_by(2)
+		.map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+		.collect()
+}
+
+/// only types browsers show safely, anything else is downloaded
+fn content_type(name: &str) -> &'static str {
+	let extension = name.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+	match extension.as_str() {
+		| "png" => "image/png",
+		| "jpg" | "jpeg" => "image/jpeg",
+		| "gif" => "image/gif",
+		| "webp" => "image/webp",
+		| "mp4" => "video/mp4",
+		| "webm" => "video/webm",
+		| "mp3" => "audio/mpeg",
+		| "ogg" => "audio/ogg",
+		| "txt" | "log" => "text/plain; charset=utf-8",
+		| _ => "application/octet-stream",
+	}
+}
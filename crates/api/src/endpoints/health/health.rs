@@ -1,12 +1,201 @@
-use axum::Router;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use shared::changes::CHANGES_DIR;
+
+/// the bot's stores, only looked at since sled lets one process open them. the bot reads them
+/// and reports how that went in its status
+const SETTINGS_DB_PATHS: [&str; 2] = [
+	"data/guild_settings/config",
+	"data/guild_settings/log_channels",
+];
+/// not `.json` so the bot doesn't take it for a change
+const PROBE_NAME: &str = ".health_check";
+const BOT_STATUS_PATH: &str = "data/bot_status.json";
+
+/// the bot writes its status every 15 seconds, anything older than this means its dead or stuck
+const BOT_STATUS_MAX_AGE: u64 = 60;
 
 pub fn health() -> Router {
 	Router::new().route("/health", get(handle_request))
 }
 
+#[derive(Serialize)]
+struct Response {
+	healthy:     bool,
+	version:     &'static str,
+	uptime_secs: u64,
+	storage:     StorageHealth,
+	bot:         BotHealth,
+}
+
+#[derive(Serialize)]
+struct StorageHealth {
+	available: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error:     Option<String>,
+}
+
+#[derive(Serialize, Default)]
+struct BotHealth {
+	connected:          bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	latency_ms:         Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	uptime_secs:        Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	version:            Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	last_heartbeat_ago: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error:              Option<String>,
+}
+
+/// written by the bot in `background/heartbeat.rs`
+#[derive(Deserialize)]
+struct BotStatus {
+	connected:     bool,
+	latency_ms:    Option<u64>,
+	version:       String,
+	started_at:    u64,
+	updated_at:    u64,
+	/// older bots don't report it
+	#[serde(default)]
+	storage_error: Option<String>,
+}
+
 async fn handle_request() -> impl IntoResponse {
-	(StatusCode::OK, "hi")
+	let (bot, bot_storage_error) = check_bot().await;
+	let storage = match tokio::task::spawn_blocking(check_storage).await {
+		| Ok(Ok(())) if bot_storage_error.is_some() => StorageHealth {
+			available: false,
+			error:     bot_storage_error,
+		},
+		| Ok(Ok(())) => StorageHealth {
+			available: true,
+			error:     None,
+		},
+		| Ok(Err(e)) => StorageHealth {
+			available: false,
+			error:     Some(e.to_string()),
+		},
+		| Err(e) => StorageHealth {
+			available: false,
+			error:     Some(e.to_string()),
+		},
+	};
+
+	let healthy = storage.available && bot.connected;
+
+	let response = Response {
+		healthy,
+		version: env!("CARGO_PKG_VERSION"),
+		uptime_secs: crate::STARTED_AT
+			.get()
+			.map(|s| s.elapsed().as_secs())
+			.unwrap_or(0),
+		storage,
+		bot,
+	};
+
+	let status = if healthy {
+		StatusCode::OK
+	} else {
+		StatusCode::SERVICE_UNAVAILABLE
+	};
+
+	(status, Json(response))
+}
+
+/// Does a write, read and delete round trip in the directory the api leaves settings changes in,
+/// and makes sure the bot's settings stores exist without touching them
+fn check_storage() -> io::Result<()> {
+	fs::create_dir_all(CHANGES_DIR)?;
+	let path = Path::new(CHANGES_DIR).join(PROBE_NAME);
+	let probe = unix_now().to_be_bytes();
+	fs::write(&path, probe)?;
+
+	let read = fs::read(&path);
+	fs::remove_file(&path)?;
+	if read? != probe {
+		return Err(io::Error::other(
+			"read back a different value than was written",
+		));
+	}
+
+	for store in SETTINGS_DB_PATHS {
+		match fs::metadata(store) {
+			| Ok(metadata) if metadata.is_dir() => {},
+			| Ok(_) => {
+				return Err(io::Error::other(format!(
+					"`{}` isn't a settings store",
+					store
+				)));
+			},
+			| Err(e) if e.kind() == io::ErrorKind::NotFound => {
+				return Err(io::Error::other(format!(
+					"`{}` doesn't exist, the bot creates it when it starts",
+					store
+				)));
+			},
+			| Err(e) => return Err(e),
+		}
+	}
+	Ok(())
+}
+
+/// Also returns why the bot couldn't read its settings stores, as long as its status is recent
+async fn check_bot() -> (BotHealth, Option<String>) {
+	let status = match tokio::fs::read(BOT_STATUS_PATH).await {
+		| Ok(bytes) => serde_json::from_slice::<BotStatus>(&bytes),
+		| Err(e) => {
+			let health = BotHealth {
+				error: Some(format!("no status reported by the bot: {}", e)),
+				..Default::default()
+			};
+			return (health, None);
+		},
+	};
+
+	let status = match status {
+		| Ok(status) => status,
+		| Err(e) => {
+			let health = BotHealth {
+				error: Some(format!("malformed bot status: {}", e)),
+				..Default::default()
+			};
+			return (health, None);
+		},
+	};
+
+	let now = unix_now();
+	let last_heartbeat_ago = now.saturating_sub(status.updated_at);
+	let stale = last_heartbeat_ago > BOT_STATUS_MAX_AGE;
+
+	let health = BotHealth {
+		connected:          status.connected && !stale,
+		latency_ms:         status.latency_ms,
+		uptime_secs:        Some(now.saturating_sub(status.started_at)),
+		version:            Some(status.version),
+		last_heartbeat_ago: Some(last_heartbeat_ago),
+		error:              stale.then(|| "bot status is stale".to_string()),
+	};
+	let storage_error = status
+		.storage_error
+		.filter(|_| !stale)
+		.map(|e| format!("the bot couldn't read its settings stores: {}", e));
+	(health, storage_error)
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
 }
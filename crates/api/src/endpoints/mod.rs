@@ -2,11 +2,12 @@
 #![cfg_attr(rustfmt, rustfmt_skip)]
 
 pub mod health;
+pub mod attachments;
 #[path = "$guild_id/mod.rs"]
 pub mod guild_id;
 
 pub use axum::Router;
 
 pub fn add_endpoints(app: Router) -> Router {
-	app.merge(health::health::health()).merge(guild_id::settings::log_channel::set_log_channel::set_log_channel())
+	app.merge(health::health::health()).merge(guild_id::settings::log_channel::set_log_channel::set_log_channel()).merge(guild_id::settings::log_ignore::set_log_ignore::set_log_ignore()).merge(guild_id::settings::prefix::set_prefix::set_prefix()).merge(attachments::get_attachment::get_attachment())
 }
\ No newline at end of file
@@ -1,15 +1,31 @@
 use std::env::var;
+use std::sync::OnceLock;
+use std::time::Instant;
 
-use tracing::{Level, info};
+use shared::attachment_links;
+use tracing::{Level, info, warn};
 
 mod endpoints;
 
+pub static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
 #[tokio::main]
 async fn main() {
+	STARTED_AT.get_or_init(Instant::now);
+
 	tracing_subscriber::fmt()
 		.with_max_level(Level::DEBUG)
 		.init();
 
+	match attachment_links::secret() {
+		| Ok(Some(_)) => {},
+		| Ok(None) => warn!(
+			"{} isn't set, attachment links won't work",
+			attachment_links::SECRET_VAR
+		),
+		| Err(e) => warn!("{}, attachment links won't work", e),
+	}
+
 	let app = endpoints::add_endpoints(axum::Router::new());
 
 	let listener = tokio::net::TcpListener::bind(format!(
@@ -0,0 +1,128 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::all::{ChannelId, GuildId, RoleId, UserId};
+use shared::changes::{self, SettingsChange};
+
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{self, IgnoreRules, LogType, Setting, SettingValue};
+use crate::utils::prefix_cache::PrefixCache;
+
+const INTERVAL: Duration = Duration::from_secs(2);
+
+/// Applies settings changes the api left behind, the api can't open the stores while the bot has
+/// them open
+pub async fn run(prefixes: Arc<PrefixCache>) {
+	let mut interval = tokio::time::interval(INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let pending = match changes::pending() {
+			| Ok(pending) => pending,
+			| Err(e) => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't read the api changes: {}",
+					e
+				);
+				continue;
+			},
+		};
+
+		for path in pending {
+			match apply(&path, &prefixes) {
+				| Ok(()) => {},
+				| Err(BotError::Storage(e)) => {
+					// kept for the next run, later changes wait so they're applied in order
+					println!(
+						"\x1b[33;1m[WARN]\x1b[0m Couldn't apply the api change {}: {}",
+						path.display(),
+						e
+					);
+					break;
+				},
+				| Err(e) => println!(
+					"\x1b[33;1m[WARN]\x1b[0m Dropped the api change {}: {}",
+					path.display(),
+					e
+				),
+			}
+
+			if let Err(e) = std::fs::remove_file(&path) {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't remove the api change {}: {}",
+					path.display(),
+					e
+				);
+			}
+		}
+	}
+}
+
+fn apply(
+	path: &Path,
+	prefixes: &PrefixCache,
+) -> Result<(), BotError> {
+	let change = changes::read(path)
+		.map_err(|e| BotError::InvalidInput(format!("it couldn't be read: {}", e)))?;
+	// the api already refused invalid changes, this only catches files that didn't come from it
+	change.validate().map_err(BotError::InvalidInput)?;
+
+	match change {
+		| SettingsChange::Prefix {
+			guild_id,
+			prefixes: new,
+			mention,
+		} => {
+			let guild_id = GuildId::new(guild_id);
+			guild_settings::set(guild_id, Setting::Prefix, &SettingValue::Prefixes(new))?;
+			if let Some(mention) = mention {
+				guild_settings::set(
+					guild_id,
+					Setting::MentionPrefix,
+					&SettingValue::Toggle(mention),
+				)?;
+			}
+			prefixes.invalidate(guild_id);
+		},
+		| SettingsChange::LogChannel {
+			guild_id,
+			log_type,
+			channel_id,
+		} => {
+			guild_settings::set_log_channels(GuildId::new(guild_id), &[(
+				log_type_of(&log_type)?,
+				ChannelId::new(channel_id),
+			)])?;
+		},
+		| SettingsChange::LogIgnore {
+			guild_id,
+			log_type,
+			rules,
+		} => {
+			let rules = IgnoreRules {
+				channels:   rules.channels.into_iter().map(ChannelId::new).collect(),
+				categories: rules.categories.into_iter().map(ChannelId::new).collect(),
+				users:      rules.users.into_iter().map(UserId::new).collect(),
+				roles:      rules.roles.into_iter().map(RoleId::new).collect(),
+				bots:       rules.bots,
+				commands:   rules.commands,
+			};
+			guild_settings::set_ignore_rules(
+				GuildId::new(guild_id),
+				log_type_of(&log_type)?,
+				&rules,
+			)?;
+		},
+	}
+
+	Ok(())
+}
+
+fn log_type_of(key: &str) -> Result<LogType, BotError> {
+	LogType::from_key(key).ok_or(BotError::InvalidInput(format!(
+		"`{}` isn't a log type",
+		key
+	)))
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use regex::{Regex, RegexBuilder};
+use serenity::all::{GuildId, RoleId};
+
+use crate::utils::automod_settings::{self, AutomodSettings, FilterKind, FilterRule};
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings;
+
+/// keeps a single rule from making a huge regex
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+/// what is allowed between the letters of a word, so `b.a d` still matches `bad`
+const SEPARATOR: &str = r"[\W_]*";
+
+/// Lowercases and undoes common letter swaps, so `B4D` and `bad` look the same
+pub fn normalize(text: &str) -> String {
+	text.chars()
+		.flat_map(char::to_lowercase)
+		.filter(|c| !matches!(c, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}'))
+		.map(|c| match c {
+			| '0' => 'o',
+			| '1' => 'i',
+			| '3' => 'e',
+			| '4' | '@' => 'a',
+			| '5' | '$' => 's',
+			| '7' => 't',
+			| '8' => 'b',
+			| '9' => 'g',
+			| c => c,
+		})
+		.collect()
+}
+
+/// Builds the regex for a word rule, matched against [`normalize`]d text. `*` matches any
+/// letters, repeated letters and anything between letters are allowed
+fn word_regex(pattern: &str) -> Result<Regex, regex::Error> {
+	let parts: Vec<String> = normalize(pattern)
+		.chars()
+		.filter(|c| !c.is_whitespace())
+		.map(|c| match c {
+			| '*' => r"\w*".to_string(),
+			| c => format!("{}+", regex::escape(&c.to_string())),
+		})
+		.collect();
+
+	RegexBuilder::new(&format!(r"\b{}\b", parts.join(SEPARATOR)))
+		.size_limit(REGEX_SIZE_LIMIT)
+		.build()
+}
+
+fn user_regex(pattern: &str) -> Result<Regex, regex::Error> {
+	RegexBuilder::new(pattern)
+		.case_insensitive(true)
+		.size_limit(REGEX_SIZE_LIMIT)
+		.build()
+}
+
+/// Checks a rule before it's saved, returning why it can't be used
+pub fn validate(rule: &FilterRule) -> Result<(), String> {
+	match rule.kind {
+		| FilterKind::Word
+			if normalize(&rule.pattern)
+				.chars()
+				.all(|c| !c.is_alphanumeric()) =>
+		{
+			Err("Word filters need at least one letter or number.".to_string())
+		},
+		| FilterKind::Word => word_regex(&rule.pattern)
+			.map(|_| ())
+			.map_err(|e| e.to_string()),
+		| FilterKind::Regex => user_regex(&rule.pattern)
+			.map(|_| ())
+			.map_err(|e| e.to_string()),
+	}
+}
+
+/// A guild's settings with its filters compiled
+pub struct Rules {
+	pub settings:        AutomodSettings,
+	/// always exempt, kept here so messages don't read the config store
+	pub moderator_roles: Vec<RoleId>,
+	filters:             Vec<(FilterRule, Regex)>,
+}
+
+impl Rules {
+	fn compile(
+		settings: AutomodSettings,
+		moderator_roles: Vec<RoleId>,
+	) -> Self {
+		let filters = settings
+			.filters
+			.iter()
+			.filter_map(|rule| {
+				let regex = match rule.kind {
+					| FilterKind::Word => word_regex(&rule.pattern),
+					| FilterKind::Regex => user_regex(&rule.pattern),
+				};
+				match regex {
+					| Ok(regex) => Some((rule.clone(), regex)),
+					| Err(e) => {
+						println!(
+							"\x1b[33;1m[WARN]\x1b[0m Skipping automod filter `{}`: {}",
+							rule.pattern, e
+						);
+						None
+					},
+				}
+			})
+			.collect();
+
+		Rules {
+			settings,
+			moderator_roles,
+			filters,
+		}
+	}
+
+	/// The broken rule with the harshest action, if any
+	pub fn check(
+		&self,
+		content: &str,
+	) -> Option<&FilterRule> {
+		let normalized = normalize(content);
+
+		self.filters
+			.iter()
+			.filter(|(rule, regex)| match rule.kind {
+				| FilterKind::Word => regex.is_match(&normalized),
+				| FilterKind::Regex => regex.is_match(content),
+			})
+			.map(|(rule, _)| rule)
+			.max_by_key(|rule| rule.action.severity())
+	}
+}
+
+/// what a guild's rules were built from, `rules` is `None` for guilds without automod
+struct Cached {
+	versions: (u64, u64),
+	rules:    Option<Arc<Rules>>,
+}
+
+/// Compiled rules per guild, rebuilt after the automod or guild settings change
+#[derive(Default)]
+pub struct RuleCache {
+	guilds: Mutex<HashMap<GuildId, Cached>>,
+}
+
+fn load(guild_id: GuildId) -> Result<Option<Arc<Rules>>, BotError> {
+	let Some(settings) = automod_settings::stored_settings(guild_id)? else {
+		return Ok(None);
+	};
+	let moderator_roles = guild_settings::moderator_roles(guild_id)?;
+	Ok(Some(Arc::new(Rules::compile(settings, moderator_roles))))
+}
+
+impl RuleCache {
+	/// `None` when the guild never set up automod
+	pub fn get(
+		&self,
+		guild_id: GuildId,
+	) -> Option<Arc<Rules>> {
+		// read before the stores, so a save while loading makes the next message load again
+		let versions = (automod_settings::version(), guild_settings::version());
+		let previous = {
+			let guilds = self.guilds.lock().ok()?;
+			match guilds.get(&guild_id) {
+				| Some(cached) if cached.versions == versions => return cached.rules.clone(),
+				| Some(cached) => cached.rules.clone(),
+				| None => None,
+			}
+		};
+
+		match load(guild_id) {
+			| Ok(rules) => {
+				self.guilds.lock().ok()?.insert(guild_id, Cached {
+					versions,
+					rules: rules.clone(),
+				});
+				rules
+			},
+			| Err(e) => {
+				// a read error isn't the guild turning automod off, keep the rules it had
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't read the automod settings of {}: {}",
+					guild_id, e
+				);
+				previous
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn normalize_undoes_letter_swaps() {
+		assert_eq!(normalize("B4D"), "bad");
+		assert_eq!(normalize("$7@1n"), "stain");
+		assert_eq!(normalize("b\u{200b}a\u{feff}d"), "bad");
+	}
+
+	#[test]
+	fn word_regex_matches_spread_out_words() {
+		let regex = word_regex("bad").unwrap();
+		for text in ["bad", "a bad day", "b.a d", "baaaad", "B4D", "b_a_d"] {
+			assert!(regex.is_match(&normalize(text)), "{}", text);
+		}
+		for text in ["badge", "abad", "bed"] {
+			assert!(!regex.is_match(&normalize(text)), "{}", text);
+		}
+
+		let wildcard = word_regex("bad*").unwrap();
+		assert!(wildcard.is_match(&normalize("badge")));
+		assert!(!wildcard.is_match(&normalize("abadge")));
+	}
+}
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use regex::Regex;
+use serenity::all::{GuildId, Http};
+
+use crate::config::PHISHING_DOMAINS_PATH;
+use crate::utils::automod_settings::{AutomodAction, LinkRule, LinkSettings, domain_matches};
+
+/// used until the file in the data dir exists, then written there so it can be edited
+const BUNDLED_PHISHING_DOMAINS: &str = include_str!("phishing_domains.txt");
+/// resolved invites are remembered this long, they rarely change guilds
+const INVITE_TTL: Duration = Duration::from_secs(3600);
+/// how often the phishing list file is checked for changes
+const PHISHING_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// invites looked up per message, so a message full of invites can't stall automod
+const MAX_INVITES: usize = 5;
+
+static INVITE: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(
+		r"(?i)(?:https?://)?(?:www\.)?(?:discord(?:app)?\.com/invite|discord\.gg)/([a-z0-9-]+)",
+	)
+	.expect("valid invite regex")
+});
+static LINK: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"(?i)https?://([^\s/?#<>]+)").expect("valid link regex"));
+
+/// the host of a link's authority, without login info or port. `https://discord.com@evil.com`
+/// goes to evil.com
+fn host(authority: &str) -> String {
+	let host = authority.rsplit('@').next().unwrap_or(authority);
+	let host = host.split(':').next().unwrap_or(host);
+	host.trim_end_matches('.').to_lowercase()
+}
+
+fn parse_domains(list: &str) -> HashSet<String> {
+	list.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(str::to_lowercase)
+		.collect()
+}
+
+#[derive(Default)]
+struct Loaded {
+	modified: Option<SystemTime>,
+	/// `None` until the file was first looked at
+	checked:  Option<Instant>,
+	domains:  HashSet<String>,
+}
+
+/// Known phishing domains, read from [`PHISHING_DOMAINS_PATH`] again when the file changes. The
+/// file is looked at every [`PHISHING_CHECK_INTERVAL`] at most, not for every link
+#[derive(Default)]
+pub struct PhishingList {
+	loaded: Mutex<Loaded>,
+}
+
+impl PhishingList {
+	fn refresh(
+		&self,
+		loaded: &mut Loaded,
+	) {
+		if loaded
+			.checked
+			.is_some_and(|checked| checked.elapsed() < PHISHING_CHECK_INTERVAL)
+		{
+			return;
+		}
+		loaded.checked = Some(Instant::now());
+
+		let path = Path::new(PHISHING_DOMAINS_PATH);
+		let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+
+		if modified.is_none() {
+			if loaded.domains.is_empty() {
+				loaded.domains = parse_domains(BUNDLED_PHISHING_DOMAINS);
+				if let Err(e) = fs::write(path, BUNDLED_PHISHING_DOMAINS) {
+					println!(
+						"\x1b[33;1m[WARN]\x1b[0m Couldn't write the phishing list to `{}`: {}",
+						PHISHING_DOMAINS_PATH, e
+					);
+				}
+			}
+			return;
+		}
+		if modified == loaded.modified {
+			return;
+		}
+
+		match fs::read_to_string(path) {
+			| Ok(list) => {
+				loaded.domains = parse_domains(&list);
+				loaded.modified = modified;
+			},
+			| Err(e) => println!(
+				"\x1b[33;1m[WARN]\x1b[0m Couldn't read the phishing list at `{}`: {}",
+				PHISHING_DOMAINS_PATH, e
+			),
+		}
+	}
+
+	pub fn contains(
+		&self,
+		host: &str,
+	) -> bool {
+		let Ok(mut loaded) = self.loaded.lock() else {
+			return false;
+		};
+		self.refresh(&mut loaded);
+
+		// the host itself and every domain above it
+		let mut domain = host;
+		loop {
+			if loaded.domains.contains(domain) {
+				return true;
+			}
+			match domain.split_once('.') {
+				| Some((_, parent)) => domain = parent,
+				| None => return false,
+			}
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum Resolved {
+	Guild(GuildId),
+	/// expired or deleted, nobody can join through it
+	Dead,
+}
+
+/// A link automod acts on
+pub struct BadLink {
+	/// shown in the log
+	pub rule:   String,
+	/// given to the member as the warn or timeout reason
+	pub reason: &'static str,
+	pub action: AutomodAction,
+}
+
+/// Checks invites and links in messages, remembering resolved invites
+#[derive(Default)]
+pub struct LinkChecker {
+	phishing: PhishingList,
+	invites:  Mutex<HashMap<String, (Resolved, Instant)>>,
+}
+
+impl LinkChecker {
+	/// `None` when discord couldn't be asked, those invites are let through
+	async fn resolve(
+		&self,
+		http: &Http,
+		code: &str,
+	) -> Option<Resolved> {
+		if let Ok(mut invites) = self.invites.lock() {
+			invites.retain(|_, (_, at)| at.elapsed() < INVITE_TTL);
+			if let Some((resolved, _)) = invites.get(code) {
+				return Some(*resolved);
+			}
+		}
+
+		let resolved = match http.get_invite(code, false, false, None).await {
+			| Ok(invite) => match invite.guild {
+				| Some(guild) => Resolved::Guild(guild.id),
+				// group dm invites
+				| None => Resolved::Dead,
+			},
+			| Err(serenity::Error::Http(e)) if e.status_code().is_some_and(|s| s.as_u16() == 404) => {
+				Resolved::Dead
+			},
+			| Err(_) => return None,
+		};
+
+		if let Ok(mut invites) = self.invites.lock() {
+			invites.insert(code.to_string(), (resolved, Instant::now()));
+		}
+		Some(resolved)
+	}
+
+	/// The link with the harshest action, if any
+	pub async fn check(
+		&self,
+		http: &Http,
+		guild_id: GuildId,
+		settings: &LinkSettings,
+		content: &str,
+	) -> Option<BadLink> {
+		let mut found = vec![];
+
+		if let Some(action) = settings.action(LinkRule::Invites) {
+			let codes: HashSet<&str> = INVITE
+				.captures_iter(content)
+				.filter_map(|c| Some(c.get(1)?.as_str()))
+				.take(MAX_INVITES)
+				.collect();
+
+			for code in codes {
+				// only invites known to lead elsewhere, so a discord outage doesn't punish
+				// invites to this server
+				let foreign = match self.resolve(http, code).await {
+					| Some(Resolved::Guild(id)) => id != guild_id,
+					| Some(Resolved::Dead) | None => false,
+				};
+				if foreign {
+					found.push(BadLink {
+						rule: format!("Invite to another server (`discord.gg/{}`)", code),
+						reason: "Posted an invite to another server",
+						action,
+					});
+				}
+			}
+		}
+
+		// invites are only judged by where they lead
+		let without_invites = INVITE.replace_all(content, "");
+		let hosts: HashSet<String> = LINK
+			.captures_iter(&without_invites)
+			.filter_map(|c| Some(host(c.get(1)?.as_str())))
+			.collect();
+
+		for host in hosts {
+			let phishing = settings.phishing.is_some() && self.phishing.contains(&host);
+			let denied = settings
+				.denied_domains
+				.iter()
+				.any(|d| domain_matches(&host, d));
+			let unlisted = !settings
+				.allowed_domains
+				.iter()
+				.any(|d| domain_matches(&host, d));
+
+			let rules = [
+				(
+					LinkRule::Phishing,
+					phishing,
+					"Phishing domain",
+					"Posted a phishing link",
+				),
+				(
+					LinkRule::Denied,
+					denied,
+					"Denied domain",
+					"Posted a blocked link",
+				),
+				(
+					LinkRule::Unlisted,
+					unlisted,
+					"Domain not on the allow list",
+					"Posted a blocked link",
+				),
+			];
+			// the first rule that matches and is turned on
+			if let Some((action, label, reason)) = rules
+				.into_iter()
+				.filter(|(_, matched, ..)| *matched)
+				.find_map(|(rule, _, label, reason)| Some((settings.action(rule)?, label, reason)))
+			{
+				found.push(BadLink {
+					rule: format!("{} `{}`", label, host),
+					reason,
+					action,
+				});
+			}
+		}
+
+		found.into_iter().max_by_key(|link| link.action.severity())
+	}
+}
+
+/// Turns what a moderator typed into a bare domain, accepting full links too
+pub fn parse_domain(input: &str) -> Option<String> {
+	let input = input.trim();
+	let without_scheme = input
+		.split_once("://")
+		.map(|(_, rest)| rest)
+		.unwrap_or(input);
+	let authority = without_scheme
+		.split(['/', '?', '#'])
+		.next()
+		.unwrap_or(without_scheme);
+	let domain = host(authority);
+	let domain = domain.strip_prefix("www.").unwrap_or(&domain);
+
+	let valid = domain.contains('.')
+		&& domain
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+	valid.then(|| domain.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn host_drops_login_info_and_port() {
+		assert_eq!(host("example.com"), "example.com");
+		assert_eq!(host("Example.COM."), "example.com");
+		assert_eq!(host("example.com:8080"), "example.com");
+		assert_eq!(host("discord.com@evil.com"), "evil.com");
+		assert_eq!(host("user:pass@evil.com:443"), "evil.com");
+		assert_eq!(host("a@b@evil.com"), "evil.com");
+	}
+}
@@ -0,0 +1,359 @@
+pub mod filters;
+pub mod links;
+pub mod raid;
+pub mod spam;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use filters::{RuleCache, Rules};
+use links::LinkChecker;
+use poise::serenity_prelude::Context;
+use raid::RaidTracker;
+use serenity::all::{
+	ChannelId,
+	Color,
+	Event,
+	GuildId,
+	Http,
+	MessageId,
+	RawEventHandler,
+	RoleId,
+	User,
+};
+use spam::{Posted, SpamTracker};
+
+use crate::background::guild_logs::log_queue::LogQueue;
+use crate::background::guild_logs::{Log, log_channel};
+use crate::commands::moderation::mute::mute_member;
+use crate::commands::moderation::warn::add_warn;
+use crate::utils::automod_settings::{AutomodAction, AutomodSettings, FilterKind};
+use crate::utils::dates::format_duration;
+use crate::utils::guild_settings::LogType;
+use crate::utils::identity::Identity;
+use crate::utils::mention::Mentionable;
+use crate::utils::mod_actions::ModActions;
+
+/// Checks new and edited messages against each guild's automod rules, and joins for raids
+pub struct Automod {
+	pub rules:    RuleCache,
+	pub links:    LinkChecker,
+	pub spam:     SpamTracker,
+	pub raids:    RaidTracker,
+	/// automod actions are logged through the same queue as the guild logs
+	pub queue:    Arc<LogQueue>,
+	/// shared with commands, so the logs say automod muted someone
+	pub actions:  Arc<ModActions>,
+	pub identity: Arc<Identity>,
+}
+
+/// The parts of a created or edited message automod looks at
+struct Checked<'a> {
+	guild_id:   GuildId,
+	channel_id: ChannelId,
+	id:         MessageId,
+	author:     &'a User,
+	roles:      Vec<RoleId>,
+	content:    &'a str,
+	mentions:   usize,
+	/// edits are only checked against filters, they don't count towards spam
+	edited:     bool,
+}
+
+/// A rule a message broke
+struct Violation {
+	/// shown in the log, can include the rule itself
+	rule:    String,
+	/// given to the member as the warn or timeout reason
+	reason:  &'static str,
+	action:  AutomodAction,
+	/// earlier messages that were part of it, deleted along with this one
+	earlier: Vec<(ChannelId, MessageId)>,
+}
+
+fn is_exempt(
+	rules: &Rules,
+	message: &Checked<'_>,
+) -> bool {
+	if rules.settings.exempt_channels.contains(&message.channel_id) {
+		return true;
+	}
+
+	message.roles.iter().any(|role| {
+		rules.settings.exempt_roles.contains(role) || rules.moderator_roles.contains(role)
+	})
+}
+
+impl Automod {
+	async fn check(
+		&self,
+		http: &Http,
+		message: Checked<'_>,
+	) {
+		if message.author.bot {
+			return;
+		}
+		let Some(rules) = self.rules.get(message.guild_id) else {
+			return;
+		};
+		if is_exempt(&rules, &message) {
+			return;
+		}
+
+		if !message.content.is_empty()
+			&& let Some(rule) = rules.check(message.content)
+		{
+			let (label, reason) = match rule.kind {
+				| FilterKind::Word => ("Word", "Used a blocked word"),
+				| FilterKind::Regex => ("Pattern", "Matched a blocked pattern"),
+			};
+			let violation = Violation {
+				rule: format!("{} `{}`", label, rule.pattern),
+				reason,
+				action: rule.action,
+				earlier: vec![],
+			};
+			self.enforce(http, &rules.settings, &message, violation)
+				.await;
+			return;
+		}
+
+		if let Some(link) = self
+			.links
+			.check(
+				http,
+				message.guild_id,
+				&rules.settings.links,
+				message.content,
+			)
+			.await
+		{
+			let violation = Violation {
+				rule:    link.rule,
+				reason:  link.reason,
+				action:  link.action,
+				earlier: vec![],
+			};
+			self.enforce(http, &rules.settings, &message, violation)
+				.await;
+			return;
+		}
+
+		if !rules.settings.spam.enabled || message.edited {
+			return;
+		}
+		let posted = Posted {
+			guild_id: message.guild_id,
+			channel:  message.channel_id,
+			id:       message.id,
+			author:   message.author.id,
+			content:  message.content,
+			mentions: message.mentions,
+		};
+		if let Some(spam) = self.spam.check(&rules.settings.spam, &posted) {
+			let violation = Violation {
+				rule:    format!("{} (strike {})", spam.rule, spam.strikes),
+				reason:  "Spam",
+				action:  rules.settings.spam.action(spam.strikes),
+				earlier: spam.messages,
+			};
+			self.enforce(http, &rules.settings, &message, violation)
+				.await;
+		}
+	}
+
+	async fn enforce(
+		&self,
+		http: &Http,
+		settings: &AutomodSettings,
+		message: &Checked<'_>,
+		violation: Violation,
+	) {
+		let mut problems = vec![];
+		let reason = format!("Automod: {}", violation.reason);
+
+		if violation.action.deletes() {
+			if let Err(e) = message.channel_id.delete_message(http, message.id).await {
+				problems.push(format!("Couldn't delete the message: {}", e));
+			}
+			if let Err(e) = delete_earlier(http, &violation.earlier).await {
+				problems.push(format!("Couldn't delete earlier messages: {}", e));
+			}
+		}
+
+		match violation.action {
+			| AutomodAction::Warn => {
+				if let Err(e) = add_warn(message.author, message.guild_id, &reason).await {
+					problems.push(format!("Couldn't save the warn: {}", e));
+				}
+			},
+			| AutomodAction::Mute => {
+				let secs = settings.mute_duration().as_secs();
+				let muted = match self.identity.require_id() {
+					| Ok(bot_id) => {
+						mute_member(
+							http,
+							&self.actions,
+							message.guild_id,
+							message.author,
+							bot_id,
+							Some(secs),
+							&reason,
+						)
+						.await
+					},
+					| Err(e) => Err(e),
+				};
+				match muted {
+					| Ok(notes) if !notes.is_empty() => problems.push(notes.trim().to_string()),
+					| Ok(_) => {},
+					| Err(e) => problems.push(format!(
+						"Couldn't mute for {}: {}",
+						format_duration(secs),
+						e
+					)),
+				}
+			},
+			| AutomodAction::Kick => {
+				if let Err(e) = message
+					.guild_id
+					.kick_with_reason(http, message.author.id, &reason)
+					.await
+				{
+					problems.push(format!("Couldn't kick: {}", e));
+				}
+			},
+			| AutomodAction::Delete | AutomodAction::Log => {},
+		}
+
+		let Some(channel) = log_channel(message.guild_id, LogType::Automod) else {
+			return;
+		};
+
+		let mut fields = vec![
+			("User", message.author.mention(), true),
+			("Channel", format!("<#{}>", message.channel_id), true),
+			("Action", violation.action.label().to_string(), true),
+			("Rule", violation.rule, false),
+			("Content", message.content.to_string(), false),
+		];
+		if !problems.is_empty() {
+			fields.push(("Problems", problems.join("\n"), false));
+		}
+
+		self.queue
+			.push(Log::new(channel, "Automod", Color::ORANGE, fields));
+	}
+}
+
+/// deletes in bulk per channel where there's more than one
+async fn delete_earlier(
+	http: &Http,
+	messages: &[(ChannelId, MessageId)],
+) -> Result<(), serenity::Error> {
+	let mut by_channel: HashMap<ChannelId, Vec<MessageId>> = HashMap::new();
+	for (channel, id) in messages {
+		by_channel.entry(*channel).or_default().push(*id);
+	}
+
+	for (channel, ids) in by_channel {
+		match ids.as_slice() {
+			| [id] => channel.delete_message(http, *id).await?,
+			| ids => channel.delete_messages(http, ids).await?,
+		}
+	}
+	Ok(())
+}
+
+#[serenity::async_trait]
+impl RawEventHandler for Automod {
+	async fn raw_event(
+		&self,
+		ctx: Context,
+		event: Event,
+	) {
+		let member_roles = |guild_id: GuildId, user_id| {
+			ctx.cache
+				.guild(guild_id)
+				.and_then(|guild| Some(guild.members.get(&user_id)?.roles.clone()))
+				.unwrap_or_default()
+		};
+
+		match event {
+			| Event::MessageCreate(event) => {
+				let message = &event.message;
+				let Some(guild_id) = message.guild_id else {
+					return;
+				};
+				let roles = match &message.member {
+					| Some(member) => member.roles.clone(),
+					| None => member_roles(guild_id, message.author.id),
+				};
+
+				self.check(&ctx.http, Checked {
+					guild_id,
+					channel_id: message.channel_id,
+					id: message.id,
+					author: &message.author,
+					roles,
+					content: &message.content,
+					mentions: message.mentions.len()
+						+ message.mention_roles.len()
+						+ usize::from(message.mention_everyone),
+					edited: false,
+				})
+				.await;
+			},
+			// someone could post something harmless and edit the bad part in
+			| Event::MessageUpdate(event) => {
+				// embeds loading in also sends an update, those never have an edit timestamp
+				let (Some(guild_id), Some(author), Some(content), Some(_)) = (
+					event.guild_id,
+					&event.author,
+					&event.content,
+					event.edited_timestamp,
+				) else {
+					return;
+				};
+				let roles = match event.member.flatten() {
+					| Some(member) => member.roles,
+					| None => member_roles(guild_id, author.id),
+				};
+
+				self.check(&ctx.http, Checked {
+					guild_id,
+					channel_id: event.channel_id,
+					id: event.id,
+					author,
+					roles,
+					content,
+					mentions: 0,
+					edited: true,
+				})
+				.await;
+			},
+			| Event::GuildMemberAdd(event) => {
+				let Some(rules) = self.rules.get(event.member.guild_id) else {
+					return;
+				};
+				let settings = &rules.settings.raid;
+				if !settings.enabled {
+					return;
+				}
+
+				if let Some(trigger) = self.raids.join(settings, &event.member) {
+					self.raids
+						.respond(
+							&ctx.http,
+							&self.queue,
+							event.member.guild_id,
+							settings,
+							trigger,
+						)
+						.await;
+				}
+			},
+			| _ => {},
+		}
+	}
+}
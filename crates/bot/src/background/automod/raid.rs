@@ -0,0 +1,322 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serenity::all::{
+	Color,
+	CreateEmbed,
+	CreateMessage,
+	EditGuild,
+	GuildId,
+	Http,
+	Member,
+	Timestamp,
+	VerificationLevel,
+};
+
+use crate::background::guild_logs::log_queue::LogQueue;
+use crate::background::guild_logs::{Log, log_channel};
+use crate::utils::automod_settings::{
+	RaidResponse,
+	RaidSettings,
+	RaidState,
+	raid_state,
+	set_raid_state,
+};
+use crate::utils::channel_locks;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{LogType, report_channel, report_notification_role};
+
+/// Recent joins per guild, to spot bursts
+#[derive(Default)]
+pub struct RaidTracker {
+	/// when each member joined and whether they looked like a throwaway account
+	joins:      Mutex<HashMap<GuildId, VecDeque<(Instant, bool)>>>,
+	/// guilds a response is running for, so a burst doesn't start it twice
+	responding: Mutex<HashSet<GuildId>>,
+}
+
+/// new accounts that never set an avatar are what raid bots usually look like
+fn is_suspicious(
+	settings: &RaidSettings,
+	member: &Member,
+) -> bool {
+	let age = Timestamp::now().unix_timestamp() - member.user.created_at().unix_timestamp();
+	// stored settings aren't bounded by the command, anything too big counts every account as new
+	let new_for = settings
+		.new_account_days
+		.checked_mul(86400)
+		.and_then(|secs| i64::try_from(secs).ok())
+		.unwrap_or(i64::MAX);
+	member.user.avatar.is_none() && age < new_for
+}
+
+impl RaidTracker {
+	/// Counts a join, returning what set off a raid when this join did
+	pub fn join(
+		&self,
+		settings: &RaidSettings,
+		member: &Member,
+	) -> Option<String> {
+		let guild_id = member.guild_id;
+		// joins during a raid are already handled by the response
+		match raid_state(guild_id) {
+			| Ok(None) => {},
+			| Ok(Some(_)) => return None,
+			| Err(e) => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't read the raid state of {}: {}",
+					guild_id, e
+				);
+				return None;
+			},
+		}
+
+		let mut joins = self.joins.lock().ok()?;
+		let window = Duration::from_secs(settings.join_seconds.max(1));
+		let now = Instant::now();
+
+		let recent = joins.entry(guild_id).or_default();
+		recent.retain(|(at, _)| now.duration_since(*at) < window);
+		recent.push_back((now, is_suspicious(settings, member)));
+
+		let suspicious = recent.iter().filter(|(_, s)| *s).count();
+		let trigger = if settings.max_joins != 0 && recent.len() > settings.max_joins as usize {
+			format!(
+				"{} joins in {} seconds",
+				recent.len(),
+				settings.join_seconds
+			)
+		} else if settings.max_suspicious != 0 && suspicious > settings.max_suspicious as usize {
+			format!(
+				"{} new accounts without an avatar joined in {} seconds",
+				suspicious, settings.join_seconds
+			)
+		} else {
+			return None;
+		};
+
+		if !self.responding.lock().ok()?.insert(guild_id) {
+			return None;
+		}
+		joins.remove(&guild_id);
+		Some(trigger)
+	}
+
+	/// Takes the guild's raid response and lets moderators know
+	pub async fn respond(
+		&self,
+		http: &Http,
+		queue: &LogQueue,
+		guild_id: GuildId,
+		settings: &RaidSettings,
+		trigger: String,
+	) {
+		let mut state = RaidState {
+			started_at:            Timestamp::now().unix_timestamp(),
+			trigger:               trigger.clone(),
+			previous_verification: None,
+			locked_channels:       vec![],
+		};
+		let mut problems = vec![];
+
+		// saved before anything changes so `/raid end` works even if this stops halfway
+		if let Err(e) = set_raid_state(guild_id, Some(&state)) {
+			problems.push(format!("Couldn't save the raid: {}", e));
+		}
+
+		match settings.response {
+			| RaidResponse::Alert => {},
+			| RaidResponse::Verification => match raise_verification(http, guild_id).await {
+				| Ok(previous) => state.previous_verification = previous,
+				| Err(e) => problems.push(format!("Couldn't raise the verification level: {}", e)),
+			},
+			| RaidResponse::Lockdown => match guild_id.channels(http).await {
+				| Ok(channels) => {
+					for channel in channels.values().filter(|c| channel_locks::is_lockable(c)) {
+						match channel_locks::lock(http, channel, None, true, None).await {
+							| Ok(true) => state.locked_channels.push(channel.id),
+							| Ok(false) => {},
+							| Err(e) => problems.push(format!("<#{}>: {}", channel.id, e)),
+						}
+					}
+				},
+				| Err(e) => problems.push(format!("Couldn't get the channels: {}", e)),
+			},
+		}
+
+		// with nothing to undo there's nothing for `/raid end` to do either, and a stored state
+		// would keep detection off until someone ran it. alert-only responses always end up here
+		let changed = state.previous_verification.is_some() || !state.locked_channels.is_empty();
+		let saved = set_raid_state(guild_id, changed.then_some(&state));
+		if let Err(e) = saved {
+			problems.push(format!("Couldn't save the raid: {}", e));
+		}
+		if let Ok(mut responding) = self.responding.lock() {
+			responding.remove(&guild_id);
+		}
+
+		let taken = match settings.response {
+			| RaidResponse::Lockdown => {
+				format!("Locked {} channels", state.locked_channels.len())
+			},
+			| response => response.label().to_string(),
+		};
+
+		alert(http, guild_id, &trigger, &taken, changed, &problems).await;
+
+		if let Some(channel) = log_channel(guild_id, LogType::Automod) {
+			let mut fields = vec![("Trigger", trigger, false), ("Response", taken, false)];
+			if !problems.is_empty() {
+				fields.push(("Problems", problems.join("\n"), false));
+			}
+			queue.push(Log::new(channel, "Raid detected", Color::RED, fields));
+		}
+	}
+}
+
+/// Returns the level from before, `None` when it was already high enough
+async fn raise_verification(
+	http: &Http,
+	guild_id: GuildId,
+) -> Result<Option<VerificationLevel>, BotError> {
+	let current = guild_id.to_partial_guild(http).await?.verification_level;
+	if current >= VerificationLevel::High {
+		return Ok(None);
+	}
+
+	guild_id
+		.edit(
+			http,
+			EditGuild::new()
+				.verification_level(VerificationLevel::High)
+				.audit_log_reason("Automod: raid detected"),
+		)
+		.await
+		.map_err(|e| {
+			BotError::from(e).with_permission_denied("I need Manage Server to change it.")
+		})?;
+	Ok(Some(current))
+}
+
+/// sent to the report channel, pinging the report role like reports do
+async fn alert(
+	http: &Http,
+	guild_id: GuildId,
+	trigger: &str,
+	taken: &str,
+	undoable: bool,
+	problems: &[String],
+) {
+	let channel_id = match report_channel(Some(guild_id)) {
+		| Ok(Some(channel_id)) => channel_id,
+		| Ok(None) => {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Raid detected in {} but it has no report channel",
+				guild_id
+			);
+			return;
+		},
+		| Err(e) => {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Raid detected in {} but its report channel couldn't be read: {}",
+				guild_id, e
+			);
+			return;
+		},
+	};
+
+	let description = match undoable {
+		| true => "Use `/raid end` once it's over to undo the response.",
+		| false => "Nothing was changed, so there's nothing to undo.",
+	};
+	let mut embed = CreateEmbed::default()
+		.title("Raid Detected")
+		.description(description)
+		.field("Trigger", trigger, false)
+		.field("Response", taken, false)
+		.color(Color::RED)
+		.timestamp(Timestamp::now());
+	if !problems.is_empty() {
+		embed = embed.field("Problems", problems.join("\n"), false);
+	}
+
+	let mut message = CreateMessage::new().embed(embed);
+	if let Ok(Some(role_id)) = report_notification_role(Some(guild_id)) {
+		message = message.content(format!("<@&{}>", role_id));
+	}
+
+	if let Err(e) = channel_id.send_message(http, message).await {
+		println!(
+			"\x1b[33;1m[WARN]\x1b[0m Couldn't send the raid alert in {}: {}",
+			guild_id, e
+		);
+	}
+}
+
+/// What `/raid end` put back
+pub struct Ended {
+	pub verification_restored: bool,
+	pub unlocked:              usize,
+	pub problems:              Vec<String>,
+	/// false when something couldn't be put back, the raid stays stored so ending it again retries
+	pub finished:              bool,
+}
+
+/// Undoes what the raid response changed and forgets the raid once everything is back
+pub async fn end(
+	http: &Http,
+	guild_id: GuildId,
+) -> Result<Ended, BotError> {
+	let mut state = raid_state(guild_id)?.ok_or(BotError::NotFound(
+		"There's no raid response to end.".into(),
+	))?;
+	let mut ended = Ended {
+		verification_restored: false,
+		unlocked:              0,
+		problems:              vec![],
+		finished:              false,
+	};
+
+	if let Some(level) = state.previous_verification {
+		match guild_id
+			.edit(
+				http,
+				EditGuild::new()
+					.verification_level(level)
+					.audit_log_reason("Raid ended"),
+			)
+			.await
+		{
+			| Ok(_) => {
+				ended.verification_restored = true;
+				state.previous_verification = None;
+			},
+			| Err(e) => ended
+				.problems
+				.push(format!("Couldn't put the verification level back: {}", e)),
+		}
+	}
+
+	// channels that fail keep their stored lock, so nothing about them is lost
+	let mut still_locked = vec![];
+	for channel_id in state.locked_channels {
+		match channel_locks::unlock(http, guild_id, channel_id).await {
+			| Ok(true) => ended.unlocked += 1,
+			// already unlocked by hand
+			| Ok(false) => {},
+			| Err(e) => {
+				ended.problems.push(format!("<#{}>: {}", channel_id, e));
+				still_locked.push(channel_id);
+			},
+		}
+	}
+	state.locked_channels = still_locked;
+
+	ended.finished = state.previous_verification.is_none() && state.locked_channels.is_empty();
+	match ended.finished {
+		| true => set_raid_state(guild_id, None)?,
+		| false => set_raid_state(guild_id, Some(&state))?,
+	}
+	Ok(ended)
+}
@@ -0,0 +1,278 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serenity::all::{ChannelId, GuildId, MessageId, UserId};
+
+use super::filters::normalize;
+use crate::utils::automod_settings::SpamSettings;
+
+/// how far back repeated messages are looked for
+const DUPLICATE_WINDOW: Duration = Duration::from_secs(60);
+/// past this many tracked members, ones that went quiet are dropped
+const MAX_TRACKED: usize = 10_000;
+
+static CUSTOM_EMOJI: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"<a?:\w+:\d+>").expect("valid emoji regex"));
+
+/// custom emojis plus anything in the unicode emoji blocks
+fn emoji_count(content: &str) -> usize {
+	let custom = CUSTOM_EMOJI.find_iter(content).count();
+	let unicode = CUSTOM_EMOJI
+		.replace_all(content, "")
+		.chars()
+		.filter(|c| matches!(*c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF))
+		.count();
+	custom + unicode
+}
+
+fn content_hash(content: &str) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	normalize(content.trim()).hash(&mut hasher);
+	hasher.finish()
+}
+
+/// A message as far as spam checks care
+pub struct Posted<'a> {
+	pub guild_id: GuildId,
+	pub channel:  ChannelId,
+	pub id:       MessageId,
+	pub author:   UserId,
+	pub content:  &'a str,
+	/// users, roles and everyone pings
+	pub mentions: usize,
+}
+
+struct Sent {
+	at:      Instant,
+	channel: ChannelId,
+	id:      MessageId,
+	/// `None` for messages without text, those aren't compared
+	hash:    Option<u64>,
+}
+
+#[derive(Default)]
+struct History {
+	sent:    VecDeque<Sent>,
+	strikes: VecDeque<Instant>,
+}
+
+/// A broken spam limit
+pub struct Spam {
+	/// shown in the log
+	pub rule:     String,
+	/// earlier messages that were part of the spam, deleted along with the current one
+	pub messages: Vec<(ChannelId, MessageId)>,
+	/// strikes the member has now, including this one
+	pub strikes:  usize,
+}
+
+/// Recent messages and strikes per member, to spot floods across several messages
+#[derive(Default)]
+pub struct SpamTracker {
+	users: Mutex<HashMap<(GuildId, UserId), History>>,
+}
+
+impl SpamTracker {
+	pub fn check(
+		&self,
+		settings: &SpamSettings,
+		message: &Posted<'_>,
+	) -> Option<Spam> {
+		let Ok(mut users) = self.users.lock() else {
+			return None;
+		};
+		let window = Duration::from_secs(settings.per_seconds.max(1));
+		// stored settings aren't bounded by the command, too long just never forgets
+		let strike_window = settings
+			.strike_minutes
+			.checked_mul(60)
+			.map_or(Duration::MAX, Duration::from_secs);
+
+		if users.len() > MAX_TRACKED {
+			users.retain(|_, history| {
+				history
+					.sent
+					.back()
+					.is_some_and(|sent| sent.at.elapsed() < window.max(DUPLICATE_WINDOW))
+					|| history
+						.strikes
+						.back()
+						.is_some_and(|at| at.elapsed() < strike_window)
+			});
+		}
+
+		let history = users.entry((message.guild_id, message.author)).or_default();
+		let now = Instant::now();
+		let hash = (!message.content.trim().is_empty()).then(|| content_hash(message.content));
+
+		history
+			.sent
+			.retain(|sent| now.duration_since(sent.at) < window.max(DUPLICATE_WINDOW));
+		history
+			.strikes
+			.retain(|at| now.duration_since(*at) < strike_window);
+		history.sent.push_back(Sent {
+			at: now,
+			channel: message.channel,
+			id: message.id,
+			hash,
+		});
+
+		let over = |limit: u32, count: usize| limit != 0 && count > limit as usize;
+		let lines = message.content.matches('\n').count() + 1;
+		let emojis = emoji_count(message.content);
+		let recent: Vec<&Sent> = history
+			.sent
+			.iter()
+			.filter(|sent| now.duration_since(sent.at) < window)
+			.collect();
+		let duplicates: Vec<&Sent> = match hash {
+			| Some(hash) => history
+				.sent
+				.iter()
+				.filter(|sent| sent.hash == Some(hash))
+				.collect(),
+			| None => vec![],
+		};
+
+		let (rule, earlier) = if over(settings.max_mentions, message.mentions) {
+			(
+				format!("{} mentions in one message", message.mentions),
+				vec![],
+			)
+		} else if over(settings.max_emojis, emojis) {
+			(format!("{} emojis in one message", emojis), vec![])
+		} else if over(settings.max_newlines, lines) {
+			(format!("{} lines in one message", lines), vec![])
+		} else if over(settings.max_messages, recent.len()) {
+			(
+				format!(
+					"{} messages in {} seconds",
+					recent.len(),
+					settings.per_seconds
+				),
+				recent,
+			)
+		} else if over(settings.max_duplicates, duplicates.len()) {
+			(
+				format!("Same message {} times in a minute", duplicates.len()),
+				duplicates,
+			)
+		} else {
+			return None;
+		};
+
+		let messages = earlier
+			.iter()
+			.filter(|sent| sent.id != message.id)
+			.map(|sent| (sent.channel, sent.id))
+			.collect();
+
+		// counting starts over, otherwise every message after a flood would be another strike
+		history.sent.clear();
+		history.strikes.push_back(now);
+
+		Some(Spam {
+			rule: format!("Spam: {}", rule),
+			messages,
+			strikes: history.strikes.len(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn posted(
+		id: u64,
+		content: &str,
+	) -> Posted<'_> {
+		Posted {
+			guild_id: GuildId::new(1),
+			channel: ChannelId::new(2),
+			id: MessageId::new(id),
+			author: UserId::new(3),
+			content,
+			mentions: 0,
+		}
+	}
+
+	#[test]
+	fn floods_take_the_earlier_messages_along() {
+		let tracker = SpamTracker::default();
+		let settings = SpamSettings::default();
+
+		for id in 1..=5 {
+			assert!(
+				tracker
+					.check(&settings, &posted(id, &id.to_string()))
+					.is_none()
+			);
+		}
+		let spam = tracker.check(&settings, &posted(6, "6")).unwrap();
+		assert_eq!(spam.rule, "Spam: 6 messages in 5 seconds");
+		assert_eq!(spam.messages.len(), 5);
+		assert!(
+			!spam
+				.messages
+				.contains(&(ChannelId::new(2), MessageId::new(6)))
+		);
+		assert_eq!(spam.strikes, 1);
+
+		// the flood was already punished
+		assert!(tracker.check(&settings, &posted(7, "7")).is_none());
+	}
+
+	#[test]
+	fn repeated_messages_are_spam() {
+		let tracker = SpamTracker::default();
+		let settings = SpamSettings::default();
+
+		for id in 1..=3 {
+			assert!(tracker.check(&settings, &posted(id, "hello")).is_none());
+		}
+		// normalized, so swapped letters don't make it a new message
+		let spam = tracker.check(&settings, &posted(4, "H3LLO")).unwrap();
+		assert_eq!(spam.rule, "Spam: Same message 4 times in a minute");
+		assert_eq!(spam.messages.len(), 3);
+	}
+
+	#[test]
+	fn single_message_limits() {
+		let tracker = SpamTracker::default();
+		let mut settings = SpamSettings::default();
+
+		let mut pings = posted(1, "hi");
+		pings.mentions = 6;
+		let spam = tracker.check(&settings, &pings).unwrap();
+		assert_eq!(spam.rule, "Spam: 6 mentions in one message");
+		assert!(spam.messages.is_empty());
+
+		let emojis = "😀".repeat(6) + &"<:cow:123>".repeat(5);
+		let spam = tracker.check(&settings, &posted(2, &emojis)).unwrap();
+		assert_eq!(spam.rule, "Spam: 11 emojis in one message");
+		assert_eq!(spam.strikes, 2);
+
+		// 0 turns a check off
+		settings.max_mentions = 0;
+		pings.id = MessageId::new(3);
+		assert!(tracker.check(&settings, &pings).is_none());
+	}
+
+	#[test]
+	fn members_are_tracked_apart() {
+		let tracker = SpamTracker::default();
+		let settings = SpamSettings::default();
+
+		for id in 1..=5 {
+			let mut message = posted(id, "hi there");
+			message.author = UserId::new(id);
+			assert!(tracker.check(&settings, &message).is_none());
+		}
+	}
+}
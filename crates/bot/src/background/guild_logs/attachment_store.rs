@@ -0,0 +1,175 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ring::{digest, hmac};
+use serenity::all::Attachment;
+
+use crate::config::{ATTACHMENTS_DIR, config};
+
+/// downloads trim the store as soon as they push it over the quota, this is only a backstop for
+/// what they miss, like files copied in by hand
+const QUOTA_INTERVAL: Duration = Duration::from_secs(600);
+/// trimming goes this far under the quota so the next few downloads don't read the whole store
+/// again, in percent
+const TRIM_TO: u64 = 90;
+
+/// bytes in the store, counted by [`enforce_quota`] and kept up to date by [`store`]
+static STORED: AtomicU64 = AtomicU64::new(0);
+/// only one trim runs at a time, downloads finishing together would all read the store
+static TRIMMING: AtomicBool = AtomicBool::new(false);
+
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// keeps file names url safe, the api checks the signature against the name in the path
+fn sanitize(filename: &str) -> String {
+	filename
+		.chars()
+		.map(
+			|c| match c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+				| true => c,
+				| false => '_',
+			},
+		)
+		.collect()
+}
+
+/// Downloads an attachment into the store, named by the sha256 of its content so reposts of the
+/// same file are only kept once. Returns the hash, or `None` if it's too big or failed
+pub async fn archive(attachment: &Attachment) -> Option<String> {
+	if u64::from(attachment.size) > config().attachment_max_size {
+		return None;
+	}
+
+	let bytes = match attachment.download().await {
+		| Ok(bytes) => bytes,
+		| Err(e) => {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Couldn't download attachment {}: {}",
+				attachment.url, e
+			);
+			return None;
+		},
+	};
+
+	let hash = hex(digest::digest(&digest::SHA256, &bytes).as_ref());
+	let path = Path::new(ATTACHMENTS_DIR).join(&hash);
+
+	let stored = tokio::task::spawn_blocking(move || store(&path, &bytes))
+		.await
+		.unwrap_or_else(|e| Err(io::Error::other(e)));
+
+	if let Err(e) = stored {
+		println!(
+			"\x1b[33;1m[WARN]\x1b[0m Couldn't store attachment {}: {}",
+			attachment.filename, e
+		);
+		return None;
+	}
+
+	if STORED.load(Ordering::Relaxed) > config().attachment_quota {
+		tokio::task::spawn_blocking(enforce_quota);
+	}
+
+	Some(hash)
+}
+
+fn store(
+	path: &Path,
+	bytes: &[u8],
+) -> io::Result<()> {
+	match path.exists() {
+		// bumped so the quota evicts it last
+		| true => File::options()
+			.append(true)
+			.open(path)
+			.and_then(|file| file.set_modified(SystemTime::now())),
+		| false => {
+			fs::create_dir_all(ATTACHMENTS_DIR).and_then(|_| fs::write(path, bytes))?;
+			STORED.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+			Ok(())
+		},
+	}
+}
+
+/// Keeps the store within `ATTACHMENT_QUOTA_MB` forever, starting with a trim that counts what's
+/// already stored
+pub async fn run() {
+	let mut interval = tokio::time::interval(QUOTA_INTERVAL);
+
+	loop {
+		interval.tick().await;
+		let _ = tokio::task::spawn_blocking(enforce_quota).await;
+	}
+}
+
+fn enforce_quota() {
+	if TRIMMING.swap(true, Ordering::AcqRel) {
+		return;
+	}
+	trim();
+	TRIMMING.store(false, Ordering::Release);
+}
+
+/// Deletes the least recently stored files once the store is over `ATTACHMENT_QUOTA_MB`, until
+/// it's back under [`TRIM_TO`] percent of it. The whole directory is read to do so
+fn trim() {
+	let Ok(entries) = fs::read_dir(ATTACHMENTS_DIR) else {
+		return;
+	};
+
+	let mut files: Vec<_> = entries
+		.flatten()
+		.filter_map(|entry| {
+			let metadata = entry.metadata().ok()?;
+			Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+		})
+		.collect();
+
+	let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+	if total > config().attachment_quota {
+		let target = config().attachment_quota / 100 * TRIM_TO;
+		files.sort_by_key(|(modified, _, _)| *modified);
+		for (_, size, path) in files {
+			if total <= target {
+				break;
+			}
+			if fs::remove_file(&path).is_ok() {
+				total -= size;
+			}
+		}
+	}
+
+	STORED.store(total, Ordering::Relaxed);
+}
+
+/// A link to a stored attachment through the api, valid for `ATTACHMENT_LINK_HOURS`
+pub fn link(
+	hash: &str,
+	filename: &str,
+) -> Option<String> {
+	let secret = config().attachment_secret.as_ref()?;
+
+	let expires = SystemTime::now()
+		.checked_add(config().attachment_link_ttl)?
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or(Duration::ZERO)
+		.as_secs();
+	let name = sanitize(filename);
+
+	let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+	let signature = hmac::sign(&key, format!("{}/{}/{}", hash, name, expires).as_bytes());
+
+	Some(format!(
+		"{}/attachments/{}/{}?expires={}&sig={}",
+		config().api_url.trim_end_matches('/'),
+		hash,
+		name,
+		expires,
+		hex(signature.as_ref())
+	))
+}
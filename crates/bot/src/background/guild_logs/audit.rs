@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use serenity::all::audit_log::{Action, MemberAction};
+use serenity::all::{GuildId, Http, Timestamp, UserId};
+
+use crate::utils::mention::Mentionable;
+use crate::utils::mod_actions::{ModAction, ModActionKind, ModActions};
+
+/// audit entries older than this belong to an earlier action
+const MAX_ENTRY_AGE: i64 = 30;
+
+/// Who did an action, from a bot command or the audit log
+pub struct Responsible {
+	pub moderator: UserId,
+	pub reason:    Option<String>,
+}
+
+impl From<ModAction> for Responsible {
+	fn from(action: ModAction) -> Self {
+		Responsible {
+			moderator: action.moderator,
+			reason:    action.reason,
+		}
+	}
+}
+
+fn audit_action(kind: ModActionKind) -> Action {
+	Action::Member(match kind {
+		| ModActionKind::Ban => MemberAction::BanAdd,
+		| ModActionKind::Unban => MemberAction::BanRemove,
+		| ModActionKind::Kick => MemberAction::Kick,
+		| ModActionKind::Timeout => MemberAction::Update,
+		| ModActionKind::RoleUpdate => MemberAction::RoleUpdate,
+	})
+}
+
+/// Finds who did `kind` to `target`, checking `attempts` times since discord can write the audit
+/// entry after sending the event
+pub async fn find_responsible(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	target: UserId,
+	kind: ModActionKind,
+	attempts: u32,
+) -> Option<Responsible> {
+	for attempt in 0..attempts {
+		if attempt > 0 {
+			tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+		}
+
+		if let Some(action) = actions.take(guild_id, target, kind) {
+			return Some(action.into());
+		}
+
+		let logs = match guild_id
+			.audit_logs(http, Some(audit_action(kind)), None, None, Some(10))
+			.await
+		{
+			| Ok(logs) => logs,
+			// missing View Audit Log, retrying won't help
+			| Err(serenity::Error::Http(e)) if e.status_code().is_some_and(|s| s.as_u16() == 403) =>
+			{
+				return None;
+			},
+			// rate limits and outages can pass before the next attempt
+			| Err(_) => continue,
+		};
+
+		let now = Timestamp::now().unix_timestamp();
+		let entry = logs.entries.into_iter().find(|entry| {
+			entry.target_id.map(|id| id.get()) == Some(target.get())
+				&& now - entry.id.created_at().unix_timestamp() <= MAX_ENTRY_AGE
+		});
+
+		if let Some(entry) = entry {
+			// the command that did this may still be between its request and recording
+			if let Some(action) = actions.take(guild_id, target, kind) {
+				return Some(action.into());
+			}
+			return Some(Responsible {
+				moderator: entry.user_id,
+				reason:    entry.reason,
+			});
+		}
+	}
+
+	None
+}
+
+/// "Moderator" and "Reason" fields for a log, `None` when it couldn't be found
+pub fn responsible_fields(responsible: &Option<Responsible>) -> Vec<(&'static str, String, bool)> {
+	match responsible {
+		| Some(responsible) => vec![
+			("Moderator", responsible.moderator.mention(), true),
+			(
+				"Reason",
+				responsible
+					.reason
+					.clone()
+					.unwrap_or("No reason provided".to_string()),
+				false,
+			),
+		],
+		| None => vec![("Moderator", "*Unknown*".to_string(), true)],
+	}
+}
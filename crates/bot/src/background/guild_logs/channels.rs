@@ -0,0 +1,116 @@
+use serenity::all::{Color, GuildChannel};
+
+use super::diff::{Field, diff_fields, diff_overwrites, show_channel, show_option};
+use super::ignore::Subject;
+use super::structure_cache::StructureCache;
+use super::{Log, log_channel};
+use crate::utils::guild_settings::LogType;
+
+fn channel_fields(channel: &GuildChannel) -> Vec<Field> {
+	let yes_no = |on: bool| match on {
+		| true => "Yes".to_string(),
+		| false => "No".to_string(),
+	};
+
+	vec![
+		("Name", channel.name.clone()),
+		("Type", format!("{:?}", channel.kind)),
+		("Category", show_channel(&channel.parent_id)),
+		("Topic", show_option(&channel.topic)),
+		("NSFW", yes_no(channel.nsfw)),
+		("Slowmode", match channel.rate_limit_per_user {
+			| Some(secs) if secs > 0 => format!("{}s", secs),
+			| _ => "Off".to_string(),
+		}),
+		(
+			"Bitrate",
+			show_option(&channel.bitrate.map(|b| format!("{}kbps", b / 1000))),
+		),
+		("User limit", match channel.user_limit {
+			| Some(limit) if limit > 0 => limit.to_string(),
+			| _ => "Unlimited".to_string(),
+		}),
+	]
+}
+
+pub fn on_create(
+	cache: &StructureCache,
+	channel: &GuildChannel,
+) -> Option<Log> {
+	cache.replace_channel(channel);
+
+	let log = log_channel(channel.guild_id, LogType::ChannelChanges)?;
+
+	let subject = Subject::new(channel.guild_id, LogType::ChannelChanges)
+		.channel(channel.id)
+		.category(channel.parent_id);
+
+	Some(
+		Log::new(log, "Channel Created", Color::DARK_GREEN, vec![
+			(
+				"Channel",
+				format!("<#{}> ({})", channel.id, channel.name),
+				true,
+			),
+			("Type", format!("{:?}", channel.kind), true),
+			("Category", show_channel(&channel.parent_id), true),
+		])
+		.about(subject),
+	)
+}
+
+pub fn on_delete(
+	cache: &StructureCache,
+	channel: &GuildChannel,
+) -> Option<Log> {
+	cache.remove_channel(channel);
+
+	let log = log_channel(channel.guild_id, LogType::ChannelChanges)?;
+
+	let subject = Subject::new(channel.guild_id, LogType::ChannelChanges)
+		.channel(channel.id)
+		.category(channel.parent_id);
+
+	Some(
+		Log::new(log, "Channel Deleted", Color::RED, vec![
+			("Channel", format!("#{}", channel.name), true),
+			("Type", format!("{:?}", channel.kind), true),
+			("Category", show_channel(&channel.parent_id), true),
+		])
+		.about(subject),
+	)
+}
+
+pub fn on_update(
+	cache: &StructureCache,
+	channel: &GuildChannel,
+) -> Option<Log> {
+	let before = cache.replace_channel(channel)?;
+
+	let changes = diff_fields(&channel_fields(&before), &channel_fields(channel));
+	let overwrites = diff_overwrites(
+		channel.guild_id,
+		&before.permission_overwrites,
+		&channel.permission_overwrites,
+	);
+
+	// position changes happen to every channel below a moved one, not worth logging
+	if changes.is_empty() && overwrites.is_empty() {
+		return None;
+	}
+
+	let log = log_channel(channel.guild_id, LogType::ChannelChanges)?;
+
+	let mut fields = vec![("Channel", format!("<#{}>", channel.id), false)];
+	if !changes.is_empty() {
+		fields.push(("Changes", changes.join("\n"), false));
+	}
+	if !overwrites.is_empty() {
+		fields.push(("Permission Overwrites", overwrites.join("\n"), false));
+	}
+
+	let subject = Subject::new(channel.guild_id, LogType::ChannelChanges)
+		.channel(channel.id)
+		.category(channel.parent_id);
+	Some(Log::new(log, "Channel Updated", Color::GOLD, fields).about(subject))
+}
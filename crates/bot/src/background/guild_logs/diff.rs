@@ -0,0 +1,112 @@
+use serenity::all::{GuildId, PermissionOverwrite, PermissionOverwriteType, Permissions};
+
+/// a named, human readable value that can be compared between two snapshots
+pub type Field = (&'static str, String);
+
+/// "**name:** before → after" for every field that changed
+pub fn diff_fields(
+	before: &[Field],
+	after: &[Field],
+) -> Vec<String> {
+	after
+		.iter()
+		.filter_map(|(name, new)| {
+			let old = before.iter().find(|(n, _)| n == name).map(|(_, v)| v)?;
+			(old != new).then(|| format!("**{}:** {} → {}", name, old, new))
+		})
+		.collect()
+}
+
+/// "Granted: ..." and "Revoked: ..." lines, empty if nothing changed
+pub fn diff_permissions(
+	before: Permissions,
+	after: Permissions,
+) -> Vec<String> {
+	let mut lines = vec![];
+	if !(after - before).is_empty() {
+		lines.push(format!("✅ Granted: {}", after - before));
+	}
+	if !(before - after).is_empty() {
+		lines.push(format!("❌ Revoked: {}", before - after));
+	}
+	lines
+}
+
+fn overwrite_target(
+	guild_id: GuildId,
+	kind: PermissionOverwriteType,
+) -> String {
+	match kind {
+		| PermissionOverwriteType::Role(id) if id.get() == guild_id.get() => "@everyone".into(),
+		| PermissionOverwriteType::Role(id) => format!("<@&{}>", id),
+		| PermissionOverwriteType::Member(id) => format!("<@{}>", id),
+		| _ => "Unknown".into(),
+	}
+}
+
+/// one line per role or member whose overwrite was added, removed or changed
+pub fn diff_overwrites(
+	guild_id: GuildId,
+	before: &[PermissionOverwrite],
+	after: &[PermissionOverwrite],
+) -> Vec<String> {
+	let mut lines = vec![];
+
+	for new in after {
+		let target = overwrite_target(guild_id, new.kind);
+		let Some(old) = before.iter().find(|o| o.kind == new.kind) else {
+			lines.push(format!(
+				"{}: added, allow {}, deny {}",
+				target,
+				permission_names(new.allow),
+				permission_names(new.deny)
+			));
+			continue;
+		};
+
+		let mut changes = vec![];
+		if !(new.allow - old.allow).is_empty() {
+			changes.push(format!("✅ {}", new.allow - old.allow));
+		}
+		if !(new.deny - old.deny).is_empty() {
+			changes.push(format!("❌ {}", new.deny - old.deny));
+		}
+		let reset = (old.allow | old.deny) - (new.allow | new.deny);
+		if !reset.is_empty() {
+			changes.push(format!("➖ {}", reset));
+		}
+
+		if !changes.is_empty() {
+			lines.push(format!("{}: {}", target, changes.join(", ")));
+		}
+	}
+
+	for old in before {
+		if !after.iter().any(|o| o.kind == old.kind) {
+			lines.push(format!("{}: removed", overwrite_target(guild_id, old.kind)));
+		}
+	}
+
+	lines
+}
+
+fn permission_names(permissions: Permissions) -> String {
+	match permissions.is_empty() {
+		| true => "nothing".into(),
+		| false => permissions.to_string(),
+	}
+}
+
+pub fn show_option<T: ToString>(value: &Option<T>) -> String {
+	match value {
+		| Some(value) => value.to_string(),
+		| None => "*None*".into(),
+	}
+}
+
+pub fn show_channel(id: &Option<serenity::all::ChannelId>) -> String {
+	match id {
+		| Some(id) => format!("<#{}>", id),
+		| None => "*None*".into(),
+	}
+}
@@ -0,0 +1,150 @@
+use serenity::all::{ChannelId, GuildId, RoleId, UserId};
+
+use super::member_cache::MemberCache;
+use super::structure_cache::StructureCache;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{LogType, ignore_rules, log_webhook};
+use crate::utils::prefix_cache::PrefixCache;
+
+/// What a log is about, checked against the guild's ignore rules for its log type before sending
+#[derive(Clone)]
+pub struct Subject {
+	pub guild_id: GuildId,
+	pub log_type: LogType,
+	pub channel:  Option<ChannelId>,
+	/// looked up from the channel when not set, deleted channels are already gone from the cache
+	pub category: Option<ChannelId>,
+	pub user:     Option<UserId>,
+	pub bot:      bool,
+	/// the user's roles when the event came with them, the member cache is used otherwise
+	pub roles:    Option<Vec<RoleId>>,
+	/// message content, to tell commands apart
+	pub content:  Option<String>,
+}
+
+impl Subject {
+	pub fn new(
+		guild_id: GuildId,
+		log_type: LogType,
+	) -> Self {
+		Subject {
+			guild_id,
+			log_type,
+			channel: None,
+			category: None,
+			user: None,
+			bot: false,
+			roles: None,
+			content: None,
+		}
+	}
+
+	pub fn channel(
+		mut self,
+		channel: ChannelId,
+	) -> Self {
+		self.channel = Some(channel);
+		self
+	}
+
+	pub fn category(
+		mut self,
+		category: Option<ChannelId>,
+	) -> Self {
+		self.category = category;
+		self
+	}
+
+	pub fn user(
+		mut self,
+		user: UserId,
+		bot: bool,
+	) -> Self {
+		self.user = Some(user);
+		self.bot = bot;
+		self
+	}
+
+	pub fn roles(
+		mut self,
+		roles: Option<Vec<RoleId>>,
+	) -> Self {
+		self.roles = roles;
+		self
+	}
+
+	pub fn content(
+		mut self,
+		content: &str,
+	) -> Self {
+		self.content = Some(content.to_string());
+		self
+	}
+}
+
+/// Logs about what the bot itself did, including messages through log webhooks. Logging those
+/// would send another log about the log, forever
+pub fn is_own_output(
+	subject: &Subject,
+	bot_id: Option<UserId>,
+) -> Result<bool, BotError> {
+	let Some(user) = subject.user else {
+		return Ok(false);
+	};
+	if bot_id == Some(user) {
+		return Ok(true);
+	}
+
+	// webhook messages are authored by the webhook itself
+	let (true, Some(channel)) = (subject.bot, subject.channel) else {
+		return Ok(false);
+	};
+	Ok(log_webhook(channel)?.is_some_and(|webhook| webhook.id.get() == user.get()))
+}
+
+pub fn is_ignored(
+	subject: &Subject,
+	structure: &StructureCache,
+	members: &MemberCache,
+	prefixes: &PrefixCache,
+	bot_id: Option<UserId>,
+) -> Result<bool, BotError> {
+	let rules = ignore_rules(subject.guild_id, subject.log_type)?;
+	let guild_id = subject.guild_id;
+
+	if let Some(channel) = subject.channel {
+		if rules.channels.contains(&channel) {
+			return Ok(true);
+		}
+		if subject
+			.category
+			.or_else(|| structure.parent(guild_id, channel))
+			.is_some_and(|parent| rules.categories.contains(&parent))
+		{
+			return Ok(true);
+		}
+	}
+
+	if let Some(user) = subject.user {
+		if rules.users.contains(&user) || (rules.bots && subject.bot) {
+			return Ok(true);
+		}
+		if !rules.roles.is_empty() {
+			let has_role = match &subject.roles {
+				| Some(roles) => roles.iter().any(|r| rules.roles.contains(r)),
+				| None => members
+					.get(guild_id, user)
+					.is_some_and(|member| member.roles.iter().any(|r| rules.roles.contains(r))),
+			};
+			if has_role {
+				return Ok(true);
+			}
+		}
+	}
+
+	Ok(rules.commands
+		&& subject
+			.content
+			.as_deref()
+			.is_some_and(|content| prefixes.strip(Some(guild_id), bot_id, content).is_some()))
+}
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serenity::all::{ChannelId, CreateMessage, ExecuteWebhook, Http};
+use serenity::builder::Builder;
+
+use super::Log;
+use crate::utils::guild_settings::{self, LogWebhook};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+/// a channel slows down to this at most while discord keeps rate limiting or failing
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// discord limits for a single message
+const MAX_EMBEDS: usize = 10;
+const MAX_EMBED_CHARS: usize = 6000;
+/// past this the oldest logs of a channel are dropped so a rate limited channel can't grow forever
+const MAX_PENDING: usize = 500;
+
+/// Logs waiting to be sent, grouped by channel so they can go out several embeds at a time
+#[derive(Default)]
+pub struct LogQueue {
+	pending:  Mutex<HashMap<ChannelId, VecDeque<Log>>>,
+	dropped:  Mutex<HashMap<ChannelId, usize>>,
+	/// channels that already have a task sending their logs
+	flushing: Mutex<HashSet<ChannelId>>,
+}
+
+impl LogQueue {
+	pub fn push(
+		&self,
+		log: Log,
+	) {
+		let Ok(mut pending) = self.pending.lock() else {
+			return;
+		};
+		let channel = log.channel;
+		let queue = pending.entry(channel).or_default();
+		queue.push_back(log);
+
+		if queue.len() > MAX_PENDING {
+			queue.pop_front();
+			if let Ok(mut dropped) = self.dropped.lock() {
+				*dropped.entry(channel).or_default() += 1;
+			}
+		}
+	}
+
+	fn channels(&self) -> Vec<ChannelId> {
+		self.pending
+			.lock()
+			.map(|pending| pending.keys().copied().collect())
+			.unwrap_or_default()
+	}
+
+	/// takes as many logs as fit in one message, `limit` embeds at most
+	fn take_batch(
+		&self,
+		channel: ChannelId,
+		limit: usize,
+	) -> Vec<Log> {
+		let Ok(mut pending) = self.pending.lock() else {
+			return vec![];
+		};
+		let Some(queue) = pending.get_mut(&channel) else {
+			return vec![];
+		};
+
+		let mut batch = vec![];
+		let mut chars = 0;
+		while let Some(log) = queue.front() {
+			let len = log.embed_len();
+			if batch.len() >= limit || (!batch.is_empty() && chars + len > MAX_EMBED_CHARS) {
+				break;
+			}
+			chars += len;
+			batch.extend(queue.pop_front());
+		}
+
+		if queue.is_empty() {
+			pending.remove(&channel);
+		}
+		batch
+	}
+
+	/// puts a batch that couldn't be sent back in front, keeping the order
+	fn requeue(
+		&self,
+		channel: ChannelId,
+		batch: Vec<Log>,
+	) {
+		let Ok(mut pending) = self.pending.lock() else {
+			return;
+		};
+		let queue = pending.entry(channel).or_default();
+		for log in batch.into_iter().rev() {
+			queue.push_front(log);
+		}
+	}
+
+	/// marks the channel as being flushed, false when a task already is
+	fn start_flush(
+		&self,
+		channel: ChannelId,
+	) -> bool {
+		self.flushing
+			.lock()
+			.map(|mut flushing| flushing.insert(channel))
+			.unwrap_or(false)
+	}
+
+	fn end_flush(
+		&self,
+		channel: ChannelId,
+	) {
+		if let Ok(mut flushing) = self.flushing.lock() {
+			flushing.remove(&channel);
+		}
+	}
+
+	fn take_dropped(&self) -> HashMap<ChannelId, usize> {
+		self.dropped
+			.lock()
+			.map(|mut dropped| std::mem::take(&mut *dropped))
+			.unwrap_or_default()
+	}
+}
+
+enum Sent {
+	Ok,
+	/// try again later, the batch is requeued
+	RateLimited,
+	/// the stored webhook is gone, send as the bot instead
+	WebhookGone,
+	/// discord or the connection had a problem, the batch is requeued and tried again later
+	Unavailable(serenity::Error),
+	/// discord didn't accept something in the batch
+	Rejected(serenity::Error),
+	Failed(serenity::Error),
+}
+
+fn status(error: &serenity::Error) -> Option<u16> {
+	match error {
+		| serenity::Error::Http(e) => e.status_code().map(|s| s.as_u16()),
+		| _ => None,
+	}
+}
+
+async fn send_batch(
+	http: &Http,
+	channel: ChannelId,
+	webhook: Option<&LogWebhook>,
+	batch: Vec<Log>,
+) -> Sent {
+	let (embeds, files): (Vec<_>, Vec<_>) = batch.into_iter().map(Log::into_parts).unzip();
+	let files = files.into_iter().flatten();
+
+	let result = match webhook {
+		| Some(webhook) => {
+			let mut message = ExecuteWebhook::new().embeds(embeds).add_files(files);
+			if let Some(name) = &webhook.name {
+				message = message.username(name);
+			}
+			if let Some(avatar_url) = &webhook.avatar_url {
+				message = message.avatar_url(avatar_url);
+			}
+			message
+				.execute(http, (webhook.id, &webhook.token, false))
+				.await
+				.map(|_| ())
+		},
+		| None => channel
+			.send_message(http, CreateMessage::new().embeds(embeds).add_files(files))
+			.await
+			.map(|_| ()),
+	};
+
+	match result {
+		| Ok(()) => Sent::Ok,
+		| Err(e) if status(&e) == Some(429) => Sent::RateLimited,
+		| Err(e) if webhook.is_some() && matches!(status(&e), Some(401 | 404)) => Sent::WebhookGone,
+		| Err(e) if status(&e) == Some(400) => Sent::Rejected(e),
+		| Err(e) if matches!(status(&e), None | Some(500..=599)) => Sent::Unavailable(e),
+		| Err(e) => Sent::Failed(e),
+	}
+}
+
+/// Sends queued logs forever. Every channel with logs waiting gets its own task, so one rate
+/// limited or failing channel doesn't hold back the others
+pub async fn run(
+	http: Arc<Http>,
+	queue: Arc<LogQueue>,
+) {
+	loop {
+		tokio::time::sleep(FLUSH_INTERVAL).await;
+
+		for (channel, count) in queue.take_dropped() {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Dropped {} logs for {}, too many were waiting to be sent",
+				count, channel
+			);
+		}
+
+		for channel in queue.channels() {
+			if queue.start_flush(channel) {
+				tokio::spawn(flush(http.clone(), queue.clone(), channel));
+			}
+		}
+	}
+}
+
+/// sends the channel's logs until none are left
+async fn flush(
+	http: Arc<Http>,
+	queue: Arc<LogQueue>,
+	channel: ChannelId,
+) {
+	// left queued until the store can be read, so logs aren't sent the wrong way
+	let mut webhook = match guild_settings::log_webhook(channel) {
+		| Ok(webhook) => webhook,
+		| Err(e) => {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Couldn't read the log webhook for {}: {}",
+				channel, e
+			);
+			queue.end_flush(channel);
+			return;
+		},
+	};
+
+	let mut limit = MAX_EMBEDS;
+	let mut backoff = FLUSH_INTERVAL;
+
+	loop {
+		let batch = queue.take_batch(channel, limit);
+		if batch.is_empty() {
+			break;
+		}
+
+		match send_batch(&http, channel, webhook.as_ref(), batch.clone()).await {
+			| Sent::Ok => {
+				limit = (limit * 2).min(MAX_EMBEDS);
+				backoff = FLUSH_INTERVAL;
+			},
+			| Sent::RateLimited => {
+				queue.requeue(channel, batch);
+				tokio::time::sleep(backoff).await;
+				backoff = (backoff * 2).min(MAX_BACKOFF);
+			},
+			| Sent::Unavailable(e) => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't send {} logs to {}, trying again later: {}",
+					batch.len(),
+					channel,
+					e
+				);
+				queue.requeue(channel, batch);
+				tokio::time::sleep(backoff).await;
+				backoff = (backoff * 2).min(MAX_BACKOFF);
+			},
+			| Sent::WebhookGone => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m The log webhook for {} was deleted, sending logs as \
+					 the bot instead",
+					channel
+				);
+				let _ = guild_settings::set_log_webhook(channel, None);
+				webhook = None;
+				queue.requeue(channel, batch);
+			},
+			// halved until the log discord doesn't like is sent on its own, so only that one is lost
+			| Sent::Rejected(_) if batch.len() > 1 => {
+				limit = batch.len() / 2;
+				queue.requeue(channel, batch);
+			},
+			| Sent::Rejected(e) | Sent::Failed(e) => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't send {} logs to {}: {}",
+					batch.len(),
+					channel,
+					e
+				);
+			},
+		}
+	}
+
+	queue.end_flush(channel);
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serenity::all::{GuildId, ImageHash, Member, RoleId, Timestamp, UserId};
+
+/// What a member looked like the last time we saw them
+#[derive(Clone)]
+pub struct MemberSnapshot {
+	pub nick:         Option<String>,
+	pub roles:        Vec<RoleId>,
+	pub avatar:       Option<ImageHash>,
+	/// server specific avatar, shown instead of `avatar` when set
+	pub guild_avatar: Option<ImageHash>,
+	pub joined_at:    Option<Timestamp>,
+	/// when their timeout ends, discord keeps past timestamps around after it expires
+	pub timeout:      Option<Timestamp>,
+}
+
+impl MemberSnapshot {
+	pub fn new(member: &Member) -> Self {
+		MemberSnapshot {
+			nick:         member.nick.clone(),
+			roles:        member.roles.clone(),
+			avatar:       member.user.avatar,
+			guild_avatar: member.avatar,
+			joined_at:    member.joined_at,
+			timeout:      member.communication_disabled_until,
+		}
+	}
+}
+
+/// Member state per guild, serenity's cache is updated at the same time raw events are handled
+/// so it can't be used to tell what changed
+#[derive(Default)]
+pub struct MemberCache {
+	members: Mutex<HashMap<(GuildId, UserId), MemberSnapshot>>,
+}
+
+impl MemberCache {
+	/// stores the new state, returning the previous one if the member was known
+	pub fn replace(
+		&self,
+		guild_id: GuildId,
+		user_id: UserId,
+		snapshot: MemberSnapshot,
+	) -> Option<MemberSnapshot> {
+		self.members
+			.lock()
+			.ok()?
+			.insert((guild_id, user_id), snapshot)
+	}
+
+	pub fn get(
+		&self,
+		guild_id: GuildId,
+		user_id: UserId,
+	) -> Option<MemberSnapshot> {
+		self.members.lock().ok()?.get(&(guild_id, user_id)).cloned()
+	}
+
+	pub fn remove(
+		&self,
+		guild_id: GuildId,
+		user_id: UserId,
+	) -> Option<MemberSnapshot> {
+		self.members.lock().ok()?.remove(&(guild_id, user_id))
+	}
+
+	/// fills the cache from the member list discord sends when the bot joins or starts
+	pub fn load_guild(
+		&self,
+		guild_id: GuildId,
+		members: &HashMap<UserId, Member>,
+	) {
+		let Ok(mut cached) = self.members.lock() else {
+			return;
+		};
+		for (user_id, member) in members {
+			cached.insert((guild_id, *user_id), MemberSnapshot::new(member));
+		}
+	}
+
+	/// drops everything for a guild the bot left
+	pub fn forget_guild(
+		&self,
+		guild_id: GuildId,
+	) {
+		if let Ok(mut cached) = self.members.lock() {
+			cached.retain(|(id, _), _| *id != guild_id);
+		}
+	}
+}
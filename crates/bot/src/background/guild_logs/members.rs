@@ -0,0 +1,283 @@
+use serenity::all::{
+	Color,
+	GuildId,
+	GuildMemberRemoveEvent,
+	GuildMemberUpdateEvent,
+	Http,
+	ImageHash,
+	Member,
+	RoleId,
+	Timestamp,
+	UserId,
+};
+
+use super::audit::{find_responsible, responsible_fields};
+use super::ignore::Subject;
+use super::member_cache::{MemberCache, MemberSnapshot};
+use super::{Log, log_channel};
+use crate::utils::dates::format_duration;
+use crate::utils::guild_settings::LogType;
+use crate::utils::mention::Mentionable;
+use crate::utils::mod_actions::{ModActionKind, ModActions};
+
+/// accounts younger than this get flagged when they join
+const NEW_ACCOUNT_DAYS: i64 = 7;
+
+fn seconds_since(timestamp: Timestamp) -> u64 {
+	(Timestamp::now().unix_timestamp() - timestamp.unix_timestamp()).max(0) as u64
+}
+
+fn avatar_url(
+	guild_id: GuildId,
+	user_id: UserId,
+	avatar: Option<ImageHash>,
+	guild_avatar: Option<ImageHash>,
+) -> Option<String> {
+	let extension = |hash: &ImageHash| match hash.is_animated() {
+		| true => "gif",
+		| false => "png",
+	};
+
+	match (guild_avatar, avatar) {
+		| (Some(hash), _) => Some(format!(
+			"https://cdn.discordapp.com/guilds/{}/users/{}/avatars/{}.{}",
+			guild_id,
+			user_id,
+			hash,
+			extension(&hash)
+		)),
+		| (None, Some(hash)) => Some(format!(
+			"https://cdn.discordapp.com/avatars/{}/{}.{}",
+			user_id,
+			hash,
+			extension(&hash)
+		)),
+		| (None, None) => None,
+	}
+}
+
+fn role_list(roles: &[RoleId]) -> String {
+	roles
+		.iter()
+		.map(|id| id.mention())
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+pub fn on_join(
+	cache: &MemberCache,
+	member: &Member,
+) -> Option<Log> {
+	cache.replace(member.guild_id, member.user.id, MemberSnapshot::new(member));
+
+	let channel = log_channel(member.guild_id, LogType::MemberJoin)?;
+
+	let created_at = member.user.created_at();
+	let age = seconds_since(created_at);
+	let is_new = age < (NEW_ACCOUNT_DAYS * 86400) as u64;
+
+	let mut fields = vec![
+		("User", member.user.mention(), true),
+		("ID", member.user.id.to_string(), true),
+		(
+			"Account Created",
+			format!(
+				"<t:{}:f> ({} old)",
+				created_at.unix_timestamp(),
+				format_duration(age)
+			),
+			false,
+		),
+	];
+	if is_new {
+		fields.push((
+			"⚠️ New Account",
+			format!("This account is less than {} days old.", NEW_ACCOUNT_DAYS),
+			false,
+		));
+	}
+
+	let mut log = Log::new(
+		channel,
+		"Member Joined",
+		match is_new {
+			| true => Color::ORANGE,
+			| false => Color::DARK_GREEN,
+		},
+		fields,
+	);
+	log.thumbnail = Some(member.user.face());
+	log.subject = Some(
+		Subject::new(member.guild_id, LogType::MemberJoin).user(member.user.id, member.user.bot),
+	);
+	Some(log)
+}
+
+pub fn on_leave(
+	cache: &MemberCache,
+	event: &GuildMemberRemoveEvent,
+) -> Option<Log> {
+	let snapshot = cache.remove(event.guild_id, event.user.id);
+
+	let channel = log_channel(event.guild_id, LogType::MemberLeave)?;
+
+	let mut fields = vec![
+		("User", event.user.mention(), true),
+		("ID", event.user.id.to_string(), true),
+	];
+
+	if let Some(snapshot) = snapshot {
+		if let Some(joined_at) = snapshot.joined_at {
+			fields.push((
+				"Joined",
+				format!(
+					"<t:{}:f> ({} ago)",
+					joined_at.unix_timestamp(),
+					format_duration(seconds_since(joined_at))
+				),
+				false,
+			));
+		}
+		if !snapshot.roles.is_empty() {
+			fields.push(("Roles", role_list(&snapshot.roles), false));
+		}
+	}
+
+	let mut log = Log::new(channel, "Member Left", Color::DARK_RED, fields);
+	log.thumbnail = Some(event.user.face());
+	log.subject = Some(
+		Subject::new(event.guild_id, LogType::MemberLeave).user(event.user.id, event.user.bot),
+	);
+	Some(log)
+}
+
+/// timeouts stay on the member after they run out, so only ones ending in the future count
+fn timed_out(until: Option<Timestamp>) -> bool {
+	until.is_some_and(|until| until > Timestamp::now())
+}
+
+pub async fn on_update(
+	http: &Http,
+	cache: &MemberCache,
+	actions: &ModActions,
+	event: &GuildMemberUpdateEvent,
+) -> Vec<Log> {
+	let guild_id = event.guild_id;
+	let user = &event.user;
+
+	let snapshot = MemberSnapshot {
+		nick:         event.nick.clone(),
+		roles:        event.roles.clone(),
+		avatar:       user.avatar,
+		guild_avatar: event.avatar,
+		joined_at:    Some(event.joined_at),
+		timeout:      event.communication_disabled_until,
+	};
+
+	// without the old state there is nothing to compare against, this update just fills the cache
+	let Some(before) = cache.replace(guild_id, user.id, snapshot.clone()) else {
+		return vec![];
+	};
+
+	let mut logs = vec![];
+	let subject = |log_type| Subject::new(guild_id, log_type).user(user.id, user.bot);
+
+	if before.nick != snapshot.nick
+		&& let Some(channel) = log_channel(guild_id, LogType::NicknameChange)
+	{
+		let show = |nick: &Option<String>| nick.clone().unwrap_or("*None*".to_string());
+		logs.push(
+			Log::new(channel, "Nickname Changed", Color::BLUE, vec![
+				("User", user.mention(), true),
+				("Before", show(&before.nick), false),
+				("After", show(&snapshot.nick), false),
+			])
+			.about(subject(LogType::NicknameChange)),
+		);
+	}
+
+	let added: Vec<_> = snapshot
+		.roles
+		.iter()
+		.filter(|id| !before.roles.contains(id))
+		.copied()
+		.collect();
+	let removed: Vec<_> = before
+		.roles
+		.iter()
+		.filter(|id| !snapshot.roles.contains(id))
+		.copied()
+		.collect();
+
+	if (!added.is_empty() || !removed.is_empty())
+		&& let Some(channel) = log_channel(guild_id, LogType::RoleChange)
+	{
+		let mut fields = vec![("User", user.mention(), true)];
+		if !added.is_empty() {
+			fields.push(("Added", role_list(&added), false));
+		}
+		if !removed.is_empty() {
+			fields.push(("Removed", role_list(&removed), false));
+		}
+		let responsible = find_responsible(
+			http,
+			actions,
+			guild_id,
+			user.id,
+			ModActionKind::RoleUpdate,
+			3,
+		)
+		.await;
+		fields.extend(responsible_fields(&responsible));
+		logs.push(
+			Log::new(channel, "Roles Changed", Color::BLUE, fields)
+				.about(subject(LogType::RoleChange)),
+		);
+	}
+
+	let before_avatar = avatar_url(guild_id, user.id, before.avatar, before.guild_avatar);
+	let after_avatar = avatar_url(guild_id, user.id, snapshot.avatar, snapshot.guild_avatar);
+
+	if before_avatar != after_avatar
+		&& let Some(channel) = log_channel(guild_id, LogType::AvatarChange)
+	{
+		let show = |url: &Option<String>| match url {
+			| Some(url) => format!("[Link]({})", url),
+			| None => "*Default avatar*".to_string(),
+		};
+		let mut log = Log::new(channel, "Avatar Changed", Color::BLUE, vec![
+			("User", user.mention(), true),
+			("Before", show(&before_avatar), true),
+			("After", show(&after_avatar), true),
+		]);
+		log.thumbnail = Some(after_avatar.unwrap_or(user.face()));
+		log.subject = Some(subject(LogType::AvatarChange));
+		logs.push(log);
+	}
+
+	// a timeout that ran out on its own sends no event, removing one early does
+	let timeout_changed = before.timeout != snapshot.timeout
+		&& (timed_out(snapshot.timeout) || timed_out(before.timeout));
+
+	if timeout_changed && let Some(channel) = log_channel(guild_id, LogType::Ban) {
+		let responsible =
+			find_responsible(http, actions, guild_id, user.id, ModActionKind::Timeout, 3).await;
+
+		let mut fields = vec![("User", user.mention(), true)];
+		let title = match snapshot.timeout.filter(|_| timed_out(snapshot.timeout)) {
+			| Some(until) => {
+				fields.push((
+					"Until",
+					format!("<t:{0}:f> (<t:{0}:R>)", until.unix_timestamp()),
+					true,
+				));
+				"Member Timed Out"
+			},
+			| None => "Timeout Removed",
+		};
+		fields.extend(responsible_fields(&responsible));
+		logs.push(Log::new(channel, title, Color::ORANGE, fields).about(subject(LogType::Ban)));
+	}
+
+	logs
+}
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serenity::all::{GuildId, Message, MessageId, UserId};
+
+use crate::config::config;
+
+#[derive(Clone)]
+pub struct CachedAttachment {
+	pub filename: String,
+	/// hash in the attachment store, once it's been downloaded
+	pub archived: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct CachedMessage {
+	pub author_id:   UserId,
+	pub author_name: String,
+	pub author_bot:  bool,
+	pub content:     String,
+	/// discord deletes the files with the message, so they are archived separately
+	pub attachments: Vec<CachedAttachment>,
+	cached_at:       Instant,
+}
+
+impl CachedMessage {
+	pub fn new(message: &Message) -> Self {
+		CachedMessage {
+			author_id:   message.author.id,
+			author_name: message.author.name.clone(),
+			author_bot:  message.author.bot,
+			content:     message.content.clone(),
+			attachments: message
+				.attachments
+				.iter()
+				.map(|a| CachedAttachment {
+					filename: a.filename.clone(),
+					archived: None,
+				})
+				.collect(),
+			cached_at:   Instant::now(),
+		}
+	}
+}
+
+#[derive(Default)]
+struct GuildMessages {
+	/// oldest first, used to evict
+	order:    VecDeque<MessageId>,
+	messages: HashMap<MessageId, CachedMessage>,
+}
+
+/// Recent messages per guild, discord doesn't send the old content on edits or deletes so logs
+/// need to remember it themselves
+pub struct MessageCache {
+	guilds:    Mutex<HashMap<GuildId, GuildMessages>>,
+	capacity:  usize,
+	retention: Duration,
+}
+
+impl MessageCache {
+	/// sized from `MESSAGE_CACHE_SIZE` and `MESSAGE_CACHE_RETENTION_HOURS`
+	pub fn from_config() -> Self {
+		MessageCache {
+			guilds:    Mutex::new(HashMap::new()),
+			capacity:  config().message_cache_size,
+			retention: config().message_cache_retention,
+		}
+	}
+
+	pub fn insert(
+		&self,
+		guild_id: GuildId,
+		message_id: MessageId,
+		message: CachedMessage,
+	) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		let Ok(mut guilds) = self.guilds.lock() else {
+			return;
+		};
+		let guild = guilds.entry(guild_id).or_default();
+
+		if guild.messages.insert(message_id, message).is_none() {
+			guild.order.push_back(message_id);
+		}
+
+		while let Some(oldest) = guild.order.front() {
+			let expired = guild
+				.messages
+				.get(oldest)
+				.is_none_or(|m| m.cached_at.elapsed() > self.retention);
+
+			if guild.order.len() <= self.capacity && !expired {
+				break;
+			}
+
+			if let Some(oldest) = guild.order.pop_front() {
+				guild.messages.remove(&oldest);
+			}
+		}
+	}
+
+	pub fn get(
+		&self,
+		guild_id: GuildId,
+		message_id: MessageId,
+	) -> Option<CachedMessage> {
+		let guilds = self.guilds.lock().ok()?;
+		guilds
+			.get(&guild_id)?
+			.messages
+			.get(&message_id)
+			.filter(|m| m.cached_at.elapsed() <= self.retention)
+			.cloned()
+	}
+
+	/// fills in archived attachments once they are downloaded, if the message is still cached
+	pub fn set_attachments(
+		&self,
+		guild_id: GuildId,
+		message_id: MessageId,
+		attachments: Vec<CachedAttachment>,
+	) {
+		let Ok(mut guilds) = self.guilds.lock() else {
+			return;
+		};
+		if let Some(message) = guilds
+			.get_mut(&guild_id)
+			.and_then(|guild| guild.messages.get_mut(&message_id))
+		{
+			message.attachments = attachments;
+		}
+	}
+
+	/// removes the message, returning what it was if it was cached
+	pub fn remove(
+		&self,
+		guild_id: GuildId,
+		message_id: MessageId,
+	) -> Option<CachedMessage> {
+		let mut guilds = self.guilds.lock().ok()?;
+		let guild = guilds.get_mut(&guild_id)?;
+		let message = guild.messages.remove(&message_id)?;
+		guild.order.retain(|id| *id != message_id);
+		Some(message).filter(|m| m.cached_at.elapsed() <= self.retention)
+	}
+}
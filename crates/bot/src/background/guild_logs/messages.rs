@@ -0,0 +1,245 @@
+use serenity::all::{
+	Color,
+	CreateAttachment,
+	Message,
+	MessageDeleteBulkEvent,
+	MessageDeleteEvent,
+	MessageUpdateEvent,
+};
+use serenity::futures::future::join_all;
+
+use super::ignore::{Subject, is_own_output};
+use super::message_cache::{CachedAttachment, CachedMessage, MessageCache};
+use super::{Log, attachment_store, log_channel};
+use crate::config::config;
+use crate::utils::guild_settings::LogType;
+use crate::utils::identity::Identity;
+use crate::utils::mention::Mentionable;
+
+fn archived_link(attachment: &CachedAttachment) -> Option<String> {
+	attachment_store::link(attachment.archived.as_ref()?, &attachment.filename)
+}
+
+/// Attachments as links to their archived copy, or just the name if they weren't archived
+fn attachment_list(attachments: &[CachedAttachment]) -> String {
+	attachments
+		.iter()
+		.map(|a| match archived_link(a) {
+			| Some(link) => format!("[{}]({})", a.filename, link),
+			| None => a.filename.clone(),
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// only worth downloading when a log that isn't ignored could show them
+fn should_archive(
+	subject: &Subject,
+	is_ignored: impl Fn(&Subject) -> bool,
+) -> bool {
+	config().attachment_secret.is_some()
+		&& [
+			LogType::MessageSent,
+			LogType::MessageDelete,
+			LogType::MessageBulkDelete,
+		]
+		.into_iter()
+		.any(|log_type| {
+			let subject = Subject {
+				log_type,
+				..subject.clone()
+			};
+			log_channel(subject.guild_id, log_type).is_some() && !is_ignored(&subject)
+		})
+}
+
+pub async fn on_create(
+	cache: &MessageCache,
+	identity: &Identity,
+	message: &Message,
+	is_ignored: impl Fn(&Subject) -> bool,
+) -> Option<Log> {
+	let guild_id = message.guild_id?;
+
+	let subject = Subject::new(guild_id, LogType::MessageSent)
+		.channel(message.channel_id)
+		.user(message.author.id, message.author.bot)
+		.roles(message.member.as_ref().map(|member| member.roles.clone()))
+		.content(&message.content);
+
+	// the handler drops these logs too, this also skips caching and archiving log messages.
+	// it also reports the error when the check fails
+	if is_own_output(&subject, identity.id()).unwrap_or(false) {
+		return None;
+	}
+
+	let cached = CachedMessage::new(message);
+	let mut attachments = cached.attachments.clone();
+	// cached before downloading so edits and deletes in the meantime still find it
+	cache.insert(guild_id, message.id, cached);
+
+	if !attachments.is_empty() && should_archive(&subject, is_ignored) {
+		let archived = join_all(message.attachments.iter().map(attachment_store::archive)).await;
+		for (cached, archived) in attachments.iter_mut().zip(archived) {
+			cached.archived = archived;
+		}
+		cache.set_attachments(guild_id, message.id, attachments.clone());
+	}
+
+	let channel = log_channel(guild_id, LogType::MessageSent)?;
+
+	let mut fields = vec![
+		("User", message.author.mention().to_string(), true),
+		("Content", message.content.clone(), false),
+	];
+	if !attachments.is_empty() {
+		fields.push(("Attachments", attachment_list(&attachments), false));
+	}
+
+	Some(Log::new(channel, "Message Sent", Color::BLURPLE, fields).about(subject))
+}
+
+pub fn on_update(
+	cache: &MessageCache,
+	event: &MessageUpdateEvent,
+) -> Option<Log> {
+	let guild_id = event.guild_id?;
+
+	// embeds loading in also sends an update, only content changes matter here
+	let after = event.content.as_ref()?;
+	let before = cache.get(guild_id, event.id);
+
+	if before.as_ref().is_some_and(|b| b.content == *after) {
+		return None;
+	}
+
+	let (author_id, author_bot) = match (&event.author, &before) {
+		| (Some(author), _) => (author.id, author.bot),
+		| (None, Some(before)) => (before.author_id, before.author_bot),
+		| (None, None) => return None,
+	};
+	if let Some(mut updated) = before.clone() {
+		updated.content = after.clone();
+		cache.insert(guild_id, event.id, updated);
+	}
+
+	let channel = log_channel(guild_id, LogType::MessageEdit)?;
+
+	let before = match before {
+		| Some(before) => before.content,
+		| None => "*Not cached, the message is older than the bot remembers*".to_string(),
+	};
+
+	let subject = Subject::new(guild_id, LogType::MessageEdit)
+		.channel(event.channel_id)
+		.user(author_id, author_bot)
+		.content(after);
+
+	Some(
+		Log::new(channel, "Message Edited", Color::GOLD, vec![
+			("User", author_id.mention(), true),
+			("Channel", format!("<#{}>", event.channel_id), true),
+			(
+				"Message",
+				format!(
+					"[Jump](https://discord.com/channels/{}/{}/{})",
+					guild_id, event.channel_id, event.id
+				),
+				true,
+			),
+			("Before", before, false),
+			("After", after.clone(), false),
+		])
+		.about(subject),
+	)
+}
+
+pub fn on_delete(
+	cache: &MessageCache,
+	event: &MessageDeleteEvent,
+) -> Option<Log> {
+	let guild_id = event.guild_id?;
+	let message = cache.remove(guild_id, event.message_id);
+
+	let channel = log_channel(guild_id, LogType::MessageDelete)?;
+
+	let mut fields = vec![("Channel", format!("<#{}>", event.channel_id), true)];
+	let mut subject = Subject::new(guild_id, LogType::MessageDelete).channel(event.channel_id);
+
+	match message {
+		| Some(message) => {
+			subject = subject
+				.user(message.author_id, message.author_bot)
+				.content(&message.content);
+			fields.insert(0, ("User", message.author_id.mention(), true));
+			fields.push(("Content", message.content.clone(), false));
+			if !message.attachments.is_empty() {
+				fields.push(("Attachments", attachment_list(&message.attachments), false));
+			}
+		},
+		| None => {
+			fields.push(("Message ID", event.message_id.to_string(), true));
+			fields.push((
+				"Content",
+				"*Not cached, the message is older than the bot remembers*".to_string(),
+				false,
+			));
+		},
+	}
+
+	Some(Log::new(channel, "Message Deleted", Color::RED, fields).about(subject))
+}
+
+pub fn on_bulk_delete(
+	cache: &MessageCache,
+	event: &MessageDeleteBulkEvent,
+) -> Option<Log> {
+	let guild_id = event.guild_id?;
+
+	let messages: Vec<CachedMessage> = event
+		.ids
+		.iter()
+		.filter_map(|id| cache.remove(guild_id, *id))
+		.collect();
+
+	let channel = log_channel(guild_id, LogType::MessageBulkDelete)?;
+
+	// the full transcript goes in a file since it won't fit in an embed
+	let transcript = messages
+		.iter()
+		.map(|m| {
+			let mut line = format!("[{} ({})] {}", m.author_name, m.author_id, m.content);
+			if !m.attachments.is_empty() {
+				let attachments = m
+					.attachments
+					.iter()
+					.map(|a| match archived_link(a) {
+						| Some(link) => format!("{} ({})", a.filename, link),
+						| None => a.filename.clone(),
+					})
+					.collect::<Vec<_>>()
+					.join(", ");
+				line.push_str(&format!(" [attachments: {}]", attachments));
+			}
+			line
+		})
+		.collect::<Vec<_>>()
+		.join("\n");
+
+	let mut log = Log::new(channel, "Messages Bulk Deleted", Color::DARK_RED, vec![
+		("Channel", format!("<#{}>", event.channel_id), true),
+		("Deleted", event.ids.len().to_string(), true),
+		("Cached", messages.len().to_string(), true),
+	]);
+
+	log = log.about(Subject::new(guild_id, LogType::MessageBulkDelete).channel(event.channel_id));
+
+	if !transcript.is_empty() {
+		log.attachment = Some(CreateAttachment::bytes(
+			transcript.into_bytes(),
+			format!("deleted-messages-{}.txt", event.channel_id),
+		));
+	}
+
+	Some(log)
+}
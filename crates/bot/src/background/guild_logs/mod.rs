@@ -0,0 +1,334 @@
+pub mod attachment_store;
+pub mod audit;
+pub mod channels;
+pub mod diff;
+pub mod ignore;
+pub mod log_queue;
+pub mod member_cache;
+pub mod members;
+pub mod message_cache;
+pub mod messages;
+pub mod moderation;
+pub mod roles;
+pub mod server;
+pub mod structure_cache;
+pub mod voice;
+pub mod voice_cache;
+
+use std::sync::Arc;
+
+use ignore::Subject;
+use log_queue::LogQueue;
+use member_cache::MemberCache;
+use message_cache::MessageCache;
+use poise::serenity_prelude::Context;
+use serenity::all::{
+	ChannelId,
+	Color,
+	CreateAttachment,
+	CreateEmbed,
+	Event,
+	GuildId,
+	RawEventHandler,
+};
+use structure_cache::StructureCache;
+use voice_cache::VoiceCache;
+
+use crate::utils::guild_settings::{self, LogType};
+use crate::utils::identity::Identity;
+use crate::utils::mod_actions::ModActions;
+use crate::utils::prefix_cache::PrefixCache;
+
+#[derive(Clone)]
+pub struct Handler {
+	pub messages:  Arc<MessageCache>,
+	pub members:   Arc<MemberCache>,
+	pub structure: Arc<StructureCache>,
+	pub voice:     Arc<VoiceCache>,
+	/// shared with commands, see [`ModActions`]
+	pub actions:   Arc<ModActions>,
+	/// flushed by [`log_queue::run`]
+	pub queue:     Arc<LogQueue>,
+	/// shared with the framework, used to skip command messages
+	pub prefixes:  Arc<PrefixCache>,
+	pub identity:  Arc<Identity>,
+}
+
+/// A log entry, sent as an embed to the channel set for its log type
+#[derive(Clone)]
+pub struct Log {
+	pub channel:    ChannelId,
+	pub title:      &'static str,
+	pub color:      Color,
+	pub fields:     Vec<(&'static str, String, bool)>,
+	/// for logs too long to fit in an embed
+	pub attachment: Option<CreateAttachment>,
+	pub thumbnail:  Option<String>,
+	/// checked against the guild's ignore rules, logs without one are always sent
+	pub subject:    Option<Subject>,
+}
+
+impl Log {
+	pub fn new(
+		channel: ChannelId,
+		title: &'static str,
+		color: Color,
+		fields: Vec<(&'static str, String, bool)>,
+	) -> Self {
+		Log {
+			channel,
+			title,
+			color,
+			fields,
+			attachment: None,
+			thumbnail: None,
+			subject: None,
+		}
+	}
+
+	pub fn about(
+		mut self,
+		subject: Subject,
+	) -> Self {
+		self.subject = Some(subject);
+		self
+	}
+
+	/// roughly what discord counts towards the 6000 character limit of a message's embeds
+	fn embed_len(&self) -> usize {
+		self.title.chars().count()
+			+ self
+				.fields
+				.iter()
+				.map(|(name, value, _)| name.chars().count() + value.chars().count().min(1024))
+				.sum::<usize>()
+	}
+
+	fn into_parts(self) -> (CreateEmbed, Option<CreateAttachment>) {
+		// discord rejects empty field values and ones over 1024 characters
+		let fields = self.fields.into_iter().map(|(name, value, inline)| {
+			let value = match value.is_empty() {
+				| true => "*empty*".to_string(),
+				| false => truncate(&value, 1024),
+			};
+			(name, value, inline)
+		});
+
+		let mut embed = CreateEmbed::new()
+			.title(self.title)
+			.colour(self.color)
+			.fields(fields)
+			.timestamp(serenity::model::Timestamp::now());
+		if let Some(thumbnail) = self.thumbnail {
+			embed = embed.thumbnail(thumbnail);
+		}
+
+		(embed, self.attachment)
+	}
+}
+
+/// Channel a log type is sent to. A store that can't be read is reported and the log skipped,
+/// since there's no one to return the error to
+pub fn log_channel(
+	guild_id: GuildId,
+	log_type: LogType,
+) -> Option<ChannelId> {
+	match guild_settings::log_channel(guild_id, log_type) {
+		| Ok(channel) => channel,
+		| Err(e) => {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Couldn't read the {} channel of {}: {}",
+				log_type.key(),
+				guild_id,
+				e
+			);
+			None
+		},
+	}
+}
+
+/// cuts `text` to at most `max` characters, marking that it was cut
+pub fn truncate(
+	text: &str,
+	max: usize,
+) -> String {
+	if text.chars().count() <= max {
+		return text.to_string();
+	}
+	let mut cut: String = text.chars().take(max - 1).collect();
+	cut.push('…');
+	cut
+}
+
+#[serenity::async_trait]
+impl RawEventHandler for Handler {
+	async fn raw_event(
+		&self,
+		ctx: Context,
+		new_event: Event,
+	) {
+		use serenity::model::event::Event::*;
+
+		let logs: Vec<Log> = match new_event {
+			| Ready(event) => {
+				self.identity.set(&event.ready.user);
+				return;
+			},
+			| GuildCreate(event) => {
+				self.members
+					.load_guild(event.guild.id, &event.guild.members);
+				self.structure
+					.load_guild(&event.guild, server::guild_fields(&event.guild));
+				self.voice
+					.load_guild(event.guild.id, &event.guild.voice_states);
+				return;
+			},
+			| GuildDelete(event) => {
+				// unavailable means an outage, the bot is still in the guild
+				if !event.guild.unavailable {
+					self.members.forget_guild(event.guild.id);
+					self.structure.forget_guild(event.guild.id);
+					self.voice.forget_guild(event.guild.id);
+				}
+				return;
+			},
+			| MessageCreate(event) => {
+				messages::on_create(&self.messages, &self.identity, &event.message, |subject| {
+					self.is_ignored(subject)
+				})
+				.await
+				.into_iter()
+				.collect()
+			},
+			| MessageUpdate(event) => messages::on_update(&self.messages, &event)
+				.into_iter()
+				.collect(),
+			| MessageDelete(event) => messages::on_delete(&self.messages, &event)
+				.into_iter()
+				.collect(),
+			| MessageDeleteBulk(event) => messages::on_bulk_delete(&self.messages, &event)
+				.into_iter()
+				.collect(),
+			| GuildMemberAdd(event) => members::on_join(&self.members, &event.member)
+				.into_iter()
+				.collect(),
+			| GuildMemberRemove(event) => {
+				self.push(members::on_leave(&self.members, &event));
+				// finding out if it was a kick waits on the audit log, which shouldn't hold up
+				// the leave log or other events
+				let handler = self.clone();
+				tokio::spawn(async move {
+					let kick = moderation::on_kick(
+						&ctx.http,
+						&handler.actions,
+						event.guild_id,
+						&event.user,
+					)
+					.await;
+					handler.push(kick);
+				});
+				return;
+			},
+			| GuildMemberUpdate(event) => {
+				members::on_update(&ctx.http, &self.members, &self.actions, &event).await
+			},
+			| ChannelCreate(event) => channels::on_create(&self.structure, &event.channel)
+				.into_iter()
+				.collect(),
+			| ChannelDelete(event) => channels::on_delete(&self.structure, &event.channel)
+				.into_iter()
+				.collect(),
+			| ChannelUpdate(event) => channels::on_update(&self.structure, &event.channel)
+				.into_iter()
+				.collect(),
+			| GuildRoleCreate(event) => roles::on_create(&self.structure, &event.role)
+				.into_iter()
+				.collect(),
+			| GuildRoleDelete(event) => {
+				roles::on_delete(&self.structure, event.guild_id, event.role_id)
+					.into_iter()
+					.collect()
+			},
+			| GuildRoleUpdate(event) => roles::on_update(&self.structure, &event.role)
+				.into_iter()
+				.collect(),
+			| GuildUpdate(event) => server::on_update(&self.structure, &event.guild)
+				.into_iter()
+				.collect(),
+			| GuildEmojisUpdate(event) => {
+				server::on_emojis_update(&self.structure, event.guild_id, &event.emojis)
+					.into_iter()
+					.collect()
+			},
+			| VoiceStateUpdate(event) => {
+				voice::on_voice_state_update(&self.voice, &event.voice_state)
+			},
+			| GuildStickersUpdate(event) => {
+				server::on_stickers_update(&self.structure, event.guild_id, &event.stickers)
+					.into_iter()
+					.collect()
+			},
+			| GuildBanAdd(event) => {
+				moderation::on_ban(&ctx.http, &self.actions, event.guild_id, &event.user)
+					.await
+					.into_iter()
+					.collect()
+			},
+			| GuildBanRemove(event) => {
+				moderation::on_unban(&ctx.http, &self.actions, event.guild_id, &event.user)
+					.await
+					.into_iter()
+					.collect()
+			},
+			| _ => return,
+		};
+
+		self.push(logs);
+	}
+}
+
+impl Handler {
+	/// Queues the logs that aren't ignored
+	fn push(
+		&self,
+		logs: impl IntoIterator<Item = Log>,
+	) {
+		for log in logs {
+			let ignored = log
+				.subject
+				.as_ref()
+				.is_some_and(|subject| self.is_ignored(subject));
+			if !ignored {
+				self.queue.push(log);
+			}
+		}
+	}
+
+	/// Whether the subject's log is dropped, for being the bot's own output or by the guild's
+	/// ignore rules. When the rules can't be read the log is sent anyway
+	fn is_ignored(
+		&self,
+		subject: &Subject,
+	) -> bool {
+		let bot_id = self.identity.id();
+		let ignored = ignore::is_own_output(subject, bot_id).and_then(|own| {
+			Ok(own
+				|| ignore::is_ignored(
+					subject,
+					&self.structure,
+					&self.members,
+					&self.prefixes,
+					bot_id,
+				)?)
+		});
+
+		ignored.unwrap_or_else(|e| {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Couldn't check the ignore rules for a log in {}, sending \
+				 it anyway: {}",
+				subject.guild_id, e
+			);
+			false
+		})
+	}
+}
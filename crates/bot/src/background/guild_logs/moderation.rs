@@ -0,0 +1,96 @@
+use serenity::all::{Color, GuildId, Http, User};
+
+use super::audit::{find_responsible, responsible_fields};
+use super::ignore::Subject;
+use super::{Log, log_channel};
+use crate::utils::guild_settings::LogType;
+use crate::utils::mention::Mentionable;
+use crate::utils::mod_actions::{ModActionKind, ModActions};
+
+/// a ban always has an audit entry so it's worth waiting for one that is late
+const ATTEMPTS: u32 = 3;
+
+pub async fn on_ban(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	user: &User,
+) -> Option<Log> {
+	let channel = log_channel(guild_id, LogType::Ban)?;
+
+	let responsible = find_responsible(
+		http,
+		actions,
+		guild_id,
+		user.id,
+		ModActionKind::Ban,
+		ATTEMPTS,
+	)
+	.await;
+
+	let mut fields = vec![
+		("Banned User", user.mention(), true),
+		("ID", user.id.to_string(), true),
+	];
+	fields.extend(responsible_fields(&responsible));
+
+	let mut log = Log::new(channel, "User Banned", Color::RED, fields);
+	log.thumbnail = Some(user.face());
+	log.subject = Some(Subject::new(guild_id, LogType::Ban).user(user.id, user.bot));
+	Some(log)
+}
+
+pub async fn on_unban(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	user: &User,
+) -> Option<Log> {
+	let channel = log_channel(guild_id, LogType::Ban)?;
+
+	let responsible = find_responsible(
+		http,
+		actions,
+		guild_id,
+		user.id,
+		ModActionKind::Unban,
+		ATTEMPTS,
+	)
+	.await;
+
+	let mut fields = vec![
+		("Unbanned User", user.mention(), true),
+		("ID", user.id.to_string(), true),
+	];
+	fields.extend(responsible_fields(&responsible));
+
+	Some(
+		Log::new(channel, "User Unbanned", Color::DARK_GREEN, fields)
+			.about(Subject::new(guild_id, LogType::Ban).user(user.id, user.bot)),
+	)
+}
+
+/// discord sends kicks as a normal leave, only the audit log can tell them apart
+pub async fn on_kick(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	user: &User,
+) -> Option<Log> {
+	let channel = log_channel(guild_id, LogType::Ban)?;
+
+	// most leaves aren't kicks, so this gives up sooner than bans do
+	let responsible =
+		find_responsible(http, actions, guild_id, user.id, ModActionKind::Kick, 2).await?;
+
+	let mut fields = vec![
+		("Kicked User", user.mention(), true),
+		("ID", user.id.to_string(), true),
+	];
+	fields.extend(responsible_fields(&Some(responsible)));
+
+	let mut log = Log::new(channel, "User Kicked", Color::ORANGE, fields);
+	log.thumbnail = Some(user.face());
+	log.subject = Some(Subject::new(guild_id, LogType::Ban).user(user.id, user.bot));
+	Some(log)
+}
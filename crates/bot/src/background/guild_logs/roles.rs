@@ -0,0 +1,86 @@
+use serenity::all::{Color, GuildId, Role, RoleId};
+
+use super::diff::{Field, diff_fields, diff_permissions};
+use super::structure_cache::StructureCache;
+use super::{Log, log_channel};
+use crate::utils::guild_settings::LogType;
+
+fn role_fields(role: &Role) -> Vec<Field> {
+	let yes_no = |on: bool| match on {
+		| true => "Yes".to_string(),
+		| false => "No".to_string(),
+	};
+
+	vec![
+		("Name", role.name.clone()),
+		("Color", format!("#{}", role.colour.hex())),
+		("Shown separately", yes_no(role.hoist)),
+		("Mentionable", yes_no(role.mentionable)),
+		(
+			"Emoji",
+			role.unicode_emoji.clone().unwrap_or("*None*".to_string()),
+		),
+	]
+}
+
+pub fn on_create(
+	cache: &StructureCache,
+	role: &Role,
+) -> Option<Log> {
+	cache.replace_role(role);
+
+	let log = log_channel(role.guild_id, LogType::RoleChanges)?;
+
+	let mut fields = vec![("Role", format!("<@&{}> ({})", role.id, role.name), false)];
+	if !role.permissions.is_empty() {
+		fields.push(("Permissions", role.permissions.to_string(), false));
+	}
+
+	Some(Log::new(log, "Role Created", Color::DARK_GREEN, fields))
+}
+
+pub fn on_delete(
+	cache: &StructureCache,
+	guild_id: GuildId,
+	role_id: RoleId,
+) -> Option<Log> {
+	let role = cache.remove_role(guild_id, role_id);
+
+	let log = log_channel(guild_id, LogType::RoleChanges)?;
+
+	let name = match role {
+		| Some(role) => format!("@{} ({})", role.name, role_id),
+		| None => role_id.to_string(),
+	};
+
+	Some(Log::new(log, "Role Deleted", Color::RED, vec![(
+		"Role", name, false,
+	)]))
+}
+
+pub fn on_update(
+	cache: &StructureCache,
+	role: &Role,
+) -> Option<Log> {
+	let before = cache.replace_role(role)?;
+
+	let changes = diff_fields(&role_fields(&before), &role_fields(role));
+	let permissions = diff_permissions(before.permissions, role.permissions);
+
+	// moving one role sends an update for every role whose position shifted
+	if changes.is_empty() && permissions.is_empty() {
+		return None;
+	}
+
+	let log = log_channel(role.guild_id, LogType::RoleChanges)?;
+
+	let mut fields = vec![("Role", format!("<@&{}>", role.id), false)];
+	if !changes.is_empty() {
+		fields.push(("Changes", changes.join("\n"), false));
+	}
+	if !permissions.is_empty() {
+		fields.push(("Permissions", permissions.join("\n"), false));
+	}
+
+	Some(Log::new(log, "Role Updated", Color::GOLD, fields))
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use serenity::all::{Color, Emoji, EmojiId, Guild, GuildId, PartialGuild, Sticker, StickerId};
+
+use super::diff::{Field, diff_fields, show_channel, show_option};
+use super::structure_cache::StructureCache;
+use super::{Log, log_channel};
+use crate::utils::guild_settings::LogType;
+
+fn image_link(
+	kind: &str,
+	guild_id: GuildId,
+	hash: &Option<impl ToString>,
+) -> String {
+	match hash {
+		| Some(hash) => format!(
+			"[View](https://cdn.discordapp.com/{}/{}/{}.png)",
+			kind,
+			guild_id,
+			hash.to_string()
+		),
+		| None => "*None*".to_string(),
+	}
+}
+
+/// `Guild` and `PartialGuild` share these fields but no trait, so this works on either
+macro_rules! server_fields {
+	($guild:expr) => {{
+		let guild = $guild;
+		vec![
+			("Name", guild.name.clone()),
+			("Description", show_option(&guild.description)),
+			("Icon", image_link("icons", guild.id, &guild.icon)),
+			("Banner", image_link("banners", guild.id, &guild.banner)),
+			("Owner", format!("<@{}>", guild.owner_id)),
+			(
+				"Verification level",
+				format!("{:?}", guild.verification_level),
+			),
+			(
+				"Explicit content filter",
+				format!("{:?}", guild.explicit_content_filter),
+			),
+			(
+				"Default notifications",
+				format!("{:?}", guild.default_message_notifications),
+			),
+			("2FA requirement", format!("{:?}", guild.mfa_level)),
+			(
+				"AFK channel",
+				show_channel(&guild.afk_metadata.as_ref().map(|afk| afk.afk_channel_id)),
+			),
+			(
+				"AFK timeout",
+				show_option(
+					&guild
+						.afk_metadata
+						.as_ref()
+						.map(|afk| format!("{}s", u16::from(afk.afk_timeout))),
+				),
+			),
+			("System channel", show_channel(&guild.system_channel_id)),
+			("Rules channel", show_channel(&guild.rules_channel_id)),
+			(
+				"Community updates channel",
+				show_channel(&guild.public_updates_channel_id),
+			),
+			("Language", guild.preferred_locale.clone()),
+			("Vanity URL", show_option(&guild.vanity_url_code)),
+		]
+	}};
+}
+
+pub fn guild_fields(guild: &Guild) -> Vec<Field> {
+	server_fields!(guild)
+}
+
+pub fn on_update(
+	cache: &StructureCache,
+	guild: &PartialGuild,
+) -> Option<Log> {
+	let after: Vec<Field> = server_fields!(guild);
+	let before = cache.replace_settings(guild.id, after.clone())?;
+
+	let changes = diff_fields(&before, &after);
+	if changes.is_empty() {
+		return None;
+	}
+
+	let log = log_channel(guild.id, LogType::ServerChanges)?;
+
+	Some(Log::new(log, "Server Updated", Color::GOLD, vec![(
+		"Changes",
+		changes.join("\n"),
+		false,
+	)]))
+}
+
+fn emoji_text(emoji: &Emoji) -> String {
+	match emoji.animated {
+		| true => format!("<a:{}:{}>", emoji.name, emoji.id),
+		| false => format!("<:{}:{}>", emoji.name, emoji.id),
+	}
+}
+
+pub fn on_emojis_update(
+	cache: &StructureCache,
+	guild_id: GuildId,
+	emojis: &HashMap<EmojiId, Emoji>,
+) -> Option<Log> {
+	let before = cache.replace_emojis(guild_id, emojis)?;
+
+	let mut changes = vec![];
+	for (id, emoji) in emojis {
+		match before.get(id) {
+			| None => changes.push(format!("Added {} `:{}:`", emoji_text(emoji), emoji.name)),
+			| Some(old) if old.name != emoji.name => changes.push(format!(
+				"Renamed {} `:{}:` → `:{}:`",
+				emoji_text(emoji),
+				old.name,
+				emoji.name
+			)),
+			| Some(_) => {},
+		}
+	}
+	for (id, emoji) in &before {
+		if !emojis.contains_key(id) {
+			changes.push(format!("Removed `:{}:`", emoji.name));
+		}
+	}
+
+	if changes.is_empty() {
+		return None;
+	}
+
+	let log = log_channel(guild_id, LogType::EmojiChanges)?;
+
+	Some(Log::new(log, "Emojis Updated", Color::GOLD, vec![(
+		"Changes",
+		changes.join("\n"),
+		false,
+	)]))
+}
+
+pub fn on_stickers_update(
+	cache: &StructureCache,
+	guild_id: GuildId,
+	stickers: &HashMap<StickerId, Sticker>,
+) -> Option<Log> {
+	let before = cache.replace_stickers(guild_id, stickers)?;
+
+	let mut changes = vec![];
+	for (id, sticker) in stickers {
+		match before.get(id) {
+			| None => changes.push(format!("Added `{}`", sticker.name)),
+			| Some(old) if old.name != sticker.name => {
+				changes.push(format!("Renamed `{}` → `{}`", old.name, sticker.name))
+			},
+			| Some(old) if old.description != sticker.description => changes.push(format!(
+				"Changed the description of `{}` to {}",
+				sticker.name,
+				show_option(&sticker.description)
+			)),
+			| Some(_) => {},
+		}
+	}
+	for (id, sticker) in &before {
+		if !stickers.contains_key(id) {
+			changes.push(format!("Removed `{}`", sticker.name));
+		}
+	}
+
+	if changes.is_empty() {
+		return None;
+	}
+
+	let log = log_channel(guild_id, LogType::EmojiChanges)?;
+
+	Some(Log::new(log, "Stickers Updated", Color::GOLD, vec![(
+		"Changes",
+		changes.join("\n"),
+		false,
+	)]))
+}
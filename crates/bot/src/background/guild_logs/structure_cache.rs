@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serenity::all::{
+	ChannelId,
+	Emoji,
+	EmojiId,
+	Guild,
+	GuildChannel,
+	GuildId,
+	Role,
+	RoleId,
+	Sticker,
+	StickerId,
+};
+
+use super::diff::Field;
+
+#[derive(Default)]
+struct GuildStructure {
+	channels: HashMap<ChannelId, GuildChannel>,
+	roles:    HashMap<RoleId, Role>,
+	emojis:   HashMap<EmojiId, Emoji>,
+	stickers: HashMap<StickerId, Sticker>,
+	settings: Vec<Field>,
+}
+
+/// Channels, roles, emojis, stickers and settings per guild, kept so update events can be diffed
+/// against what they were before
+#[derive(Default)]
+pub struct StructureCache {
+	guilds: Mutex<HashMap<GuildId, GuildStructure>>,
+}
+
+impl StructureCache {
+	pub fn load_guild(
+		&self,
+		guild: &Guild,
+		settings: Vec<Field>,
+	) {
+		if let Ok(mut guilds) = self.guilds.lock() {
+			guilds.insert(guild.id, GuildStructure {
+				channels: guild.channels.clone(),
+				roles: guild.roles.clone(),
+				emojis: guild.emojis.clone(),
+				stickers: guild.stickers.clone(),
+				settings,
+			});
+		}
+	}
+
+	pub fn forget_guild(
+		&self,
+		guild_id: GuildId,
+	) {
+		if let Ok(mut guilds) = self.guilds.lock() {
+			guilds.remove(&guild_id);
+		}
+	}
+
+	/// stores the new channel, returning the old one if it was known
+	pub fn replace_channel(
+		&self,
+		channel: &GuildChannel,
+	) -> Option<GuildChannel> {
+		let mut guilds = self.guilds.lock().ok()?;
+		guilds
+			.entry(channel.guild_id)
+			.or_default()
+			.channels
+			.insert(channel.id, channel.clone())
+	}
+
+	pub fn remove_channel(
+		&self,
+		channel: &GuildChannel,
+	) -> Option<GuildChannel> {
+		let mut guilds = self.guilds.lock().ok()?;
+		guilds
+			.get_mut(&channel.guild_id)?
+			.channels
+			.remove(&channel.id)
+	}
+
+	/// the category a channel is in
+	pub fn parent(
+		&self,
+		guild_id: GuildId,
+		channel_id: ChannelId,
+	) -> Option<ChannelId> {
+		self.guilds
+			.lock()
+			.ok()?
+			.get(&guild_id)?
+			.channels
+			.get(&channel_id)?
+			.parent_id
+	}
+
+	pub fn replace_role(
+		&self,
+		role: &Role,
+	) -> Option<Role> {
+		let mut guilds = self.guilds.lock().ok()?;
+		guilds
+			.entry(role.guild_id)
+			.or_default()
+			.roles
+			.insert(role.id, role.clone())
+	}
+
+	pub fn remove_role(
+		&self,
+		guild_id: GuildId,
+		role_id: RoleId,
+	) -> Option<Role> {
+		let mut guilds = self.guilds.lock().ok()?;
+		guilds.get_mut(&guild_id)?.roles.remove(&role_id)
+	}
+
+	/// the whole emoji list is sent on every change, returns the previous list if it was known
+	pub fn replace_emojis(
+		&self,
+		guild_id: GuildId,
+		emojis: &HashMap<EmojiId, Emoji>,
+	) -> Option<HashMap<EmojiId, Emoji>> {
+		let mut guilds = self.guilds.lock().ok()?;
+		let guild = guilds.get_mut(&guild_id)?;
+		Some(std::mem::replace(&mut guild.emojis, emojis.clone()))
+	}
+
+	pub fn replace_stickers(
+		&self,
+		guild_id: GuildId,
+		stickers: &HashMap<StickerId, Sticker>,
+	) -> Option<HashMap<StickerId, Sticker>> {
+		let mut guilds = self.guilds.lock().ok()?;
+		let guild = guilds.get_mut(&guild_id)?;
+		Some(std::mem::replace(&mut guild.stickers, stickers.clone()))
+	}
+
+	pub fn replace_settings(
+		&self,
+		guild_id: GuildId,
+		settings: Vec<Field>,
+	) -> Option<Vec<Field>> {
+		let mut guilds = self.guilds.lock().ok()?;
+		let guild = guilds.get_mut(&guild_id)?;
+		Some(std::mem::replace(&mut guild.settings, settings))
+	}
+}
@@ -0,0 +1,147 @@
+use std::time::Instant;
+
+use serenity::all::{Color, VoiceState};
+
+use super::ignore::Subject;
+use super::voice_cache::{VoiceCache, VoiceSession};
+use super::{Log, log_channel};
+use crate::utils::dates::format_duration;
+use crate::utils::guild_settings::LogType;
+use crate::utils::mention::Mentionable;
+
+fn since(instant: Instant) -> String {
+	format_duration(instant.elapsed().as_secs())
+}
+
+pub fn on_voice_state_update(
+	cache: &VoiceCache,
+	state: &VoiceState,
+) -> Vec<Log> {
+	let Some(guild_id) = state.guild_id else {
+		return vec![];
+	};
+	let user = state.user_id.mention();
+
+	let before = cache.get(guild_id, state.user_id);
+
+	let after = match (&before, state.channel_id) {
+		| (_, None) => None,
+		| (None, Some(channel_id)) => Some(VoiceSession::new(channel_id, state)),
+		| (Some(before), Some(channel_id)) => {
+			let now = Instant::now();
+			let streaming = state.self_stream.unwrap_or(false);
+			Some(VoiceSession {
+				channel_id,
+				started_at: before.started_at,
+				channel_joined: match before.channel_id == channel_id {
+					| true => before.channel_joined,
+					| false => now,
+				},
+				mute: state.mute,
+				deaf: state.deaf,
+				streaming: match (before.streaming, streaming) {
+					| (Some(started), true) => Some(started),
+					| (None, true) => Some(now),
+					| (_, false) => None,
+				},
+			})
+		},
+	};
+
+	cache.set(guild_id, state.user_id, after.clone());
+
+	let Some(channel) = log_channel(guild_id, LogType::Voice) else {
+		return vec![];
+	};
+
+	let bot = state.member.as_ref().is_some_and(|m| m.user.bot);
+	let mut subject = Subject::new(guild_id, LogType::Voice).user(state.user_id, bot);
+	if let Some(voice_channel) = state.channel_id.or(before.as_ref().map(|b| b.channel_id)) {
+		subject = subject.channel(voice_channel);
+	}
+	let about = |logs: Vec<Log>| -> Vec<Log> {
+		logs.into_iter()
+			.map(|log| log.about(subject.clone()))
+			.collect()
+	};
+
+	let mut logs = vec![];
+
+	match (&before, &after) {
+		| (None, Some(after)) => {
+			logs.push(Log::new(channel, "Joined Voice", Color::DARK_GREEN, vec![
+				("User", user.clone(), true),
+				("Channel", format!("<#{}>", after.channel_id), true),
+			]));
+		},
+		| (Some(before), None) => {
+			let mut fields = vec![
+				("User", user.clone(), true),
+				("Channel", format!("<#{}>", before.channel_id), true),
+				("Time in channel", since(before.channel_joined), true),
+			];
+			if before.started_at != before.channel_joined {
+				fields.push(("Session length", since(before.started_at), true));
+			}
+			logs.push(Log::new(channel, "Left Voice", Color::DARK_RED, fields));
+		},
+		| (Some(before), Some(after)) if before.channel_id != after.channel_id => {
+			logs.push(Log::new(channel, "Moved Voice Channel", Color::BLUE, vec![
+				("User", user.clone(), true),
+				("From", format!("<#{}>", before.channel_id), true),
+				("To", format!("<#{}>", after.channel_id), true),
+				(
+					"Time in previous channel",
+					since(before.channel_joined),
+					true,
+				),
+			]));
+		},
+		| _ => {},
+	}
+
+	let (Some(before), Some(after)) = (&before, &after) else {
+		return about(logs);
+	};
+
+	if before.mute != after.mute {
+		let title = match after.mute {
+			| true => "Server Muted",
+			| false => "Server Unmuted",
+		};
+		logs.push(Log::new(channel, title, Color::ORANGE, vec![
+			("User", user.clone(), true),
+			("Channel", format!("<#{}>", after.channel_id), true),
+		]));
+	}
+
+	if before.deaf != after.deaf {
+		let title = match after.deaf {
+			| true => "Server Deafened",
+			| false => "Server Undeafened",
+		};
+		logs.push(Log::new(channel, title, Color::ORANGE, vec![
+			("User", user.clone(), true),
+			("Channel", format!("<#{}>", after.channel_id), true),
+		]));
+	}
+
+	match (before.streaming, after.streaming) {
+		| (None, Some(_)) => {
+			logs.push(Log::new(channel, "Started Streaming", Color::PURPLE, vec![
+				("User", user.clone(), true),
+				("Channel", format!("<#{}>", after.channel_id), true),
+			]));
+		},
+		| (Some(started), None) => {
+			logs.push(Log::new(channel, "Stopped Streaming", Color::PURPLE, vec![
+				("User", user.clone(), true),
+				("Channel", format!("<#{}>", after.channel_id), true),
+				("Stream length", since(started), true),
+			]));
+		},
+		| _ => {},
+	}
+
+	about(logs)
+}
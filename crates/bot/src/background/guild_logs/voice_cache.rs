@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serenity::all::{ChannelId, GuildId, UserId, VoiceState};
+
+/// Where a member is in voice and since when
+#[derive(Clone)]
+pub struct VoiceSession {
+	pub channel_id:     ChannelId,
+	/// when they joined voice at all, moving between channels keeps this
+	pub started_at:     Instant,
+	/// when they joined `channel_id`
+	pub channel_joined: Instant,
+	pub mute:           bool,
+	pub deaf:           bool,
+	pub streaming:      Option<Instant>,
+}
+
+impl VoiceSession {
+	pub fn new(
+		channel_id: ChannelId,
+		state: &VoiceState,
+	) -> Self {
+		let now = Instant::now();
+		VoiceSession {
+			channel_id,
+			started_at: now,
+			channel_joined: now,
+			mute: state.mute,
+			deaf: state.deaf,
+			streaming: state.self_stream.unwrap_or(false).then_some(now),
+		}
+	}
+}
+
+/// Voice sessions per guild, durations for people already in voice when the bot started are
+/// counted from the start
+#[derive(Default)]
+pub struct VoiceCache {
+	sessions: Mutex<HashMap<(GuildId, UserId), VoiceSession>>,
+}
+
+impl VoiceCache {
+	pub fn load_guild(
+		&self,
+		guild_id: GuildId,
+		states: &HashMap<UserId, VoiceState>,
+	) {
+		let Ok(mut sessions) = self.sessions.lock() else {
+			return;
+		};
+		sessions.retain(|(id, _), _| *id != guild_id);
+		for (user_id, state) in states {
+			if let Some(channel_id) = state.channel_id {
+				sessions.insert((guild_id, *user_id), VoiceSession::new(channel_id, state));
+			}
+		}
+	}
+
+	pub fn forget_guild(
+		&self,
+		guild_id: GuildId,
+	) {
+		if let Ok(mut sessions) = self.sessions.lock() {
+			sessions.retain(|(id, _), _| *id != guild_id);
+		}
+	}
+
+	pub fn get(
+		&self,
+		guild_id: GuildId,
+		user_id: UserId,
+	) -> Option<VoiceSession> {
+		self.sessions
+			.lock()
+			.ok()?
+			.get(&(guild_id, user_id))
+			.cloned()
+	}
+
+	pub fn set(
+		&self,
+		guild_id: GuildId,
+		user_id: UserId,
+		session: Option<VoiceSession>,
+	) {
+		let Ok(mut sessions) = self.sessions.lock() else {
+			return;
+		};
+		match session {
+			| Some(session) => sessions.insert((guild_id, user_id), session),
+			| None => sessions.remove(&(guild_id, user_id)),
+		};
+	}
+}
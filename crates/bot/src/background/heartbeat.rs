@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serenity::all::{ConnectionStage, ShardManager};
+
+use crate::config::{BOT_STATUS_PATH, CONFIG_DB_PATH, LOG_CHANNELS_DB_PATH};
+use crate::utils::db;
+use crate::utils::errors::BotError;
+
+const INTERVAL: Duration = Duration::from_secs(15);
+/// sled lets one process open a store, so the bot reads them for the api's health check. the
+/// api looks for the same ones
+const SETTINGS_STORES: [&str; 2] = [CONFIG_DB_PATH, LOG_CHANNELS_DB_PATH];
+
+/// Snapshot of the gateway connection, read by the api's `/health` endpoint
+#[derive(Serialize)]
+struct BotStatus {
+	connected:     bool,
+	latency_ms:    Option<u128>,
+	shards:        Vec<ShardStatus>,
+	version:       &'static str,
+	started_at:    u64,
+	updated_at:    u64,
+	/// why the settings stores couldn't be read
+	storage_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ShardStatus {
+	id:         u32,
+	stage:      String,
+	latency_ms: Option<u128>,
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
+}
+
+pub async fn run(shard_manager: Arc<ShardManager>) {
+	let started_at = unix_now();
+	let mut interval = tokio::time::interval(INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let runners = shard_manager.runners.lock().await;
+		let connected = !runners.is_empty()
+			&& runners
+				.values()
+				.all(|info| info.stage == ConnectionStage::Connected);
+		let shards: Vec<ShardStatus> = runners
+			.iter()
+			.map(|(id, info)| ShardStatus {
+				id:         id.0,
+				stage:      info.stage.to_string(),
+				latency_ms: info.latency.map(|l| l.as_millis()),
+			})
+			.collect();
+		drop(runners);
+
+		// average over the shards that have actually received a heartbeat ack
+		let latencies: Vec<u128> = shards.iter().filter_map(|s| s.latency_ms).collect();
+		let latency_ms = (!latencies.is_empty())
+			.then(|| latencies.iter().sum::<u128>() / latencies.len() as u128);
+
+		let storage_error = match tokio::task::spawn_blocking(read_settings_stores).await {
+			| Ok(Ok(())) => None,
+			| Ok(Err(e)) => Some(e.to_string()),
+			| Err(e) => Some(e.to_string()),
+		};
+
+		let status = BotStatus {
+			connected,
+			latency_ms,
+			shards,
+			version: env!("CARGO_PKG_VERSION"),
+			started_at,
+			updated_at: unix_now(),
+			storage_error,
+		};
+
+		if let Err(e) = write_status(&status).await {
+			println!("\x1b[31;1m[ERROR] Failed to write bot status:\x1b[0m {}", e);
+		}
+	}
+}
+
+/// a real read from each store, which also creates them on a fresh install
+fn read_settings_stores() -> Result<(), BotError> {
+	for path in SETTINGS_STORES {
+		db::open(path)?.get(b"health_check")?;
+	}
+	Ok(())
+}
+
+/// writes to a temp file first so the api never reads a half written file
+async fn write_status(status: &BotStatus) -> std::io::Result<()> {
+	let json = serde_json::to_vec(status)?;
+	let tmp_path = format!("{}.tmp", BOT_STATUS_PATH);
+	tokio::fs::write(&tmp_path, json).await?;
+	tokio::fs::rename(&tmp_path, BOT_STATUS_PATH).await
+}
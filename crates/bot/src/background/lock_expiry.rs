@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::all::Http;
+
+use crate::utils::channel_locks::{self, expired};
+
+const INTERVAL: Duration = Duration::from_secs(30);
+
+/// Lifts timed channel locks once they run out, stored locks survive restarts
+pub async fn run(http: Arc<Http>) {
+	let mut interval = tokio::time::interval(INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let expired = match expired() {
+			| Ok(expired) => expired,
+			| Err(e) => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't read the channel locks: {}",
+					e
+				);
+				continue;
+			},
+		};
+
+		for (guild_id, channel_id) in expired {
+			match channel_locks::unlock(&http, guild_id, channel_id).await {
+				| Ok(true) => {
+					if let Ok(channel) = channel_id.to_channel(&http).await
+						&& let Some(channel) = channel.guild()
+					{
+						channel_locks::notify(&http, &channel, None).await;
+					}
+				},
+				| Ok(false) => {},
+				| Err(e) => println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't lift the lock on {}: {}",
+					channel_id, e
+				),
+			}
+		}
+	}
+}
@@ -1 +1,9 @@
+pub mod api_changes;
+pub mod automod;
 pub mod guild_logs;
+pub mod heartbeat;
+pub mod lock_expiry;
+pub mod mute_enforcement;
+pub mod mute_expiry;
+pub mod slowmode_schedules;
+pub mod welcome;
@@ -0,0 +1,164 @@
+use poise::serenity_prelude::{Context, FullEvent};
+use serenity::all::{EditMember, GuildChannel, GuildMemberUpdateEvent, Member, Timestamp};
+
+use crate::Data;
+use crate::commands::moderation::mute::deny_muted_role;
+use crate::commands::moderation::warn::add_warn;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::muted_role;
+use crate::utils::mod_actions::ModActionKind;
+use crate::utils::mutes::{MuteKind, mute_of, set_mute};
+
+const EVASION_REASON: &str = "Mute evasion: left and rejoined while muted";
+
+/// Keeps mutes working in channels made after the muted role was set up, and on members who
+/// leave and rejoin to get rid of it. Mutes lifted by hand are forgotten, so rejoining after that
+/// isn't evasion
+pub async fn on_event(
+	ctx: &Context,
+	event: &FullEvent,
+	data: &Data,
+) {
+	let result = match event {
+		| FullEvent::ChannelCreate {
+			channel,
+		} => enforce_overwrite(ctx, channel).await,
+		| FullEvent::ChannelUpdate {
+			new, ..
+		} => enforce_overwrite(ctx, new).await,
+		| FullEvent::GuildMemberAddition {
+			new_member,
+		} => on_rejoin(ctx, new_member, data).await,
+		| FullEvent::GuildMemberUpdate {
+			event, ..
+		} => on_update(event),
+		| _ => Ok(()),
+	};
+
+	if let Err(e) = result {
+		println!("\x1b[33;1m[WARN]\x1b[0m Couldn't enforce mutes: {}", e);
+	}
+}
+
+async fn enforce_overwrite(
+	ctx: &Context,
+	channel: &GuildChannel,
+) -> Result<(), BotError> {
+	let Some(role_id) = muted_role(channel.guild_id)? else {
+		return Ok(());
+	};
+
+	if let Err(e) = deny_muted_role(&ctx.http, channel, role_id).await {
+		println!(
+			"\x1b[33;1m[WARN]\x1b[0m Couldn't set the muted role's permissions in {}: {}",
+			channel.id, e
+		);
+	}
+	Ok(())
+}
+
+/// forgets the mute once the role or timeout it relies on is gone
+fn on_update(event: &GuildMemberUpdateEvent) -> Result<(), BotError> {
+	let Some(mute) = mute_of(event.guild_id, event.user.id)? else {
+		return Ok(());
+	};
+
+	let lifted = match mute.kind {
+		| MuteKind::Role => match muted_role(event.guild_id)? {
+			| Some(role_id) => !event.roles.contains(&role_id),
+			| None => true,
+		},
+		| MuteKind::Timeout => event
+			.communication_disabled_until
+			.is_none_or(|until| until <= Timestamp::now()),
+	};
+
+	if lifted {
+		set_mute(event.guild_id, event.user.id, None)?;
+	}
+	Ok(())
+}
+
+async fn on_rejoin(
+	ctx: &Context,
+	member: &Member,
+	data: &Data,
+) -> Result<(), BotError> {
+	let guild_id = member.guild_id;
+	let Some(mute) = mute_of(guild_id, member.user.id)? else {
+		return Ok(());
+	};
+	let now = Timestamp::now().unix_timestamp();
+	if mute.until.is_some_and(|until| until <= now) {
+		return Ok(());
+	}
+	let moderator = data.identity.id().unwrap_or(mute.moderator);
+
+	let reapplied = match mute.kind {
+		| MuteKind::Role => {
+			let Some(role_id) = muted_role(guild_id)? else {
+				return Ok(());
+			};
+			data.mod_actions.record(
+				guild_id,
+				member.user.id,
+				ModActionKind::RoleUpdate,
+				moderator,
+				Some(EVASION_REASON),
+			);
+			member
+				.add_role(&ctx.http, role_id)
+				.await
+				.map_err(|e| e.to_string())
+		},
+		// discord keeps timeouts through a rejoin, this only catches ones it dropped
+		| MuteKind::Timeout => {
+			let timed_out = member
+				.communication_disabled_until
+				.is_some_and(|until| until.unix_timestamp() > now);
+			let Some(until) = mute
+				.until
+				.and_then(|until| Timestamp::from_unix_timestamp(until).ok())
+			else {
+				return Ok(());
+			};
+			if timed_out {
+				return Ok(());
+			}
+			data.mod_actions.record(
+				guild_id,
+				member.user.id,
+				ModActionKind::Timeout,
+				moderator,
+				Some(EVASION_REASON),
+			);
+			guild_id
+				.edit_member(
+					&ctx.http,
+					member.user.id,
+					EditMember::new()
+						.disable_communication_until_datetime(until)
+						.audit_log_reason(EVASION_REASON),
+				)
+				.await
+				.map(|_| ())
+				.map_err(|e| e.to_string())
+		},
+	};
+
+	if let Err(e) = reapplied {
+		println!(
+			"\x1b[33;1m[WARN]\x1b[0m Couldn't mute {} again after they rejoined {}: {}",
+			member.user.id, guild_id, e
+		);
+	}
+
+	// the warn is the case on their record, whether or not muting them again worked
+	if let Err(e) = add_warn(&member.user, guild_id, EVASION_REASON).await {
+		println!(
+			"\x1b[33;1m[WARN]\x1b[0m Couldn't record the mute evasion of {}: {}",
+			member.user.id, e
+		);
+	}
+	Ok(())
+}
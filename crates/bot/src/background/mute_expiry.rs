@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::all::Http;
+
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::muted_role;
+use crate::utils::mutes::{MuteKind, expired, set_mute};
+
+const INTERVAL: Duration = Duration::from_secs(30);
+
+/// Takes the muted role away once role mutes run out, timeouts are lifted by discord
+pub async fn run(http: Arc<Http>) {
+	let mut interval = tokio::time::interval(INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let expired = match expired() {
+			| Ok(expired) => expired,
+			| Err(e) => {
+				println!("\x1b[33;1m[WARN]\x1b[0m Couldn't read the mutes: {}", e);
+				continue;
+			},
+		};
+
+		for (guild_id, user_id, mute) in expired {
+			let role_id = match mute.kind {
+				| MuteKind::Role => match muted_role(guild_id) {
+					| Ok(role_id) => role_id,
+					| Err(e) => {
+						println!(
+							"\x1b[33;1m[WARN]\x1b[0m Couldn't read the muted role of {}: {}",
+							guild_id, e
+						);
+						continue;
+					},
+				},
+				| MuteKind::Timeout => None,
+			};
+
+			if let Some(role_id) = role_id
+				&& let Err(e) = http
+					.remove_member_role(guild_id, user_id, role_id, Some("Mute ran out"))
+					.await
+			{
+				match BotError::from(e) {
+					// they left, the role is gone with them
+					| BotError::NotFound(_) => {},
+					| e => {
+						println!(
+							"\x1b[33;1m[WARN]\x1b[0m Couldn't unmute {} in {}: {}",
+							user_id, guild_id, e
+						);
+						continue;
+					},
+				}
+			}
+
+			if let Err(e) = set_mute(guild_id, user_id, None) {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't forget the mute of {} in {}: {}",
+					user_id, guild_id, e
+				);
+			}
+		}
+	}
+}
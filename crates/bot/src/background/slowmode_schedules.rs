@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Timelike, Utc};
+use serenity::all::Http;
+
+use crate::background::guild_logs::log_queue::LogQueue;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::utc_offset;
+use crate::utils::slowmode::{all_schedules, set_previous, set_schedule, set_slowmode};
+
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// Turns scheduled slowmode on and off as windows start and end in each guild's timezone
+pub async fn run(
+	http: Arc<Http>,
+	queue: Arc<LogQueue>,
+) {
+	let mut interval = tokio::time::interval(INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let schedules = match all_schedules() {
+			| Ok(schedules) => schedules,
+			| Err(e) => {
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't read the slowmode schedules: {}",
+					e
+				);
+				continue;
+			},
+		};
+
+		for (guild_id, channel_id, schedule) in schedules {
+			let offset = match utc_offset(guild_id) {
+				| Ok(offset) => offset,
+				| Err(e) => {
+					println!(
+						"\x1b[33;1m[WARN]\x1b[0m Couldn't read the timezone of {}: {}",
+						guild_id, e
+					);
+					continue;
+				},
+			};
+			let now = Utc::now().with_timezone(&offset);
+			let minute = (now.hour() * 60 + now.minute()) as u16;
+			let by = format!("Schedule ({})", schedule.window());
+
+			let (seconds, previous) = match (schedule.contains(minute), schedule.previous) {
+				| (true, None) => {
+					let current = match channel_id.to_channel(&http).await {
+						| Ok(channel) => channel
+							.guild()
+							.and_then(|c| c.rate_limit_per_user)
+							.unwrap_or(0),
+						| Err(e) => {
+							if let BotError::NotFound(_) = BotError::from(e) {
+								// the channel is gone, so is its schedule
+								let _ = set_schedule(guild_id, channel_id, None);
+							}
+							continue;
+						},
+					};
+					(schedule.seconds, Some(current))
+				},
+				| (false, Some(previous)) => (previous, None),
+				| _ => continue,
+			};
+
+			match set_slowmode(&http, &queue, guild_id, channel_id, seconds as u64, by).await {
+				| Ok(()) => {
+					if let Err(e) = set_previous(guild_id, channel_id, previous) {
+						println!(
+							"\x1b[33;1m[WARN]\x1b[0m Couldn't save the slowmode schedule for {}: {}",
+							channel_id, e
+						);
+					}
+				},
+				| Err(e) => println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't apply the slowmode schedule in {}: {}",
+					channel_id, e
+				),
+			}
+		}
+	}
+}
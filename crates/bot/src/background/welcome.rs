@@ -0,0 +1,30 @@
+use poise::serenity_prelude::Context;
+use serenity::all::{Color, CreateEmbed, CreateMessage, Guild};
+
+/// Points whoever added the bot at `/setup`, sent to the system channel if the guild has one
+pub async fn send_setup_prompt(
+	ctx: &Context,
+	guild: &Guild,
+) {
+	let Some(channel_id) = guild.system_channel_id else {
+		return;
+	};
+
+	let embed = CreateEmbed::new()
+		.title("Thanks for adding me!")
+		.description(
+			"Run `/setup` to pick your moderator roles, muted role, report channel and log \
+			 channels. Everything can be changed later with `/config`.",
+		)
+		.color(Color::BLURPLE);
+
+	if let Err(e) = channel_id
+		.send_message(&ctx.http, CreateMessage::new().embed(embed))
+		.await
+	{
+		println!(
+			"\x1b[33;1m[WARN]\x1b[0m Couldn't send the setup prompt in {}: {}",
+			guild.name, e
+		);
+	}
+}
@@ -4,7 +4,8 @@
 pub mod reporting;
 pub mod tags;
 pub mod moderation;
+pub mod settings;
 
 pub fn get_all_commands() -> Vec<poise::Command<crate::Data, crate::Error>> {
-	vec![reporting::report_message::report_message(), reporting::report_user::report_user(), tags::dtag::dtag(), tags::tag::tag(), moderation::unban::unban(), moderation::dban::dban(), moderation::warn::warn(), moderation::mute::mute(), moderation::ban::ban(), moderation::kick::kick()]
+	vec![reporting::report_message::report_message(), reporting::report_user::report_user(), tags::dtag::dtag(), tags::tag::tag(), moderation::unban::unban(), moderation::dban::dban(), moderation::warn::warn(), moderation::mute::mute(), moderation::unmute::unmute(), moderation::mutes::mutes(), moderation::ban::ban(), moderation::kick::kick(), moderation::raid::raid(), moderation::lock::lock(), moderation::unlock::unlock(), moderation::lockdown::lockdown(), moderation::slowmode::slowmode(), settings::automod::automod(), settings::config::config(), settings::logignore::logignore(), settings::setup::setup()]
 }
\ No newline at end of file
@@ -2,6 +2,9 @@ use poise::CreateReply;
 use serenity::all::User;
 
 use crate::utils::dm_notifier_utils::send_mod_action_reason_dm;
+use crate::utils::errors::BotError;
+use crate::utils::mod_actions::ModActionKind;
+use crate::utils::moderation_utils::check_hierarchy;
 use crate::{Context, Error};
 
 /// Ban a guild member and delete all messages
@@ -17,26 +20,35 @@ pub async fn dban(
 
 	// todo: check for config admin role
 
-	let guild_id = ctx
-		.guild_id()
-		.ok_or("This command can only be used in a guild")?;
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
 	let reason_text = reason.as_deref().unwrap_or("No reason provided");
 
-	let ban_result = guild_id
+	check_hierarchy(ctx, &user).await?;
+
+	ctx.data().mod_actions.record(
+		guild_id,
+		user.id,
+		ModActionKind::Ban,
+		ctx.author().id,
+		Some(reason_text),
+	);
+
+	guild_id
 		.ban_with_reason(&ctx.serenity_context().http, user.id, 0, reason_text)
 		.await
-		.err()
-		.map(|e| format!("❌ Failed to ban user: {}\n", e));
-
-	let mut response: String;
-	if let Some(ban_result) = ban_result {
-		response = ban_result;
-	} else {
-		response = format!("✅ Banned {}.\n", user.name);
-		match send_mod_action_reason_dm(ctx, &user, "banned", reason_text).await {
-			| Ok(()) => response.push_str("✅ DM sent successfully."),
-			| Err(_) => response.push_str("❌ Could not send DM."),
-		}
+		.map_err(|e| {
+			ctx.data()
+				.mod_actions
+				.forget(guild_id, user.id, ModActionKind::Ban);
+			BotError::from(e).with_permission_denied(
+				"I can't ban members here, make sure I have the Ban Members permission.",
+			)
+		})?;
+
+	let mut response = format!("✅ Banned {}.\n", user.name);
+	match send_mod_action_reason_dm(ctx, &user, "banned", reason_text).await {
+		| Ok(()) => response.push_str("✅ DM sent successfully."),
+		| Err(_) => response.push_str("❌ Could not send DM."),
 	}
 
 	// todo: purge rest of their messages, waiting on /purge backend
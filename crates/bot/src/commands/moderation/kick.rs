@@ -2,6 +2,9 @@ use poise::CreateReply;
 use serenity::all::User;
 
 use crate::utils::dm_notifier_utils::send_mod_action_reason_dm;
+use crate::utils::errors::BotError;
+use crate::utils::mod_actions::ModActionKind;
+use crate::utils::moderation_utils::check_hierarchy;
 use crate::{Context, Error};
 
 /// Kick a guild member
@@ -17,26 +20,37 @@ pub async fn kick(
 
 	// todo: check for config moderator role
 
-	let guild_id = ctx
-		.guild_id()
-		.ok_or("This command can only be used in a guild")?;
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
 	let reason_text = reason.as_deref().unwrap_or("No reason provided");
 
-	let kick_result = guild_id
+	check_hierarchy(ctx, &user).await?;
+
+	ctx.data().mod_actions.record(
+		guild_id,
+		user.id,
+		ModActionKind::Kick,
+		ctx.author().id,
+		Some(reason_text),
+	);
+
+	guild_id
 		.kick_with_reason(&ctx.serenity_context().http, user.id, reason_text)
 		.await
-		.err()
-		.map(|e| format!("❌ Failed to kick user: {}\n", e));
-
-	let mut response: String;
-	if let Some(kick_result) = kick_result {
-		response = kick_result;
-	} else {
-		response = format!("✅ Muted {}.\n", user.name);
-		match send_mod_action_reason_dm(ctx, &user, "kicked", reason_text).await {
-			| Ok(()) => response.push_str("✅ DM sent successfully."),
-			| Err(_) => response.push_str("❌ Could not send DM."),
-		}
+		.map_err(|e| {
+			ctx.data()
+				.mod_actions
+				.forget(guild_id, user.id, ModActionKind::Kick);
+			BotError::from(e)
+				.with_not_found(format!("{} isn't in this server.", user.name))
+				.with_permission_denied(
+					"I can't kick members here, make sure I have the Kick Members permission.",
+				)
+		})?;
+
+	let mut response = format!("✅ Kicked {}.\n", user.name);
+	match send_mod_action_reason_dm(ctx, &user, "kicked", reason_text).await {
+		| Ok(()) => response.push_str("✅ DM sent successfully."),
+		| Err(_) => response.push_str("❌ Could not send DM."),
 	}
 
 	ctx.send(
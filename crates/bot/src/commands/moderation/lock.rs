@@ -0,0 +1,114 @@
+use poise::CreateReply;
+use serenity::all::{GuildChannel, Timestamp};
+
+use crate::utils::channel_locks::{self, is_lockable};
+use crate::utils::dates::{format_duration, parse_duration};
+use crate::utils::errors::BotError;
+use crate::{Context, Error};
+
+/// Turns a duration option into the unix timestamp a lock ends at
+fn lock_until(duration: Option<&str>) -> Result<Option<i64>, BotError> {
+	let Some(duration) = duration else {
+		return Ok(None);
+	};
+	let secs = parse_duration(duration).ok_or(BotError::InvalidInput(
+		"Durations look like `30m`, `2h` or `1d12h`.".into(),
+	))?;
+	i64::try_from(secs)
+		.ok()
+		.and_then(|secs| Timestamp::now().unix_timestamp().checked_add(secs))
+		.map(Some)
+		.ok_or(BotError::InvalidInput("That duration is too long.".into()))
+}
+
+/// [`lock_until`] for the command's options. With text commands the first word of the reason lands
+/// in `duration` when there is none, so a duration that doesn't parse goes back into the reason
+pub fn until_and_reason(
+	ctx: Context<'_>,
+	duration: Option<String>,
+	reason: Option<String>,
+) -> Result<(Option<i64>, Option<String>), BotError> {
+	match duration.as_deref().map(parse_duration) {
+		| Some(None) if ctx.prefix() != "/" => Ok((
+			None,
+			Some(
+				[duration, reason]
+					.into_iter()
+					.flatten()
+					.collect::<Vec<_>>()
+					.join(" "),
+			),
+		)),
+		| _ => Ok((lock_until(duration.as_deref())?, reason)),
+	}
+}
+
+/// The channel given, or the one the command was used in
+pub async fn target_channel(
+	ctx: Context<'_>,
+	channel: Option<GuildChannel>,
+) -> Result<GuildChannel, Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let channel = match channel {
+		| Some(channel) => channel,
+		| None => ctx.guild_channel().await.ok_or(BotError::InvalidInput(
+			"Pick a channel, threads can't be locked on their own.".into(),
+		))?,
+	};
+
+	if channel.guild_id != guild_id || !is_lockable(&channel) {
+		return Err(BotError::InvalidInput(format!(
+			"<#{}> can't be locked.",
+			channel.id
+		)));
+	}
+	Ok(channel)
+}
+
+/// Stop everyone from sending messages in a channel
+#[poise::command(
+	prefix_command,
+	slash_command,
+	default_member_permissions = "MANAGE_CHANNELS",
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+pub async fn lock(
+	ctx: Context<'_>,
+	#[description = "Channel to lock, defaults to this one"] channel: Option<GuildChannel>,
+	#[description = "How long to lock it for, like 30m or 2h"] duration: Option<String>,
+	#[rest]
+	#[description = "Reason, shown in the channel"]
+	reason: Option<String>,
+) -> Result<(), Error> {
+	ctx.defer().await?;
+
+	let channel = target_channel(ctx, channel).await?;
+	let (until, reason) = until_and_reason(ctx, duration, reason)?;
+
+	let notice = channel_locks::notice(true, until, reason.as_deref());
+	if !channel_locks::lock(ctx.http(), &channel, until, false, Some(notice)).await? {
+		return Err(BotError::InvalidInput(format!(
+			"<#{}> is already locked.",
+			channel.id
+		)));
+	}
+
+	let response = match until {
+		| Some(until) => format!(
+			"✅ Locked <#{}> for {}.",
+			channel.id,
+			format_duration((until - Timestamp::now().unix_timestamp()).max(0) as u64)
+		),
+		| None => format!("✅ Locked <#{}>.", channel.id),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
@@ -0,0 +1,123 @@
+use poise::CreateReply;
+
+use crate::commands::moderation::lock::until_and_reason;
+use crate::utils::channel_locks::{self, is_lockable};
+use crate::utils::errors::BotError;
+use crate::{Context, Error};
+
+/// Lock or unlock every channel at once
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("start", "end"),
+	default_member_permissions = "MANAGE_CHANNELS",
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+pub async fn lockdown(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+async fn reply(
+	ctx: Context<'_>,
+	mut response: String,
+	problems: Vec<String>,
+) -> Result<(), Error> {
+	if !problems.is_empty() {
+		response += &format!("\n⚠️ Some channels were skipped:\n{}", problems.join("\n"));
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+	Ok(())
+}
+
+/// Lock every channel, ones that are already locked keep their own lock
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+async fn start(
+	ctx: Context<'_>,
+	#[description = "How long the lockdown lasts, like 30m or 2h"] duration: Option<String>,
+	#[rest]
+	#[description = "Reason, shown in every channel"]
+	reason: Option<String>,
+) -> Result<(), Error> {
+	ctx.defer().await?;
+
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let (until, reason) = until_and_reason(ctx, duration, reason)?;
+	let channels = guild_id.channels(ctx.http()).await?;
+
+	let mut locked = 0;
+	let mut problems = vec![];
+	let notice = channel_locks::notice(true, until, reason.as_deref());
+	for channel in channels.values().filter(|c| is_lockable(c)) {
+		match channel_locks::lock(ctx.http(), channel, until, true, Some(notice.clone())).await {
+			| Ok(true) => locked += 1,
+			| Ok(false) => {},
+			| Err(e) => problems.push(format!("<#{}>: {}", channel.id, e)),
+		}
+	}
+
+	let response = match until {
+		| Some(until) => format!("✅ Locked {} channels until <t:{}:f>.", locked, until),
+		| None => format!(
+			"✅ Locked {} channels, use `/lockdown end` to unlock them.",
+			locked
+		),
+	};
+	reply(ctx, response, problems).await
+}
+
+/// Unlock every channel the lockdown locked
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+async fn end(
+	ctx: Context<'_>,
+	#[rest]
+	#[description = "Reason, shown in every channel"]
+	reason: Option<String>,
+) -> Result<(), Error> {
+	ctx.defer().await?;
+
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let locked: Vec<_> = channel_locks::locks(guild_id)?
+		.into_iter()
+		.filter(|(_, lock)| lock.lockdown)
+		.map(|(id, _)| id)
+		.collect();
+	if locked.is_empty() {
+		return Err(BotError::NotFound("There's no lockdown to end.".into()));
+	}
+
+	let channels = guild_id.channels(ctx.http()).await?;
+	let mut unlocked = 0;
+	let mut problems = vec![];
+	for channel_id in locked {
+		match channel_locks::unlock(ctx.http(), guild_id, channel_id).await {
+			| Ok(true) => {
+				unlocked += 1;
+				if let Some(channel) = channels.get(&channel_id) {
+					channel_locks::notify(ctx.http(), channel, reason.as_deref()).await;
+				}
+			},
+			// unlocked by someone else in the meantime
+			| Ok(false) => {},
+			| Err(e) => problems.push(format!("<#{}>: {}", channel_id, e)),
+		}
+	}
+
+	reply(ctx, format!("✅ Unlocked {} channels.", unlocked), problems).await
+}
@@ -6,4 +6,11 @@ pub mod dban;
 pub mod warn;
 pub mod mute;
 pub mod ban;
-pub mod kick;
\ No newline at end of file
+pub mod kick;
+pub mod raid;
+pub mod lock;
+pub mod unlock;
+pub mod lockdown;
+pub mod slowmode;
+pub mod unmute;
+pub mod mutes;
\ No newline at end of file
@@ -1,21 +1,46 @@
-use std::env::var;
-
 use poise::CreateReply;
 use serenity::all::{
-	EditChannel, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, User,
+	EditMember,
+	GuildChannel,
+	Http,
+	PermissionOverwrite,
+	PermissionOverwriteType,
+	Permissions,
+	RoleId,
+	Timestamp,
+	User,
+	UserId,
 };
 use serenity::builder::EditRole;
 use serenity::model::id::GuildId;
-use serenity::prelude::*;
 
+use crate::utils::dates::{format_duration, parse_duration};
 use crate::utils::dm_notifier_utils::send_mod_action_reason_dm;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{self, Setting, SettingValue};
+use crate::utils::mod_actions::{ModActionKind, ModActions};
+use crate::utils::moderation_utils::check_hierarchy;
+use crate::utils::mutes::{MAX_TIMEOUT_SECS, Mute, MuteKind, set_mute};
 use crate::{Context, Error};
 
-/// Mute a guild member
-#[poise::command(prefix_command, slash_command, aliases("timeout"), guild_only)]
+/// Mute a guild member, timeouts are used when the server turned them on and the mute is short
+/// enough
+///
+/// See who is muted with `/mutes list` and `/mutes info`.
+#[poise::command(
+	prefix_command,
+	slash_command,
+	aliases("timeout"),
+	default_member_permissions = "MODERATE_MEMBERS",
+	required_permissions = "MODERATE_MEMBERS",
+	guild_only
+)]
 pub async fn mute(
 	ctx: Context<'_>,
 	#[description = "User to mute"] user: User,
+	#[description = "How long, like 30m or 2d. Leave empty to mute until unmuted"] duration: Option<
+		String,
+	>,
 	#[rest]
 	#[description = "Reason"]
 	reason: Option<String>,
@@ -24,48 +49,52 @@ pub async fn mute(
 
 	// todo: check for config moderator role
 
+	// with text commands the first word of the reason lands in duration when there is none
+	let (secs, reason) = match duration.as_deref().map(parse_duration) {
+		| Some(Some(secs)) => (Some(secs), reason),
+		| Some(None) if ctx.prefix() != "/" => (
+			None,
+			Some(
+				[duration, reason]
+					.into_iter()
+					.flatten()
+					.collect::<Vec<_>>()
+					.join(" "),
+			),
+		),
+		| Some(None) => {
+			return Err(BotError::InvalidInput(
+				"Durations look like `30m`, `2h` or `1d12h`.".into(),
+			));
+		},
+		| None => (None, reason),
+	};
 	let reason_text = reason.as_deref().unwrap_or("No reason provided");
 
-	let guild_id = ctx
-		.guild_id()
-		.ok_or("This command can only be used in a guild.")?;
-
-	let muted_role_id = get_or_create_muted_role(&ctx, guild_id).await?;
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
 
-	let member = guild_id
-		.member(ctx.serenity_context(), user.id)
-		.await
-		.map_err(|e| format!("Failed to fetch member: {}", e))?;
+	check_hierarchy(ctx, &user).await?;
 
-	let add_role_result = member
-		.add_role(ctx.serenity_context(), muted_role_id)
-		.await
-		.err()
-		.map(|e| format!("Failed to assign Muted role: {}", e));
-
-	let channel_perms_result = override_channel_perms(&ctx, guild_id, muted_role_id)
-		.await
-		.err()
-		.map(|_| "❌ Could not update channel permissions.".to_string());
+	let mut response = mute_member(
+		ctx.http(),
+		&ctx.data().mod_actions,
+		guild_id,
+		&user,
+		ctx.author().id,
+		secs,
+		reason_text,
+	)
+	.await?;
 
-	let mut response = String::new();
+	let length = match secs {
+		| Some(secs) => format!(" for {}", format_duration(secs)),
+		| None => String::new(),
+	};
+	response.insert_str(0, &format!("✅ Muted {}{}.\n", user.name, length));
 
-	if add_role_result.is_none() && channel_perms_result.is_none() {
-		response = format!("✅ Muted {}.\n", user.name);
-		match send_mod_action_reason_dm(ctx, &user, "muted", reason_text).await {
-			| Ok(()) => response.push_str("✅ DM sent successfully."),
-			| Err(_) => response.push_str("❌ Could not send DM."),
-		}
-	} else {
-		if let Some(msg) = add_role_result {
-			response.push_str(&msg);
-		}
-		if let Some(msg) = channel_perms_result {
-			if !response.is_empty() {
-				response.push('\n');
-			}
-			response.push_str(&msg);
-		}
+	match send_mod_action_reason_dm(ctx, &user, "muted", reason_text).await {
+		| Ok(()) => response.push_str("✅ DM sent successfully."),
+		| Err(_) => response.push_str("❌ Could not send DM."),
 	}
 
 	ctx.send(
@@ -78,20 +107,153 @@ pub async fn mute(
 	Ok(())
 }
 
+/// Mutes `user` the way the guild is set up to and stores the mute so it can be listed, lifted
+/// and enforced. Used by automod too, returns notes about anything that only partly worked
+pub async fn mute_member(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	user: &User,
+	moderator: UserId,
+	secs: Option<u64>,
+	reason: &str,
+) -> Result<String, Error> {
+	let now = Timestamp::now().unix_timestamp();
+	let until = match secs {
+		| Some(secs) => Some(
+			i64::try_from(secs)
+				.ok()
+				.and_then(|secs| now.checked_add(secs))
+				.ok_or(BotError::InvalidInput("That mute is too long.".into()))?,
+		),
+		| None => None,
+	};
+	let kind = match secs {
+		| Some(secs) if secs <= MAX_TIMEOUT_SECS && guild_settings::timeout_mutes(guild_id)? => {
+			MuteKind::Timeout
+		},
+		| _ => MuteKind::Role,
+	};
+
+	let notes = match kind {
+		| MuteKind::Timeout => {
+			timeout(http, actions, guild_id, user, moderator, until, reason).await?
+		},
+		| MuteKind::Role => {
+			mute_with_role(http, actions, guild_id, user, moderator, reason).await?
+		},
+	};
+
+	set_mute(
+		guild_id,
+		user.id,
+		Some(&Mute {
+			kind,
+			moderator,
+			reason: reason.to_string(),
+			since: now,
+			until,
+		}),
+	)?;
+
+	Ok(notes)
+}
+
+async fn timeout(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	user: &User,
+	moderator: UserId,
+	until: Option<i64>,
+	reason: &str,
+) -> Result<String, Error> {
+	let until = until
+		.and_then(|until| Timestamp::from_unix_timestamp(until).ok())
+		.ok_or(BotError::InvalidInput("That mute is too long.".into()))?;
+
+	actions.record(
+		guild_id,
+		user.id,
+		ModActionKind::Timeout,
+		moderator,
+		Some(reason),
+	);
+
+	guild_id
+		.edit_member(
+			http,
+			user.id,
+			EditMember::new()
+				.disable_communication_until_datetime(until)
+				.audit_log_reason(reason),
+		)
+		.await
+		.map_err(|e| {
+			actions.forget(guild_id, user.id, ModActionKind::Timeout);
+			BotError::from(e)
+				.with_not_found(format!("{} isn't in this server.", user.name))
+				.with_permission_denied(
+					"I can't time out members here, make sure I have the Moderate Members \
+					 permission.",
+				)
+		})?;
+
+	Ok(String::new())
+}
+
+async fn mute_with_role(
+	http: &Http,
+	actions: &ModActions,
+	guild_id: GuildId,
+	user: &User,
+	moderator: UserId,
+	reason: &str,
+) -> Result<String, Error> {
+	let muted_role_id = get_or_create_muted_role(http, guild_id).await?;
+
+	let member = guild_id.member(http, user.id).await.map_err(|e| {
+		BotError::from(e).with_not_found(format!("{} isn't in this server.", user.name))
+	})?;
+
+	actions.record(
+		guild_id,
+		user.id,
+		ModActionKind::RoleUpdate,
+		moderator,
+		Some(reason),
+	);
+
+	member.add_role(http, muted_role_id).await.map_err(|e| {
+		actions.forget(guild_id, user.id, ModActionKind::RoleUpdate);
+		BotError::from(e).with_permission_denied(
+			"I can't give out the muted role, make sure I have Manage Roles and my highest role \
+			 is above the muted role.",
+		)
+	})?;
+
+	if override_channel_perms(http, guild_id, muted_role_id)
+		.await
+		.is_err()
+	{
+		return Ok(
+			"❌ Could not update channel permissions, the muted role might not work in every \
+			 channel.\n"
+				.to_string(),
+		);
+	}
+	Ok(String::new())
+}
+
 async fn get_or_create_muted_role(
-	ctx: &Context<'_>,
+	http: &Http,
 	guild_id: GuildId,
-) -> Result<RoleId, Box<dyn std::error::Error + Send + Sync>> {
-	if let Ok(role_id_str) = var("MUTED_ROLE_ID") {
-		if let Ok(role_id_num) = role_id_str.parse::<u64>() {
-			return Ok(RoleId::new(role_id_num));
-		}
+) -> Result<RoleId, Error> {
+	if let Some(role_id) = guild_settings::muted_role(guild_id)? {
+		return Ok(role_id);
 	}
 
-	let guild = guild_id
-		.to_partial_guild(ctx.http())
-		.await
-		.map_err(|e| format!("Failed to fetch guild: {}", e))?;
+	let guild = guild_id.to_partial_guild(http).await?;
 
 	if let Some(role) = guild
 		.roles
@@ -106,20 +268,17 @@ async fn get_or_create_muted_role(
 		if role.permissions.intersects(unwanted_permissions) {
 			let new_permissions = role.permissions - unwanted_permissions;
 			guild_id
-				.edit_role(
-					ctx.http(),
-					role.id,
-					EditRole::new().permissions(new_permissions),
-				)
+				.edit_role(http, role.id, EditRole::new().permissions(new_permissions))
 				.await?;
 		}
 
+		guild_settings::set(guild_id, Setting::MutedRole, &SettingValue::Role(role.id))?;
 		return Ok(role.id);
 	}
 
 	let new_role = guild_id
 		.create_role(
-			&ctx.http(),
+			http,
 			EditRole::new()
 				.name("Muted")
 				.permissions(Permissions::empty()),
@@ -127,46 +286,53 @@ async fn get_or_create_muted_role(
 		.await?
 		.id;
 
+	// remembered so renaming the role later doesn't make us create another one
+	guild_settings::set(guild_id, Setting::MutedRole, &SettingValue::Role(new_role))?;
+
 	Ok(new_role)
 }
 
-// should run on channel update evnts too
+/// what the muted role is denied in every channel
+const MUTED_DENY: Permissions = Permissions::SEND_MESSAGES
+	.union(Permissions::SPEAK)
+	.union(Permissions::ADD_REACTIONS)
+	.union(Permissions::SEND_MESSAGES_IN_THREADS);
+
+/// Denies [`MUTED_DENY`] to the muted role in one channel, returning false when it already was.
+/// Also run for new and updated channels, the check keeps our own edit from looping
+pub async fn deny_muted_role(
+	http: &Http,
+	channel: &GuildChannel,
+	muted_role_id: RoleId,
+) -> Result<bool, Error> {
+	let kind = PermissionOverwriteType::Role(muted_role_id);
+	let current = channel
+		.permission_overwrites
+		.iter()
+		.find(|po| po.kind == kind);
+
+	if current.is_some_and(|po| po.deny.contains(MUTED_DENY) && !po.allow.intersects(MUTED_DENY)) {
+		return Ok(false);
+	}
+
+	let overwrite = PermissionOverwrite {
+		allow: current.map(|po| po.allow).unwrap_or_default() - MUTED_DENY,
+		deny: current.map(|po| po.deny).unwrap_or_default() | MUTED_DENY,
+		kind,
+	};
+	channel.id.create_permission(http, overwrite).await?;
+	Ok(true)
+}
+
 pub async fn override_channel_perms(
-	ctx: &Context<'_>,
+	http: &Http,
 	guild_id: GuildId,
 	muted_role_id: RoleId,
 ) -> Result<(), Error> {
-	let deny_permissions = Permissions::SEND_MESSAGES
-		| Permissions::SPEAK
-		| Permissions::ADD_REACTIONS
-		| Permissions::SEND_MESSAGES_IN_THREADS;
-	let channels = guild_id.channels(&ctx.http()).await?;
-
-	for (channel_id, channel) in channels {
-		let mut overwrites = channel.permission_overwrites.clone();
-		let updated: bool;
-
-		if let Some(pos) = overwrites
-			.iter()
-			.position(|po| po.kind == PermissionOverwriteType::Role(muted_role_id))
-		{
-			overwrites[pos].deny |= deny_permissions;
-			overwrites[pos].allow &= !deny_permissions;
-			updated = true;
-		} else {
-			overwrites.push(PermissionOverwrite {
-				allow: Permissions::empty(),
-				deny: deny_permissions,
-				kind: PermissionOverwriteType::Role(muted_role_id),
-			});
-			updated = true;
-		}
+	let channels = guild_id.channels(http).await?;
 
-		if updated {
-			channel_id
-				.edit(ctx.http(), EditChannel::new().permissions(overwrites))
-				.await?;
-		}
+	for channel in channels.values() {
+		deny_muted_role(http, channel, muted_role_id).await?;
 	}
 
 	Ok(())
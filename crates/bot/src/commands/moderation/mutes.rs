@@ -0,0 +1,125 @@
+use poise::{Command, CreateReply};
+use serenity::all::{Color, CreateEmbed, User};
+
+use crate::background::guild_logs::truncate;
+use crate::utils::dates::format_duration;
+use crate::utils::errors::BotError;
+use crate::utils::mutes::{self as mute_store, Mute, MuteKind};
+use crate::{Context, Data, Error};
+
+/// See who is muted
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("list", "info"),
+	default_member_permissions = "MODERATE_MEMBERS",
+	required_permissions = "MODERATE_MEMBERS",
+	guild_only
+)]
+pub async fn mutes(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+/// lets text commands keep using `mute list` and `mute info`, slash commands only get `/mutes`
+/// since discord doesn't allow subcommands next to `/mute`'s options
+pub fn add_prefix_subcommands(commands: &mut [Command<Data, Error>]) {
+	if let Some(mute) = commands.iter_mut().find(|command| command.name == "mute") {
+		mute.subcommands = vec![list(), info()];
+	}
+}
+
+fn mute_kind(kind: MuteKind) -> &'static str {
+	match kind {
+		| MuteKind::Timeout => "Timeout",
+		| MuteKind::Role => "Muted role",
+	}
+}
+
+fn time_left(mute: &Mute) -> String {
+	match (mute.until, mute.remaining()) {
+		| (Some(until), Some(left)) => format!("{} (<t:{}:f>)", format_duration(left), until),
+		| _ => "Until unmuted".to_string(),
+	}
+}
+
+/// Show everyone who is muted and for how much longer
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MODERATE_MEMBERS",
+	guild_only
+)]
+async fn list(ctx: Context<'_>) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let mut muted = mute_store::mutes(guild_id)?;
+	muted.retain(|(_, mute)| mute.remaining() != Some(0));
+	// ones that end soonest first, mutes without an end last
+	muted.sort_by_key(|(_, mute)| mute.until.unwrap_or(i64::MAX));
+
+	let description = match muted.is_empty() {
+		| true => "Nobody is muted.".to_string(),
+		| false => muted
+			.iter()
+			.map(|(user_id, mute)| {
+				format!(
+					"<@{}> - {}: {}",
+					user_id,
+					mute_kind(mute.kind),
+					time_left(mute)
+				)
+			})
+			.collect::<Vec<_>>()
+			.join("\n"),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::default()
+					.title(format!("Muted Members ({})", muted.len()))
+					.description(truncate(&description, 4096))
+					.color(Color::BLURPLE),
+			)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Show who muted a member, why and for how long
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MODERATE_MEMBERS",
+	guild_only
+)]
+async fn info(
+	ctx: Context<'_>,
+	#[description = "Muted user"] user: User,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let mute = mute_store::mute_of(guild_id, user.id)?
+		.filter(|mute| mute.remaining() != Some(0))
+		.ok_or(BotError::NotFound(format!("{} isn't muted.", user.name)))?;
+
+	let embed = CreateEmbed::default()
+		.title(format!("Mute of {}", user.name))
+		.field("Moderator", format!("<@{}>", mute.moderator), true)
+		.field("Type", mute_kind(mute.kind), true)
+		.field("Muted", format!("<t:{}:R>", mute.since), true)
+		.field("Time left", time_left(&mute), false)
+		.field("Reason", &mute.reason, false)
+		.color(Color::BLURPLE);
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
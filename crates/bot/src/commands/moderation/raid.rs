@@ -0,0 +1,65 @@
+use poise::CreateReply;
+
+use crate::background::automod::raid;
+use crate::utils::errors::BotError;
+use crate::{Context, Error};
+
+/// Manage automod's raid response
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("end"),
+	default_member_permissions = "MANAGE_GUILD",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+pub async fn raid(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+/// End the raid response, putting back what it changed
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn end(ctx: Context<'_>) -> Result<(), Error> {
+	ctx.defer().await?;
+
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let ended = raid::end(&ctx.serenity_context().http, guild_id).await?;
+
+	let mut undone = vec![];
+	if ended.verification_restored {
+		undone.push("the verification level is back".to_string());
+	}
+	if ended.unlocked > 0 {
+		undone.push(format!("{} channels were unlocked", ended.unlocked));
+	}
+
+	let mut response = match (ended.finished, undone.is_empty()) {
+		| (true, true) => "✅ Raid ended.".to_string(),
+		| (true, false) => format!("✅ Raid ended, {}.", undone.join(" and ")),
+		| (false, true) => "⚠️ The raid response is still active.".to_string(),
+		| (false, false) => format!(
+			"⚠️ The raid response is still active, {}.",
+			undone.join(" and ")
+		),
+	};
+	if !ended.problems.is_empty() {
+		response += &format!(
+			"\nSome things couldn't be undone, use `/raid end` again to retry:\n{}",
+			ended.problems.join("\n")
+		);
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
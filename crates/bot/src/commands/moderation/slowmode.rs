@@ -0,0 +1,237 @@
+use poise::CreateReply;
+use serenity::all::{Color, CreateEmbed, GuildChannel};
+
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{Setting, get_or_default};
+use crate::utils::slowmode::{
+	self as slowmode_settings,
+	SlowmodeSchedule,
+	parse_slowmode,
+	parse_time,
+	schedule_of,
+	set_schedule,
+	set_slowmode,
+	show_slowmode,
+};
+use crate::{Context, Error};
+
+/// Change slowmode now or on a daily schedule
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("set", "schedule", "unschedule", "schedules"),
+	default_member_permissions = "MANAGE_CHANNELS",
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+pub async fn slowmode(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+async fn reply(
+	ctx: Context<'_>,
+	response: String,
+) -> Result<(), Error> {
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+	Ok(())
+}
+
+fn check_channel(
+	ctx: Context<'_>,
+	channel: &GuildChannel,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	if channel.guild_id != guild_id {
+		return Err(BotError::InvalidInput(format!(
+			"<#{}> isn't in this server.",
+			channel.id
+		)));
+	}
+	Ok(())
+}
+
+/// Set a channel's slowmode
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+async fn set(
+	ctx: Context<'_>,
+	#[description = "Channel to change"] channel: GuildChannel,
+	#[description = "Like 10s, 2m or 1h, or off"] duration: String,
+) -> Result<(), Error> {
+	check_channel(ctx, &channel)?;
+	let seconds = parse_slowmode(&duration)?;
+
+	set_slowmode(
+		ctx.http(),
+		&ctx.data().log_queue,
+		channel.guild_id,
+		channel.id,
+		seconds,
+		format!("{} ({})", ctx.author().name, ctx.author().id),
+	)
+	.await?;
+
+	let response = match seconds {
+		| 0 => format!("✅ Turned off slowmode in <#{}>.", channel.id),
+		| seconds => format!(
+			"✅ Slowmode in <#{}> set to {}.",
+			channel.id,
+			show_slowmode(seconds)
+		),
+	};
+	reply(ctx, response).await
+}
+
+/// Turn slowmode on every day between two times, in the server's timezone
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+async fn schedule(
+	ctx: Context<'_>,
+	#[description = "Channel to schedule"] channel: GuildChannel,
+	#[description = "When it starts, like 18:00"] start: String,
+	#[description = "When it ends, like 23:00"] end: String,
+	#[description = "Like 10s, 2m or 1h"] duration: String,
+) -> Result<(), Error> {
+	check_channel(ctx, &channel)?;
+
+	let (Some(start), Some(end)) = (parse_time(&start), parse_time(&end)) else {
+		return Err(BotError::InvalidInput(
+			"Times look like `18:00`, in 24 hour time.".into(),
+		));
+	};
+	if start == end {
+		return Err(BotError::InvalidInput(
+			"The schedule has to start and end at different times.".into(),
+		));
+	}
+	let seconds = match parse_slowmode(&duration)? {
+		| 0 => {
+			return Err(BotError::InvalidInput(
+				"Use `/slowmode unschedule` to remove a schedule.".into(),
+			));
+		},
+		| seconds => seconds,
+	};
+
+	// a running window keeps what it has to restore
+	let previous = schedule_of(channel.guild_id, channel.id)?.and_then(|s| s.previous);
+	let schedule = SlowmodeSchedule {
+		start,
+		end,
+		seconds: seconds as u16,
+		previous,
+	};
+	set_schedule(channel.guild_id, channel.id, Some(&schedule))?;
+
+	let timezone = get_or_default(ctx.guild_id(), Setting::Timezone)?
+		.map(|tz| tz.display())
+		.unwrap_or_else(|| "UTC".to_string());
+	reply(
+		ctx,
+		format!(
+			"✅ <#{}> gets {} slowmode every day {} ({}, which doesn't follow daylight saving \
+			 time).",
+			channel.id,
+			show_slowmode(seconds),
+			schedule.window(),
+			timezone
+		),
+	)
+	.await
+}
+
+/// Remove a channel's slowmode schedule
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+async fn unschedule(
+	ctx: Context<'_>,
+	#[description = "Channel to stop scheduling"] channel: GuildChannel,
+) -> Result<(), Error> {
+	check_channel(ctx, &channel)?;
+
+	let schedule = schedule_of(channel.guild_id, channel.id)?.ok_or(BotError::NotFound(
+		format!("<#{}> has no slowmode schedule.", channel.id),
+	))?;
+
+	// ending it halfway through a window puts the old slowmode back
+	if let Some(previous) = schedule.previous {
+		set_slowmode(
+			ctx.http(),
+			&ctx.data().log_queue,
+			channel.guild_id,
+			channel.id,
+			previous as u64,
+			format!("{} ({})", ctx.author().name, ctx.author().id),
+		)
+		.await?;
+	}
+	set_schedule(channel.guild_id, channel.id, None)?;
+
+	reply(
+		ctx,
+		format!("✅ Removed the slowmode schedule for <#{}>.", channel.id),
+	)
+	.await
+}
+
+/// Show every slowmode schedule in this server
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+async fn schedules(ctx: Context<'_>) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let lines: Vec<String> = slowmode_settings::schedules(guild_id)?
+		.into_iter()
+		.map(|(channel_id, schedule)| {
+			format!(
+				"<#{}>: {} every day {}{}",
+				channel_id,
+				show_slowmode(schedule.seconds as u64),
+				schedule.window(),
+				match schedule.previous {
+					| Some(_) => " (on now)",
+					| None => "",
+				}
+			)
+		})
+		.collect();
+	let description = match lines.is_empty() {
+		| true => "No schedules yet, add one with `/slowmode schedule`.".to_string(),
+		| false => lines.join("\n"),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::default()
+					.title("Slowmode Schedules")
+					.color(Color::BLURPLE)
+					.description(description),
+			)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
@@ -1,6 +1,8 @@
 use poise::CreateReply;
 use serenity::all::User;
 
+use crate::utils::errors::BotError;
+use crate::utils::mod_actions::ModActionKind;
 use crate::{Context, Error};
 
 /// Unban a guild member
@@ -13,14 +15,31 @@ pub async fn unban(
 
 	// todo: check for config admin role
 
-	let guild_id = ctx
-		.guild_id()
-		.ok_or("This command can only be used in a guild")?;
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
 
-	let response = match guild_id.unban(&ctx.serenity_context().http, user.id).await {
-		| Ok(_) => format!("✅ Unbanned {}.", user.name),
-		| Err(e) => format!("❌ Failed to unban user: {}", e),
-	};
+	ctx.data().mod_actions.record(
+		guild_id,
+		user.id,
+		ModActionKind::Unban,
+		ctx.author().id,
+		None,
+	);
+
+	guild_id
+		.unban(&ctx.serenity_context().http, user.id)
+		.await
+		.map_err(|e| {
+			ctx.data()
+				.mod_actions
+				.forget(guild_id, user.id, ModActionKind::Unban);
+			BotError::from(e)
+				.with_not_found(format!("{} isn't banned.", user.name))
+				.with_permission_denied(
+					"I can't unban members here, make sure I have the Ban Members permission.",
+				)
+		})?;
+
+	let response = format!("✅ Unbanned {}.", user.name);
 
 	ctx.send(
 		CreateReply::default()
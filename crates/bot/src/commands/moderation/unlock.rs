@@ -0,0 +1,44 @@
+use poise::CreateReply;
+use serenity::all::GuildChannel;
+
+use crate::commands::moderation::lock::target_channel;
+use crate::utils::channel_locks;
+use crate::utils::errors::BotError;
+use crate::{Context, Error};
+
+/// Let everyone send messages in a locked channel again
+#[poise::command(
+	prefix_command,
+	slash_command,
+	default_member_permissions = "MANAGE_CHANNELS",
+	required_permissions = "MANAGE_CHANNELS",
+	guild_only
+)]
+pub async fn unlock(
+	ctx: Context<'_>,
+	#[description = "Channel to unlock, defaults to this one"] channel: Option<GuildChannel>,
+	#[rest]
+	#[description = "Reason, shown in the channel"]
+	reason: Option<String>,
+) -> Result<(), Error> {
+	ctx.defer().await?;
+
+	let channel = target_channel(ctx, channel).await?;
+
+	if !channel_locks::unlock(ctx.http(), channel.guild_id, channel.id).await? {
+		return Err(BotError::NotFound(format!(
+			"<#{}> isn't locked.",
+			channel.id
+		)));
+	}
+	channel_locks::notify(ctx.http(), &channel, reason.as_deref()).await;
+
+	ctx.send(
+		CreateReply::default()
+			.content(format!("✅ Unlocked <#{}>.", channel.id))
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
@@ -0,0 +1,143 @@
+use poise::CreateReply;
+use serenity::all::{EditMember, Timestamp, User};
+
+use crate::utils::dm_notifier_utils::send_mod_action_reason_dm;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings;
+use crate::utils::mod_actions::ModActionKind;
+use crate::utils::moderation_utils::check_hierarchy;
+use crate::utils::mutes::{mute_of, set_mute};
+use crate::{Context, Error};
+
+/// Unmute a guild member, whether they were timed out or given the muted role
+#[poise::command(
+	prefix_command,
+	slash_command,
+	aliases("untimeout"),
+	default_member_permissions = "MODERATE_MEMBERS",
+	required_permissions = "MODERATE_MEMBERS",
+	guild_only
+)]
+pub async fn unmute(
+	ctx: Context<'_>,
+	#[description = "User to unmute"] user: User,
+	#[rest]
+	#[description = "Reason"]
+	reason: Option<String>,
+) -> Result<(), Error> {
+	ctx.defer().await?;
+
+	// todo: check for config moderator role
+
+	let reason_text = reason.as_deref().unwrap_or("No reason provided");
+
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let member = match guild_id.member(ctx.serenity_context(), user.id).await {
+		| Ok(member) => member,
+		| Err(e) => match BotError::from(e) {
+			// nothing to take off someone who left, only the stored mute so rejoining isn't
+			// treated as evasion
+			| BotError::NotFound(_) => {
+				if mute_of(guild_id, user.id)?.is_none() {
+					return Err(BotError::NotFound(format!(
+						"{} isn't in this server or muted.",
+						user.name
+					)));
+				}
+				set_mute(guild_id, user.id, None)?;
+				ctx.send(
+					CreateReply::default()
+						.content(format!(
+							"✅ {} isn't in this server, their mute won't be put back if they \
+							 rejoin.",
+							user.name
+						))
+						.ephemeral(ctx.prefix() == "/"),
+				)
+				.await?;
+				return Ok(());
+			},
+			| e => return Err(e),
+		},
+	};
+
+	check_hierarchy(ctx, &user).await?;
+
+	let timed_out = member
+		.communication_disabled_until
+		.is_some_and(|until| until > Timestamp::now());
+	let muted_role = guild_settings::muted_role(guild_id)?.filter(|id| member.roles.contains(id));
+
+	if !timed_out && muted_role.is_none() {
+		set_mute(guild_id, user.id, None)?;
+		return Err(BotError::NotFound(format!("{} isn't muted.", user.name)));
+	}
+
+	if timed_out {
+		ctx.data().mod_actions.record(
+			guild_id,
+			user.id,
+			ModActionKind::Timeout,
+			ctx.author().id,
+			Some(reason_text),
+		);
+		guild_id
+			.edit_member(
+				ctx.http(),
+				user.id,
+				EditMember::new()
+					.enable_communication()
+					.audit_log_reason(reason_text),
+			)
+			.await
+			.map_err(|e| {
+				ctx.data()
+					.mod_actions
+					.forget(guild_id, user.id, ModActionKind::Timeout);
+				BotError::from(e).with_permission_denied(
+					"I can't remove timeouts here, make sure I have the Moderate Members \
+					 permission.",
+				)
+			})?;
+	}
+
+	if let Some(role_id) = muted_role {
+		ctx.data().mod_actions.record(
+			guild_id,
+			user.id,
+			ModActionKind::RoleUpdate,
+			ctx.author().id,
+			Some(reason_text),
+		);
+		member
+			.remove_role(ctx.serenity_context(), role_id)
+			.await
+			.map_err(|e| {
+				ctx.data()
+					.mod_actions
+					.forget(guild_id, user.id, ModActionKind::RoleUpdate);
+				BotError::from(e).with_permission_denied(
+					"I can't take away the muted role, make sure I have Manage Roles and my \
+					 highest role is above the muted role.",
+				)
+			})?;
+	}
+
+	set_mute(guild_id, user.id, None)?;
+
+	let mut response = format!("✅ Unmuted {}.\n", user.name);
+	match send_mod_action_reason_dm(ctx, &user, "unmuted", reason_text).await {
+		| Ok(()) => response.push_str("✅ DM sent successfully."),
+		| Err(_) => response.push_str("❌ Could not send DM."),
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
@@ -14,8 +14,11 @@ use serenity::all::{
 	User,
 };
 
+use crate::config::WARNS_DB_PATH;
 use crate::utils::dates::format_timestamp_ddmmyyyy;
 use crate::utils::dm_notifier_utils::send_mod_action_reason_dm;
+use crate::utils::errors::BotError;
+use crate::utils::moderation_utils::check_hierarchy;
 use crate::{Context, Error};
 
 const WARNS_PER_PAGE: usize = 10;
@@ -33,12 +36,12 @@ pub async fn warn(
 
 	// todo: check for config moderator role
 
-	let guild_id = ctx
-		.guild_id()
-		.ok_or("This command can only be used in a guild.")?;
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
 
 	let reason_text = reason.as_deref().unwrap_or("No reason provided");
 
+	check_hierarchy(ctx, &user).await?;
+
 	add_warn(&user, guild_id, reason_text).await?;
 
 	let response = match send_mod_action_reason_dm(ctx, &user, "warned", reason_text).await {
@@ -70,12 +73,10 @@ pub async fn list(
 ) -> Result<(), Error> {
 	ctx.defer().await?;
 
-	let guild_id = ctx
-		.guild_id()
-		.ok_or("This command can only be used in a guild.")?;
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
 
 	let table_name = format!("guild_{}", guild_id);
-	let conn = Connection::open("data/user_warns.db")?;
+	let conn = Connection::open(WARNS_DB_PATH)?;
 	let user_id = user.id.to_string();
 
 	let warns: Vec<Warning> = conn
@@ -218,7 +219,7 @@ pub async fn add_warn(
 	guild_id: GuildId,
 	reason: &str,
 ) -> Result<(), Error> {
-	let conn = Connection::open("data/user_warns.db")?;
+	let conn = Connection::open(WARNS_DB_PATH)?;
 
 	let table_name = format!("guild_{}", guild_id);
 
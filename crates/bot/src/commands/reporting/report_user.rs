@@ -1,10 +1,9 @@
-use std::env::var;
-
-use dotenv::dotenv;
 use poise::{CreateReply, Modal};
-use serenity::all::{ChannelId, CreateEmbed, CreateMessage, User};
+use serenity::all::{CreateEmbed, CreateMessage, User};
 
-use crate::{ApplicationContext, Error, ExpectError};
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings;
+use crate::{ApplicationContext, Error};
 
 #[derive(Debug, Modal)]
 struct ReportModal {
@@ -17,7 +16,11 @@ pub async fn report_user(
 	#[description = "Reported user"] user: User,
 ) -> Result<(), Error> {
 	use poise::Modal as _;
-	dotenv().ok();
+
+	// checked before the modal so people don't write a whole report for nothing
+	let channel_id = guild_settings::report_channel(ctx.guild_id())?.ok_or(BotError::NotFound(
+		"Reports aren't set up yet, let a moderator know.".into(),
+	))?;
 
 	let data = ReportModal::execute(ctx).await?;
 
@@ -36,25 +39,24 @@ pub async fn report_user(
 			.color(0xd14821)
 			.timestamp(serenity::model::Timestamp::now());
 
-		dotenv().ok();
-		let channel_id = var("REPORT_CHANNEL_ID")
-			.expect_error(
-				"Missing `REPORT_CHANNEL_ID` env var, please include this in your .env file",
-			)
-			.parse::<u64>()
-			.expect_error("REPORT_CHANNEL_ID must be a valid u64 number");
-
 		let mut message = CreateMessage::new().embed(embed);
 
-		if let Ok(notification_role_id) = var("REPORT_NOTIFICATION_ROLE") {
-			if let Ok(role_id) = notification_role_id.parse::<u64>() {
-				message = message.content(format!("<@&{}>", role_id));
-			}
+		if let Some(role_id) = guild_settings::report_notification_role(ctx.guild_id())? {
+			message = message.content(format!("<@&{}>", role_id));
 		}
 
-		ChannelId::new(channel_id)
+		channel_id
 			.send_message(ctx.serenity_context(), message)
-			.await?;
+			.await
+			.map_err(|e| {
+				BotError::from(e)
+					.with_not_found(
+						"The report channel doesn't exist anymore, let a moderator know.",
+					)
+					.with_permission_denied(
+						"I can't send messages in the report channel, let a moderator know.",
+					)
+			})?;
 
 		ctx.send(
 			CreateReply::default()
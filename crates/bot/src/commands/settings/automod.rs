@@ -0,0 +1,689 @@
+use poise::CreateReply;
+use serenity::all::{Color, CreateEmbed, GuildChannel, GuildId, Role};
+
+use crate::background::automod::{filters, links};
+use crate::background::guild_logs::truncate;
+use crate::utils::automod_settings::{
+	self,
+	AutomodAction,
+	AutomodSettings,
+	DomainList,
+	FilterKind,
+	FilterRule,
+	LinkRule,
+	LinkSettings,
+	RaidResponse,
+	RaidSettings,
+	SpamSettings,
+};
+use crate::utils::dates::format_duration;
+use crate::utils::errors::BotError;
+use crate::utils::mention::Mentionable;
+use crate::{Context, Error};
+
+/// keeps `/automod view` readable and every message quick to check
+const MAX_FILTERS: usize = 100;
+/// discord's longest timeout
+const MAX_MUTE_MINUTES: u64 = 28 * 24 * 60;
+/// a week, strikes are meant for bursts of spam
+const MAX_STRIKE_MINUTES: u64 = 7 * 24 * 60;
+/// a year, past that nearly every account would count as new
+const MAX_ACCOUNT_DAYS: u64 = 365;
+
+/// Set up automatic moderation of messages
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands(
+		"view",
+		"filter",
+		"spam",
+		"links",
+		"domain",
+		"raid",
+		"exempt",
+		"mute_length"
+	),
+	default_member_permissions = "MANAGE_GUILD",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+pub async fn automod(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+fn update(
+	guild_id: GuildId,
+	change: impl FnOnce(&mut AutomodSettings) -> Result<(), Error>,
+) -> Result<AutomodSettings, Error> {
+	let mut settings = automod_settings::settings(guild_id)?;
+	change(&mut settings)?;
+	automod_settings::set_settings(guild_id, &settings)?;
+	Ok(settings)
+}
+
+async fn reply(
+	ctx: Context<'_>,
+	response: String,
+) -> Result<(), Error> {
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+	Ok(())
+}
+
+fn filter_line(
+	number: usize,
+	rule: &FilterRule,
+) -> String {
+	let kind = match rule.kind {
+		| FilterKind::Word => "Word",
+		| FilterKind::Regex => "Regex",
+	};
+	format!(
+		"`{}.` {} `{}`: {}",
+		number,
+		kind,
+		rule.pattern,
+		rule.action.label()
+	)
+}
+
+fn spam_summary(spam: &SpamSettings) -> String {
+	if !spam.enabled {
+		return "Off".to_string();
+	}
+
+	let limit = |limit: u32, what: &str| match limit {
+		| 0 => format!("No {} limit", what),
+		| limit => format!("At most {} {}", limit, what),
+	};
+	let strikes = |limit: u32, action: &str| match limit {
+		| 0 => format!("Never {}", action),
+		| limit => format!("{} after {} strikes", action, limit),
+	};
+
+	[
+		limit(spam.max_messages, "messages") + &format!(" in {} seconds", spam.per_seconds),
+		limit(spam.max_duplicates, "repeats of a message"),
+		limit(spam.max_mentions, "mentions per message"),
+		limit(spam.max_emojis, "emojis per message"),
+		limit(spam.max_newlines, "lines per message"),
+		strikes(spam.mute_after, "Muted"),
+		strikes(spam.kick_after, "Kicked"),
+		format!("Strikes last {} minutes", spam.strike_minutes),
+	]
+	.join("\n")
+}
+
+fn links_summary(links: &LinkSettings) -> String {
+	let domains = |list: &[String]| match list.is_empty() {
+		| true => "None".to_string(),
+		| false => list
+			.iter()
+			.map(|d| format!("`{}`", d))
+			.collect::<Vec<_>>()
+			.join(" "),
+	};
+
+	LinkRule::ALL
+		.into_iter()
+		.map(|rule| {
+			let action = links.action(rule).map(|a| a.label()).unwrap_or("Allowed");
+			format!("{}: {}", rule.label(), action)
+		})
+		.chain([
+			format!("Allowed domains: {}", domains(&links.allowed_domains)),
+			format!("Denied domains: {}", domains(&links.denied_domains)),
+		])
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+fn raid_summary(raid: &RaidSettings) -> String {
+	if !raid.enabled {
+		return "Off".to_string();
+	}
+
+	let joins = match raid.max_joins {
+		| 0 => "No join limit".to_string(),
+		| limit => format!("At most {} joins in {} seconds", limit, raid.join_seconds),
+	};
+	let suspicious = match raid.max_suspicious {
+		| 0 => "New accounts aren't counted".to_string(),
+		| limit => format!(
+			"At most {} accounts younger than {} days without an avatar",
+			limit, raid.new_account_days
+		),
+	};
+
+	[
+		joins,
+		suspicious,
+		format!("Response: {}", raid.response.label()),
+	]
+	.join("\n")
+}
+
+/// Show this server's automod rules
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn view(ctx: Context<'_>) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let settings = automod_settings::settings(guild_id)?;
+
+	let filters = match settings.filters.is_empty() {
+		| true => "No filters yet, add one with `/automod filter add`.".to_string(),
+		| false => settings
+			.filters
+			.iter()
+			.enumerate()
+			.map(|(i, rule)| filter_line(i + 1, rule))
+			.collect::<Vec<_>>()
+			.join("\n"),
+	};
+	let channels = match settings.exempt_channels.is_empty() {
+		| true => "None".to_string(),
+		| false => settings
+			.exempt_channels
+			.iter()
+			.map(|id| format!("<#{}>", id))
+			.collect::<Vec<_>>()
+			.join(" "),
+	};
+	let roles = match settings.exempt_roles.is_empty() {
+		| true => "Moderator roles only".to_string(),
+		| false => settings
+			.exempt_roles
+			.iter()
+			.map(|id| id.mention())
+			.collect::<Vec<_>>()
+			.join(" "),
+	};
+
+	let embed = CreateEmbed::default()
+		.title("Automod")
+		.color(Color::BLURPLE)
+		.description(truncate(&filters, 4096))
+		.field("Spam", spam_summary(&settings.spam), false)
+		.field("Raids", raid_summary(&settings.raid), false)
+		.field(
+			"Links",
+			truncate(&links_summary(&settings.links), 1024),
+			false,
+		)
+		.field("Exempt channels", channels, false)
+		.field("Exempt roles", roles, false)
+		.field(
+			"Mute length",
+			format_duration(settings.mute_duration().as_secs()),
+			true,
+		);
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Manage blocked words and patterns
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("filter_add", "filter_remove"),
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn filter(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+/// Block a word or regex, `*` in words matches any letters
+#[poise::command(
+	prefix_command,
+	slash_command,
+	rename = "add",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn filter_add(
+	ctx: Context<'_>,
+	#[description = "Word or regex"] kind: FilterKind,
+	#[description = "What to do with matching messages"] action: AutomodAction,
+	#[description = "The word or regex to block"]
+	#[rest]
+	pattern: String,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let rule = FilterRule {
+		kind,
+		pattern: pattern.trim().to_string(),
+		action,
+	};
+	filters::validate(&rule).map_err(|e| {
+		BotError::InvalidInput(format!(
+			"`{}` can't be used as a filter: {}",
+			rule.pattern, e
+		))
+	})?;
+
+	let settings = update(guild_id, |settings| {
+		if settings.filters.len() >= MAX_FILTERS {
+			return Err(BotError::InvalidInput(format!(
+				"There can be at most {} filters, remove one first.",
+				MAX_FILTERS
+			)));
+		}
+		settings
+			.filters
+			.retain(|r| r.kind != rule.kind || r.pattern != rule.pattern);
+		settings.filters.push(rule.clone());
+		Ok(())
+	})?;
+
+	reply(
+		ctx,
+		format!("✅ Added {}", filter_line(settings.filters.len(), &rule)),
+	)
+	.await
+}
+
+/// Remove a filter by its number in `/automod view`
+#[poise::command(
+	prefix_command,
+	slash_command,
+	rename = "remove",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn filter_remove(
+	ctx: Context<'_>,
+	#[description = "Filter number"]
+	#[min = 1]
+	number: usize,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let mut removed = None;
+	update(guild_id, |settings| {
+		if number == 0 || number > settings.filters.len() {
+			return Err(BotError::NotFound(format!(
+				"There's no filter {}, check `/automod view`.",
+				number
+			)));
+		}
+		removed = Some(settings.filters.remove(number - 1));
+		Ok(())
+	})?;
+
+	let line = removed
+		.map(|rule| filter_line(number, &rule))
+		.unwrap_or_default();
+	reply(ctx, format!("✅ Removed {}", line)).await
+}
+
+/// Turn spam detection on or off and change its limits, 0 turns a limit off
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+#[allow(clippy::too_many_arguments)]
+async fn spam(
+	ctx: Context<'_>,
+	#[description = "Check for spam"] enabled: Option<bool>,
+	#[description = "Messages allowed in the time below"] messages: Option<u32>,
+	#[description = "Seconds the message limit counts over"]
+	#[min = 1]
+	seconds: Option<u64>,
+	#[description = "Times the same message can be sent in a minute"] duplicates: Option<u32>,
+	#[description = "Mentions allowed in one message"] mentions: Option<u32>,
+	#[description = "Emojis allowed in one message"] emojis: Option<u32>,
+	#[description = "Lines allowed in one message"] lines: Option<u32>,
+	#[description = "Strikes before a member is muted"] mute_after: Option<u32>,
+	#[description = "Strikes before a member is kicked"] kick_after: Option<u32>,
+	#[description = "Minutes until a strike is forgotten"]
+	#[min = 1]
+	#[max = 10080]
+	strike_minutes: Option<u64>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if strike_minutes.is_some_and(|minutes| minutes > MAX_STRIKE_MINUTES) {
+		return Err(BotError::InvalidInput(
+			"Strikes can last a week at most.".to_string(),
+		));
+	}
+
+	let settings = update(guild_id, |settings| {
+		let spam = &mut settings.spam;
+		let fields = [
+			(messages, &mut spam.max_messages),
+			(duplicates, &mut spam.max_duplicates),
+			(mentions, &mut spam.max_mentions),
+			(emojis, &mut spam.max_emojis),
+			(lines, &mut spam.max_newlines),
+			(mute_after, &mut spam.mute_after),
+			(kick_after, &mut spam.kick_after),
+		];
+		for (value, field) in fields {
+			if let Some(value) = value {
+				*field = value;
+			}
+		}
+		if let Some(enabled) = enabled {
+			spam.enabled = enabled;
+		}
+		if let Some(seconds) = seconds {
+			spam.per_seconds = seconds.max(1);
+		}
+		if let Some(minutes) = strike_minutes {
+			spam.strike_minutes = minutes.max(1);
+		}
+		Ok(())
+	})?;
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::default()
+					.title("Spam detection")
+					.color(Color::BLURPLE)
+					.description(spam_summary(&settings.spam)),
+			)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Turn raid detection on or off and change when it triggers, 0 turns a limit off
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn raid(
+	ctx: Context<'_>,
+	#[description = "Watch joins for raids"] enabled: Option<bool>,
+	#[description = "Joins allowed in the time below"] joins: Option<u32>,
+	#[description = "Seconds the join limits count over"]
+	#[min = 1]
+	seconds: Option<u64>,
+	#[description = "New accounts without an avatar allowed in that time"] new_accounts: Option<
+		u32,
+	>,
+	#[description = "Accounts younger than this many days count as new"]
+	#[max = 365]
+	account_days: Option<u64>,
+	#[description = "What to do when a raid starts"] response: Option<RaidResponse>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if account_days.is_some_and(|days| days > MAX_ACCOUNT_DAYS) {
+		return Err(BotError::InvalidInput(format!(
+			"Accounts can count as new for {} days at most.",
+			MAX_ACCOUNT_DAYS
+		)));
+	}
+
+	let settings = update(guild_id, |settings| {
+		let raid = &mut settings.raid;
+		if let Some(enabled) = enabled {
+			raid.enabled = enabled;
+		}
+		if let Some(joins) = joins {
+			raid.max_joins = joins;
+		}
+		if let Some(seconds) = seconds {
+			raid.join_seconds = seconds.max(1);
+		}
+		if let Some(new_accounts) = new_accounts {
+			raid.max_suspicious = new_accounts;
+		}
+		if let Some(days) = account_days {
+			raid.new_account_days = days;
+		}
+		if let Some(response) = response {
+			raid.response = response;
+		}
+		Ok(())
+	})?;
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::default()
+					.title("Raid detection")
+					.color(Color::BLURPLE)
+					.description(raid_summary(&settings.raid)),
+			)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose what happens to invites and links
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn links(
+	ctx: Context<'_>,
+	#[description = "Kind of link"] rule: LinkRule,
+	#[description = "What to do with them, leave empty to allow them"] action: Option<
+		AutomodAction,
+	>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	update(guild_id, |settings| {
+		settings.links.set_action(rule, action);
+		Ok(())
+	})?;
+
+	let response = match action {
+		| Some(action) => format!("✅ {}: {}", rule.label(), action.label()),
+		| None => format!("✅ {} are allowed", rule.label()),
+	};
+	reply(ctx, response).await
+}
+
+/// Manage the allowed and denied domains
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("domain_add", "domain_remove"),
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn domain(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+fn parse_domain(input: &str) -> Result<String, Error> {
+	links::parse_domain(input).ok_or(BotError::InvalidInput(format!(
+		"`{}` isn't a domain, use something like `example.com`.",
+		input
+	)))
+}
+
+/// Add a domain to the allow or deny list, subdomains are included
+#[poise::command(
+	prefix_command,
+	slash_command,
+	rename = "add",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn domain_add(
+	ctx: Context<'_>,
+	#[description = "Allow or deny list"] list: DomainList,
+	#[description = "Domain, like example.com"] domain: String,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let domain = parse_domain(&domain)?;
+
+	update(guild_id, |settings| {
+		let domains = settings.links.domains(list);
+		if !domains.contains(&domain) {
+			domains.push(domain.clone());
+		}
+		Ok(())
+	})?;
+
+	let response = match list {
+		| DomainList::Allow => format!("✅ Links to `{}` are allowed", domain),
+		| DomainList::Deny => format!("✅ Links to `{}` are denied", domain),
+	};
+	reply(ctx, response).await
+}
+
+/// Take a domain off the allow or deny list
+#[poise::command(
+	prefix_command,
+	slash_command,
+	rename = "remove",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn domain_remove(
+	ctx: Context<'_>,
+	#[description = "Allow or deny list"] list: DomainList,
+	#[description = "Domain, like example.com"] domain: String,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let domain = parse_domain(&domain)?;
+
+	update(guild_id, |settings| {
+		let domains = settings.links.domains(list);
+		if !domains.contains(&domain) {
+			return Err(BotError::NotFound(format!(
+				"`{}` isn't on that list.",
+				domain
+			)));
+		}
+		domains.retain(|d| *d != domain);
+		Ok(())
+	})?;
+
+	reply(ctx, format!("✅ Removed `{}`", domain)).await
+}
+
+/// Choose channels and roles automod leaves alone
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn exempt(
+	ctx: Context<'_>,
+	#[description = "Exempt, or check again"] exempt: bool,
+	#[description = "Channel to exempt"] channel: Option<GuildChannel>,
+	#[description = "Role to exempt"] role: Option<Role>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if channel.is_none() && role.is_none() {
+		return Err(BotError::InvalidInput(
+			"Give a channel or role.".to_string(),
+		));
+	}
+	if let Some(channel) = &channel
+		&& channel.guild_id != guild_id
+	{
+		return Err(BotError::NotFound(format!(
+			"<#{}> isn't a channel in this server.",
+			channel.id
+		)));
+	}
+
+	update(guild_id, |settings| {
+		if let Some(channel) = &channel {
+			settings.exempt_channels.retain(|id| *id != channel.id);
+			if exempt {
+				settings.exempt_channels.push(channel.id);
+			}
+		}
+		if let Some(role) = &role {
+			settings.exempt_roles.retain(|id| *id != role.id);
+			if exempt {
+				settings.exempt_roles.push(role.id);
+			}
+		}
+		Ok(())
+	})?;
+
+	let targets = [
+		channel.map(|c| format!("<#{}>", c.id)),
+		role.map(|r| r.mention()),
+	]
+	.into_iter()
+	.flatten()
+	.collect::<Vec<_>>()
+	.join(", ");
+
+	let response = match exempt {
+		| true => format!("✅ Automod will ignore {}", targets),
+		| false => format!("✅ Automod will check {} again", targets),
+	};
+	reply(ctx, response).await
+}
+
+/// Change how long the mute action times members out for
+#[poise::command(
+	prefix_command,
+	slash_command,
+	rename = "mute-length",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn mute_length(
+	ctx: Context<'_>,
+	#[description = "Length in minutes, up to 28 days"]
+	#[min = 1]
+	#[max = 40320]
+	minutes: u64,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if minutes == 0 || minutes > MAX_MUTE_MINUTES {
+		return Err(BotError::InvalidInput(
+			"Mutes can be between 1 minute and 28 days.".to_string(),
+		));
+	}
+
+	let settings = update(guild_id, |settings| {
+		settings.mute_minutes = minutes;
+		Ok(())
+	})?;
+
+	reply(
+		ctx,
+		format!(
+			"✅ Automod mutes now last {}",
+			format_duration(settings.mute_duration().as_secs())
+		),
+	)
+	.await
+}
@@ -0,0 +1,328 @@
+use poise::CreateReply;
+use serenity::all::{ChannelType, Color, CreateEmbed, CreateWebhook, GuildChannel, GuildId};
+
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{self, LogWebhook, Setting, SettingValue};
+use crate::utils::mention::Mentionable;
+use crate::{Context, Error};
+
+/// View or change this server's settings
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("view", "set", "reset", "prefix", "webhook"),
+	default_member_permissions = "MANAGE_GUILD",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+/// Show every setting and where its value comes from
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn view(ctx: Context<'_>) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let mut embed = CreateEmbed::default()
+		.title("Server Settings")
+		.color(Color::BLURPLE);
+
+	for setting in Setting::ALL {
+		let value = match guild_settings::get(guild_id, setting)? {
+			| Some(value) => value.display(),
+			| None => match setting.default_value() {
+				| Some(value) => format!("{} (default)", value.display()),
+				| None => "Not set".to_string(),
+			},
+		};
+		embed = embed.field(setting.label(), value, true);
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Change a setting for this server
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn set(
+	ctx: Context<'_>,
+	#[description = "Setting to change"] setting: Setting,
+	#[description = "Channel, role or text depending on the setting"]
+	#[rest]
+	value: String,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let value = setting.parse(&value)?;
+	validate(ctx, guild_id, &value).await?;
+
+	guild_settings::set(guild_id, setting, &value)?;
+	ctx.data().prefixes.invalidate(guild_id);
+
+	ctx.send(
+		CreateReply::default()
+			.content(format!("✅ {} set to {}", setting.label(), value.display()))
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Reset a setting back to its default
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn reset(
+	ctx: Context<'_>,
+	#[description = "Setting to reset"] setting: Setting,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	guild_settings::reset(guild_id, setting)?;
+	ctx.data().prefixes.invalidate(guild_id);
+
+	let default = match setting.default_value() {
+		| Some(value) => format!("the default ({})", value.display()),
+		| None => "not set".to_string(),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.content(format!("✅ {} reset to {}", setting.label(), default))
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Show or change the prefixes for text commands
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn prefix(
+	ctx: Context<'_>,
+	#[description = "New prefixes, separated by spaces"] prefixes: Option<String>,
+	#[description = "Whether mentioning the bot works as a prefix"] mention: Option<bool>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if let Some(prefixes) = prefixes {
+		let value = Setting::Prefix.parse(&prefixes)?;
+		guild_settings::set(guild_id, Setting::Prefix, &value)?;
+	}
+
+	if let Some(mention) = mention {
+		guild_settings::set(
+			guild_id,
+			Setting::MentionPrefix,
+			&SettingValue::Toggle(mention),
+		)?;
+	}
+
+	ctx.data().prefixes.invalidate(guild_id);
+
+	let prefixes = guild_settings::prefixes(Some(guild_id))?
+		.iter()
+		.map(|p| format!("`{}`", p))
+		.collect::<Vec<_>>()
+		.join(" ");
+	let mut response = format!("✅ Prefixes: {}", prefixes);
+	if guild_settings::mention_prefix(Some(guild_id))? {
+		response.push_str(&format!(
+			" or {}",
+			ctx.data().identity.require_id()?.mention()
+		));
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Send logs in a channel through a webhook with its own name and avatar
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn webhook(
+	ctx: Context<'_>,
+	#[description = "Log channel"] channel: GuildChannel,
+	#[description = "Send through a webhook instead of as the bot"] enabled: bool,
+	#[description = "Name logs are sent with"] name: Option<String>,
+	#[description = "Link to the avatar logs are sent with"] avatar_url: Option<String>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if channel.guild_id != guild_id {
+		return Err(BotError::NotFound(format!(
+			"<#{}> isn't a channel in this server.",
+			channel.id
+		)));
+	}
+
+	let existing = guild_settings::log_webhook(channel.id)?;
+
+	if !enabled {
+		if let Some(existing) = existing {
+			// already deleted by hand is fine too
+			let _ = ctx
+				.http()
+				.delete_webhook_with_token(existing.id, &existing.token, None)
+				.await;
+		}
+		guild_settings::set_log_webhook(channel.id, None)?;
+
+		ctx.send(
+			CreateReply::default()
+				.content(format!(
+					"✅ Logs in <#{}> are sent as the bot again",
+					channel.id
+				))
+				.ephemeral(ctx.prefix() == "/"),
+		)
+		.await?;
+		return Ok(());
+	}
+
+	// discord refuses to send with names it doesn't allow, which would lose every log
+	if let Some(name) = &name {
+		if !(1..=80).contains(&name.chars().count()) {
+			return Err(BotError::InvalidInput(
+				"The name has to be between 1 and 80 characters.".to_string(),
+			));
+		}
+		if name.to_lowercase().contains("discord") {
+			return Err(BotError::InvalidInput(
+				"Discord doesn't allow webhook names containing \"discord\".".to_string(),
+			));
+		}
+	}
+
+	if let Some(url) = &avatar_url
+		&& !url.starts_with("https://")
+	{
+		return Err(BotError::InvalidInput(
+			"The avatar has to be an https link.".to_string(),
+		));
+	}
+
+	let (id, token) = match existing {
+		| Some(existing) => (existing.id, existing.token),
+		| None => {
+			let webhook = channel
+				.id
+				.create_webhook(ctx.http(), CreateWebhook::new("Logs"))
+				.await
+				.map_err(|e| {
+					BotError::from(e).with_permission_denied(format!(
+						"I can't create webhooks in <#{}>, make sure I have the Manage Webhooks \
+						 permission there.",
+						channel.id
+					))
+				})?;
+			// the token is only exposed through the url, which ends with it
+			let url = webhook.url()?;
+			let token = url.rsplit('/').next().unwrap_or_default().to_string();
+			(webhook.id, token)
+		},
+	};
+
+	guild_settings::set_log_webhook(
+		channel.id,
+		Some(&LogWebhook {
+			id,
+			token,
+			name,
+			avatar_url,
+		}),
+	)?;
+
+	ctx.send(
+		CreateReply::default()
+			.content(format!(
+				"✅ Logs in <#{}> are sent through a webhook",
+				channel.id
+			))
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// makes sure channels and roles actually belong to this server
+async fn validate(
+	ctx: Context<'_>,
+	guild_id: GuildId,
+	value: &SettingValue,
+) -> Result<(), Error> {
+	match value {
+		| SettingValue::Channel(channel_id) => {
+			let channels = guild_id.channels(ctx.http()).await?;
+			let channel = channels.get(channel_id).ok_or(BotError::NotFound(format!(
+				"<#{}> isn't a channel in this server.",
+				channel_id
+			)))?;
+
+			if !matches!(channel.kind, ChannelType::Text | ChannelType::News) {
+				return Err(BotError::InvalidInput(format!(
+					"<#{}> isn't a text channel.",
+					channel_id
+				)));
+			}
+		},
+		| SettingValue::Role(role_id) => {
+			let roles = guild_id.roles(ctx.http()).await?;
+			if !roles.contains_key(role_id) {
+				return Err(BotError::NotFound(format!(
+					"<@&{}> isn't a role in this server.",
+					role_id
+				)));
+			}
+		},
+		| SettingValue::Roles(role_ids) => {
+			let roles = guild_id.roles(ctx.http()).await?;
+			if let Some(role_id) = role_ids.iter().find(|id| !roles.contains_key(id)) {
+				return Err(BotError::NotFound(format!(
+					"<@&{}> isn't a role in this server.",
+					role_id
+				)));
+			}
+		},
+		| SettingValue::Prefixes(_) | SettingValue::Toggle(_) | SettingValue::Offset(_) => {},
+	}
+
+	Ok(())
+}
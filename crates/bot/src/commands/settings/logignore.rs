@@ -0,0 +1,276 @@
+use poise::CreateReply;
+use serenity::all::{ChannelType, Color, CreateEmbed, GuildChannel, GuildId, Role, User};
+
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{self, IgnoreRules, LogType};
+use crate::utils::mention::Mentionable;
+use crate::{Context, Error};
+
+/// Choose what logs skip, like bot channels or staff members
+#[poise::command(
+	prefix_command,
+	slash_command,
+	subcommands("view", "add", "remove", "bots", "commands"),
+	default_member_permissions = "MANAGE_GUILD",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+pub async fn logignore(_ctx: Context<'_>) -> Result<(), Error> {
+	Ok(())
+}
+
+/// changes the rules of one log type, or every log type when `None`
+fn update(
+	guild_id: GuildId,
+	log_type: Option<LogType>,
+	change: impl Fn(&mut IgnoreRules),
+) -> Result<(), Error> {
+	let log_types = match log_type {
+		| Some(log_type) => vec![log_type],
+		| None => LogType::ALL.to_vec(),
+	};
+
+	for log_type in log_types {
+		let mut rules = guild_settings::ignore_rules(guild_id, log_type)?;
+		change(&mut rules);
+		guild_settings::set_ignore_rules(guild_id, log_type, &rules)?;
+	}
+
+	Ok(())
+}
+
+fn scope(log_type: Option<LogType>) -> String {
+	match log_type {
+		| Some(log_type) => log_type.label().to_lowercase(),
+		| None => "all logs".to_string(),
+	}
+}
+
+fn mentions<T: Mentionable>(items: &[T]) -> String {
+	match items.is_empty() {
+		| true => "None".to_string(),
+		| false => items
+			.iter()
+			.map(|i| i.mention())
+			.collect::<Vec<_>>()
+			.join(" "),
+	}
+}
+
+/// Show what a log type ignores
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn view(
+	ctx: Context<'_>,
+	#[description = "Log type"] log_type: LogType,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+	let rules = guild_settings::ignore_rules(guild_id, log_type)?;
+
+	let channels = |ids: &[serenity::all::ChannelId]| match ids.is_empty() {
+		| true => "None".to_string(),
+		| false => ids
+			.iter()
+			.map(|id| format!("<#{}>", id))
+			.collect::<Vec<_>>()
+			.join(" "),
+	};
+	let toggle = |on: bool| match on {
+		| true => "Ignored",
+		| false => "Logged",
+	};
+
+	let embed = CreateEmbed::default()
+		.title(format!("Ignored in {}", log_type.label().to_lowercase()))
+		.color(Color::BLURPLE)
+		.field("Channels", channels(&rules.channels), false)
+		.field("Categories", channels(&rules.categories), false)
+		.field("Users", mentions(&rules.users), false)
+		.field("Roles", mentions(&rules.roles), false)
+		.field("Bots", toggle(rules.bots), true)
+		.field("Commands", toggle(rules.commands), true);
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Stop logging a channel, category, user or role
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn add(
+	ctx: Context<'_>,
+	#[description = "Log type, leave empty for every log"] log_type: Option<LogType>,
+	#[description = "Channel or category to ignore"] channel: Option<GuildChannel>,
+	#[description = "User to ignore"] user: Option<User>,
+	#[description = "Role to ignore"] role: Option<Role>,
+) -> Result<(), Error> {
+	change(ctx, log_type, channel, user, role, true).await
+}
+
+/// Start logging a channel, category, user or role again
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn remove(
+	ctx: Context<'_>,
+	#[description = "Log type, leave empty for every log"] log_type: Option<LogType>,
+	#[description = "Channel or category to log again"] channel: Option<GuildChannel>,
+	#[description = "User to log again"] user: Option<User>,
+	#[description = "Role to log again"] role: Option<Role>,
+) -> Result<(), Error> {
+	change(ctx, log_type, channel, user, role, false).await
+}
+
+async fn change(
+	ctx: Context<'_>,
+	log_type: Option<LogType>,
+	channel: Option<GuildChannel>,
+	user: Option<User>,
+	role: Option<Role>,
+	ignore: bool,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	if channel.is_none() && user.is_none() && role.is_none() {
+		return Err(BotError::InvalidInput(
+			"Give a channel, user or role.".to_string(),
+		));
+	}
+	if let Some(channel) = &channel
+		&& channel.guild_id != guild_id
+	{
+		return Err(BotError::NotFound(format!(
+			"<#{}> isn't a channel in this server.",
+			channel.id
+		)));
+	}
+
+	fn toggle<T: PartialEq + Copy>(
+		list: &mut Vec<T>,
+		item: T,
+		ignore: bool,
+	) {
+		list.retain(|i| *i != item);
+		if ignore {
+			list.push(item);
+		}
+	}
+
+	update(guild_id, log_type, |rules| {
+		if let Some(channel) = &channel {
+			match channel.kind {
+				| ChannelType::Category => toggle(&mut rules.categories, channel.id, ignore),
+				| _ => toggle(&mut rules.channels, channel.id, ignore),
+			}
+		}
+		if let Some(user) = &user {
+			toggle(&mut rules.users, user.id, ignore);
+		}
+		if let Some(role) = &role {
+			toggle(&mut rules.roles, role.id, ignore);
+		}
+	})?;
+
+	let targets = [
+		channel.map(|c| format!("<#{}>", c.id)),
+		user.map(|u| u.mention()),
+		role.map(|r| r.mention()),
+	]
+	.into_iter()
+	.flatten()
+	.collect::<Vec<_>>()
+	.join(", ");
+
+	let response = match ignore {
+		| true => format!("✅ {} will be ignored in {}", targets, scope(log_type)),
+		| false => format!("✅ {} will be logged in {} again", targets, scope(log_type)),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose whether messages and actions from bots are logged
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn bots(
+	ctx: Context<'_>,
+	#[description = "Ignore bots"] ignore: bool,
+	#[description = "Log type, leave empty for every log"] log_type: Option<LogType>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	update(guild_id, log_type, |rules| rules.bots = ignore)?;
+
+	let response = match ignore {
+		| true => format!("✅ Bots will be ignored in {}", scope(log_type)),
+		| false => format!("✅ Bots will be logged in {}", scope(log_type)),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose whether messages using the bot's prefix are logged
+#[poise::command(
+	prefix_command,
+	slash_command,
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+async fn commands(
+	ctx: Context<'_>,
+	#[description = "Ignore command messages"] ignore: bool,
+	#[description = "Log type, leave empty for every log"] log_type: Option<LogType>,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	update(guild_id, log_type, |rules| rules.commands = ignore)?;
+
+	let response = match ignore {
+		| true => format!("✅ Commands will be ignored in {}", scope(log_type)),
+		| false => format!("✅ Commands will be logged in {}", scope(log_type)),
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.content(response)
+			.ephemeral(ctx.prefix() == "/"),
+	)
+	.await?;
+
+	Ok(())
+}
@@ -0,0 +1,7 @@
+//! This file is autogenerated by build.rs, do not edit.
+#![cfg_attr(rustfmt, rustfmt_skip)]
+
+pub mod automod;
+pub mod config;
+pub mod logignore;
+pub mod setup;
\ No newline at end of file
@@ -0,0 +1,590 @@
+use std::time::Duration;
+
+use poise::CreateReply;
+use serenity::all::{
+	ButtonStyle,
+	ChannelId,
+	ChannelType,
+	Color,
+	ComponentInteractionCollector,
+	ComponentInteractionDataKind,
+	CreateActionRow,
+	CreateButton,
+	CreateEmbed,
+	CreateInteractionResponse,
+	CreateInteractionResponseMessage,
+	CreateSelectMenu,
+	CreateSelectMenuKind,
+	CreateSelectMenuOption,
+	GuildId,
+	Member,
+	PartialGuild,
+	Permissions,
+	RoleId,
+};
+
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::{self, LogType, Setting, SettingValue};
+use crate::utils::moderation_utils::highest_position;
+use crate::{Context, Error};
+
+/// how long the wizard waits for each click before giving up
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// what the bot needs in every channel it posts to
+const CHANNEL_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+	.union(Permissions::SEND_MESSAGES)
+	.union(Permissions::EMBED_LINKS);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+	ModeratorRoles,
+	MutedRole,
+	ReportChannel,
+	LogChannels,
+	Confirm,
+}
+
+impl Step {
+	const ALL: [Step; 5] = [
+		Step::ModeratorRoles,
+		Step::MutedRole,
+		Step::ReportChannel,
+		Step::LogChannels,
+		Step::Confirm,
+	];
+
+	fn index(self) -> usize {
+		Step::ALL.iter().position(|s| *s == self).unwrap_or(0)
+	}
+
+	fn next(self) -> Step {
+		Step::ALL[(self.index() + 1).min(Step::ALL.len() - 1)]
+	}
+
+	fn previous(self) -> Step {
+		Step::ALL[self.index().saturating_sub(1)]
+	}
+}
+
+/// everything picked so far, only written to the settings store when the admin saves
+struct SetupState {
+	moderator_roles:    Vec<RoleId>,
+	muted_role:         Option<RoleId>,
+	report_channel:     Option<ChannelId>,
+	log_channels:       Vec<(LogType, ChannelId)>,
+	/// log types picked in the select menu, waiting for a channel
+	selected_log_types: Vec<LogType>,
+}
+
+impl SetupState {
+	fn load(guild_id: GuildId) -> Result<Self, Error> {
+		let muted_role = match guild_settings::get(guild_id, Setting::MutedRole)? {
+			| Some(SettingValue::Role(id)) => Some(id),
+			| _ => None,
+		};
+		let report_channel = match guild_settings::get(guild_id, Setting::ReportChannel)? {
+			| Some(SettingValue::Channel(id)) => Some(id),
+			| _ => None,
+		};
+
+		let mut log_channels = vec![];
+		for log_type in LogType::ALL {
+			if let Some(channel_id) = guild_settings::log_channel(guild_id, log_type)? {
+				log_channels.push((log_type, channel_id));
+			}
+		}
+
+		Ok(SetupState {
+			moderator_roles: guild_settings::moderator_roles(guild_id)?,
+			muted_role,
+			report_channel,
+			log_channels,
+			selected_log_types: vec![],
+		})
+	}
+
+	fn save(
+		&self,
+		guild_id: GuildId,
+	) -> Result<(), Error> {
+		guild_settings::set(
+			guild_id,
+			Setting::ModeratorRoles,
+			&SettingValue::Roles(self.moderator_roles.clone()),
+		)?;
+
+		match self.muted_role {
+			| Some(id) => {
+				guild_settings::set(guild_id, Setting::MutedRole, &SettingValue::Role(id))?
+			},
+			| None => guild_settings::reset(guild_id, Setting::MutedRole)?,
+		}
+
+		match self.report_channel {
+			| Some(id) => {
+				guild_settings::set(guild_id, Setting::ReportChannel, &SettingValue::Channel(id))?
+			},
+			| None => guild_settings::reset(guild_id, Setting::ReportChannel)?,
+		}
+
+		guild_settings::set_log_channels(guild_id, &self.log_channels)?;
+		// everything that was set is loaded into the wizard, so anything missing was cleared
+		let cleared: Vec<LogType> = LogType::ALL
+			.into_iter()
+			.filter(|t| !self.log_channels.iter().any(|(log_type, _)| log_type == t))
+			.collect();
+		guild_settings::remove_log_channels(guild_id, &cleared)?;
+
+		Ok(())
+	}
+
+	fn summary(&self) -> Vec<(&'static str, String, bool)> {
+		let moderator_roles = SettingValue::Roles(self.moderator_roles.clone()).display();
+		let muted_role = match self.muted_role {
+			| Some(id) => format!("<@&{}>", id),
+			| None => "Created when someone is first muted".to_string(),
+		};
+		let report_channel = match self.report_channel {
+			| Some(id) => format!("<#{}>", id),
+			| None => "Reports disabled".to_string(),
+		};
+
+		vec![
+			("Moderator roles", moderator_roles, false),
+			("Muted role", muted_role, true),
+			("Report channel", report_channel, true),
+			("Log channels", self.log_summary(), false),
+		]
+	}
+
+	fn log_summary(&self) -> String {
+		LogType::ALL
+			.iter()
+			.map(|t| {
+				let channel = self
+					.log_channels
+					.iter()
+					.find(|(log_type, _)| log_type == t)
+					.map(|(_, id)| format!("<#{}>", id))
+					.unwrap_or("Not logged".to_string());
+				format!("{}: {}", t.label(), channel)
+			})
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+}
+
+/// Set up moderation, reports and logging for this server step by step
+#[poise::command(
+	prefix_command,
+	slash_command,
+	default_member_permissions = "MANAGE_GUILD",
+	required_permissions = "MANAGE_GUILD",
+	guild_only
+)]
+pub async fn setup(ctx: Context<'_>) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let guild = ctx
+		.partial_guild()
+		.await
+		.ok_or(BotError::NotFound("Couldn't load this server.".into()))?;
+	let bot_id = ctx.data().identity.require_id()?;
+	let bot_member = guild_id.member(ctx.serenity_context(), bot_id).await?;
+
+	let mut state = SetupState::load(guild_id)?;
+	let mut step = Step::ModeratorRoles;
+
+	let (embed, components) = render(step, &state, None);
+	let reply = ctx
+		.send(
+			CreateReply::default()
+				.embed(embed)
+				.components(components)
+				.ephemeral(true),
+		)
+		.await?;
+	let message_id = reply.message().await?.id;
+
+	loop {
+		let Some(interaction) = ComponentInteractionCollector::new(ctx.serenity_context())
+			.message_id(message_id)
+			.author_id(ctx.author().id)
+			.timeout(TIMEOUT)
+			.await
+		else {
+			reply
+				.edit(
+					ctx,
+					CreateReply::default()
+						.embed(
+							CreateEmbed::default()
+								.title("Server Setup")
+								.description("Setup timed out, nothing was saved.")
+								.color(Color::RED),
+						)
+						.components(vec![]),
+				)
+				.await?;
+			return Ok(());
+		};
+
+		// a click that fails gets its own error, the wizard keeps going
+		match handle(
+			ctx,
+			&interaction,
+			&guild,
+			&bot_member,
+			&mut state,
+			&mut step,
+		)
+		.await
+		{
+			| Ok(false) => {},
+			| Ok(true) => return Ok(()),
+			| Err(e) => {
+				if e.is_internal() {
+					println!(
+						"\x1b[33;1m[WARN]\x1b[0m Setup failed in {}: {}",
+						guild_id, e
+					);
+				}
+				let _ = interaction
+					.create_response(
+						ctx.serenity_context(),
+						CreateInteractionResponse::Message(
+							CreateInteractionResponseMessage::new()
+								.content(e.to_string())
+								.ephemeral(true),
+						),
+					)
+					.await;
+			},
+		}
+	}
+}
+
+/// handles one click, true once the wizard is over
+async fn handle(
+	ctx: Context<'_>,
+	interaction: &serenity::all::ComponentInteraction,
+	guild: &PartialGuild,
+	bot_member: &Member,
+	state: &mut SetupState,
+	step: &mut Step,
+) -> Result<bool, Error> {
+	let mut problem = None;
+
+	match (&interaction.data.kind, interaction.data.custom_id.as_str()) {
+		| (
+			ComponentInteractionDataKind::RoleSelect {
+				values,
+			},
+			"setup_moderator_roles",
+		) => {
+			state.moderator_roles = values.clone();
+		},
+		| (
+			ComponentInteractionDataKind::RoleSelect {
+				values,
+			},
+			"setup_muted_role",
+		) => match values.first() {
+			| Some(role_id) => match check_muted_role(guild, bot_member, *role_id) {
+				| Ok(()) => state.muted_role = Some(*role_id),
+				| Err(e) => problem = Some(e),
+			},
+			| None => state.muted_role = None,
+		},
+		| (
+			ComponentInteractionDataKind::ChannelSelect {
+				values,
+			},
+			"setup_report_channel",
+		) => match values.first() {
+			| Some(channel_id) => match check_channel(ctx, guild, bot_member, *channel_id).await? {
+				| None => state.report_channel = Some(*channel_id),
+				| Some(e) => problem = Some(e),
+			},
+			| None => state.report_channel = None,
+		},
+		| (
+			ComponentInteractionDataKind::StringSelect {
+				values,
+			},
+			"setup_log_types",
+		) => {
+			state.selected_log_types = values
+				.iter()
+				.filter_map(|key| LogType::from_key(key))
+				.collect();
+		},
+		| (
+			ComponentInteractionDataKind::ChannelSelect {
+				values,
+			},
+			"setup_log_channel",
+		) => {
+			if let Some(channel_id) = values.first() {
+				if state.selected_log_types.is_empty() {
+					problem = Some("Pick which logs should go there first.".to_string());
+				} else if let Some(e) = check_channel(ctx, guild, bot_member, *channel_id).await? {
+					problem = Some(e);
+				} else {
+					for log_type in std::mem::take(&mut state.selected_log_types) {
+						state.log_channels.retain(|(t, _)| *t != log_type);
+						state.log_channels.push((log_type, *channel_id));
+					}
+				}
+			}
+		},
+		| (ComponentInteractionDataKind::Button, "setup_log_clear") => {
+			if state.selected_log_types.is_empty() {
+				problem = Some("Pick which logs to stop first.".to_string());
+			} else {
+				for log_type in std::mem::take(&mut state.selected_log_types) {
+					state.log_channels.retain(|(t, _)| *t != log_type);
+				}
+			}
+		},
+		| (ComponentInteractionDataKind::Button, "setup_back") => *step = step.previous(),
+		| (ComponentInteractionDataKind::Button, "setup_next") => *step = step.next(),
+		| (ComponentInteractionDataKind::Button, "setup_save") => {
+			state.save(guild.id)?;
+			let embed = CreateEmbed::default()
+				.title("Server Setup")
+				.description("✅ Setup saved! You can change any of this later with `/config`.")
+				.fields(state.summary())
+				.color(Color::DARK_GREEN);
+			respond(ctx, interaction, embed, vec![]).await?;
+			return Ok(true);
+		},
+		| (ComponentInteractionDataKind::Button, "setup_cancel") => {
+			let embed = CreateEmbed::default()
+				.title("Server Setup")
+				.description("Setup cancelled, nothing was saved.")
+				.color(Color::RED);
+			respond(ctx, interaction, embed, vec![]).await?;
+			return Ok(true);
+		},
+		| _ => {},
+	}
+
+	let (embed, components) = render(*step, state, problem.as_deref());
+	respond(ctx, interaction, embed, components).await?;
+	Ok(false)
+}
+
+async fn respond(
+	ctx: Context<'_>,
+	interaction: &serenity::all::ComponentInteraction,
+	embed: CreateEmbed,
+	components: Vec<CreateActionRow>,
+) -> Result<(), Error> {
+	interaction
+		.create_response(
+			ctx.serenity_context(),
+			CreateInteractionResponse::UpdateMessage(
+				CreateInteractionResponseMessage::new()
+					.embed(embed)
+					.components(components),
+			),
+		)
+		.await?;
+	Ok(())
+}
+
+fn render(
+	step: Step,
+	state: &SetupState,
+	problem: Option<&str>,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+	let (description, mut components) = match step {
+		| Step::ModeratorRoles => ("Pick the roles your moderators have.".to_string(), vec![
+			CreateActionRow::SelectMenu(
+				CreateSelectMenu::new("setup_moderator_roles", CreateSelectMenuKind::Role {
+					default_roles: Some(state.moderator_roles.clone()),
+				})
+				.placeholder("Moderator roles")
+				.min_values(0)
+				.max_values(10),
+			),
+		]),
+		| Step::MutedRole => (
+			"Pick the role muted members get. Leave it empty and one will be created the first \
+			 time someone is muted."
+				.to_string(),
+			vec![CreateActionRow::SelectMenu(
+				CreateSelectMenu::new("setup_muted_role", CreateSelectMenuKind::Role {
+					default_roles: state.muted_role.map(|id| vec![id]),
+				})
+				.placeholder("Muted role")
+				.min_values(0)
+				.max_values(1),
+			)],
+		),
+		| Step::ReportChannel => (
+			"Pick the channel user reports are sent to. Leave it empty to turn reports off."
+				.to_string(),
+			vec![CreateActionRow::SelectMenu(
+				CreateSelectMenu::new("setup_report_channel", CreateSelectMenuKind::Channel {
+					channel_types:    Some(vec![ChannelType::Text, ChannelType::News]),
+					default_channels: state.report_channel.map(|id| vec![id]),
+				})
+				.placeholder("Report channel")
+				.min_values(0)
+				.max_values(1),
+			)],
+		),
+		| Step::LogChannels => {
+			let options = LogType::ALL
+				.iter()
+				.map(|t| {
+					CreateSelectMenuOption::new(t.label(), t.key())
+						.default_selection(state.selected_log_types.contains(t))
+				})
+				.collect();
+
+			(
+				format!(
+					"Pick some logs, then the channel they should go to, or **Stop logging** to turn \
+					 them off. Repeat until everything is where you want it.\n\n{}",
+					state.log_summary()
+				),
+				vec![
+					CreateActionRow::SelectMenu(
+						CreateSelectMenu::new("setup_log_types", CreateSelectMenuKind::String {
+							options,
+						})
+						.placeholder("Logs")
+						.min_values(0)
+						.max_values(LogType::ALL.len() as u8),
+					),
+					CreateActionRow::SelectMenu(
+						CreateSelectMenu::new("setup_log_channel", CreateSelectMenuKind::Channel {
+							channel_types:    Some(vec![ChannelType::Text, ChannelType::News]),
+							default_channels: None,
+						})
+						.placeholder("Send them to")
+						.min_values(1)
+						.max_values(1),
+					),
+					CreateActionRow::Buttons(vec![
+						CreateButton::new("setup_log_clear")
+							.label("Stop logging")
+							.style(ButtonStyle::Secondary),
+					]),
+				],
+			)
+		},
+		| Step::Confirm => ("Here's everything you picked, save it?".to_string(), vec![]),
+	};
+
+	let mut buttons = vec![
+		CreateButton::new("setup_back")
+			.label("Back")
+			.style(ButtonStyle::Secondary)
+			.disabled(step == Step::ModeratorRoles),
+	];
+	if step == Step::Confirm {
+		buttons.push(
+			CreateButton::new("setup_save")
+				.label("Save")
+				.style(ButtonStyle::Success),
+		);
+	} else {
+		buttons.push(
+			CreateButton::new("setup_next")
+				.label("Next")
+				.style(ButtonStyle::Primary),
+		);
+	}
+	buttons.push(
+		CreateButton::new("setup_cancel")
+			.label("Cancel")
+			.style(ButtonStyle::Danger),
+	);
+	components.push(CreateActionRow::Buttons(buttons));
+
+	let mut embed = CreateEmbed::default()
+		.title(format!(
+			"Server Setup ({}/{})",
+			step.index() + 1,
+			Step::ALL.len()
+		))
+		.description(description)
+		.color(Color::BLURPLE);
+
+	if step == Step::Confirm {
+		embed = embed.fields(state.summary());
+	}
+
+	if let Some(problem) = problem {
+		embed = embed.field("❌ Not saved", problem, false);
+	}
+
+	(embed, components)
+}
+
+/// the bot has to be able to hand out the muted role, so it needs Manage Roles and a higher role
+fn check_muted_role(
+	guild: &PartialGuild,
+	bot_member: &Member,
+	role_id: RoleId,
+) -> Result<(), String> {
+	let Some(role) = guild.roles.get(&role_id) else {
+		return Err("That role doesn't exist anymore.".to_string());
+	};
+
+	if role.managed || role.id.get() == guild.id.get() {
+		return Err(format!(
+			"<@&{}> can't be given to members, pick another role.",
+			role_id
+		));
+	}
+
+	// guild wide permissions, channel overwrites don't matter for managing roles
+	let permissions = bot_member
+		.roles
+		.iter()
+		.chain([&RoleId::new(guild.id.get())])
+		.filter_map(|id| guild.roles.get(id))
+		.fold(Permissions::empty(), |acc, role| acc | role.permissions);
+
+	if !permissions.administrator() && !permissions.manage_roles() {
+		return Err("I need the Manage Roles permission to give out the muted role.".to_string());
+	}
+
+	if highest_position(bot_member, &guild.roles) <= role.position {
+		return Err(format!(
+			"<@&{}> is above my highest role, move my role above it first.",
+			role_id
+		));
+	}
+
+	Ok(())
+}
+
+/// returns what's wrong with the channel, if anything
+async fn check_channel(
+	ctx: Context<'_>,
+	guild: &PartialGuild,
+	bot_member: &Member,
+	channel_id: ChannelId,
+) -> Result<Option<String>, Error> {
+	let channels = guild.id.channels(ctx.http()).await?;
+	let Some(channel) = channels.get(&channel_id) else {
+		return Ok(Some("That channel doesn't exist anymore.".to_string()));
+	};
+
+	let missing = CHANNEL_PERMISSIONS - guild.user_permissions_in(channel, bot_member);
+	if missing.is_empty() {
+		return Ok(None);
+	}
+
+	Ok(Some(format!(
+		"I can't post in <#{}>, I'm missing: {}",
+		channel_id, missing
+	)))
+}
@@ -1,7 +1,5 @@
-use poise::CreateReply;
 use serenity::all::CreateMessage;
 
-use crate::utils::embeds::ToEmbed;
 use crate::utils::tag_utils::get_data_and_id;
 use crate::{Context, Error};
 
@@ -17,23 +15,27 @@ pub async fn dtag(
 
 	let (data, id) = get_data_and_id(ctx).await?;
 
-		match data.tag_db.get_tag(&name, id).await {
-		| Ok(v) => {
-			let mut message = CreateMessage::default().content(v);
+	// the invoking message gets deleted even if the tag doesn't exist
+	let content = match data.tag_db.get_tag(&name, id).await {
+		| Ok(content) => content,
+		| Err(e) => {
+			if let Some(msg) = msg {
+				msg.delete(ctx.serenity_context()).await?;
+			}
+			return Err(e);
+		},
+	};
 
-		if let Some(referenced_message) = msg.and_then(|m| m.message_reference.clone()) {
-			message = message.reference_message(referenced_message);
-		}
+	let mut message = CreateMessage::default().content(content);
 
-			ctx.channel_id()
-				.send_message(ctx.serenity_context(), message)
-				.await?;
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed())).await?;
-		}
+	if let Some(referenced_message) = msg.and_then(|m| m.message_reference.clone()) {
+		message = message.reference_message(referenced_message);
 	}
 
+	ctx.channel_id()
+		.send_message(ctx.serenity_context(), message)
+		.await?;
+
 	if let Some(msg) = msg {
 		msg.delete(ctx.serenity_context()).await?;
 	}
@@ -1,7 +1,6 @@
 use poise::CreateReply;
 use serenity::all::{CreateEmbed, CreateMessage};
 
-use crate::utils::embeds::ToEmbed;
 use crate::utils::tag_utils::get_data_and_id;
 use crate::{Context, Error};
 
@@ -24,23 +23,17 @@ pub async fn tag(
 
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.get_tag(&name, id).await {
-		| Ok(v) => {
-			let mut message = CreateMessage::default().content(v);
+	let content = data.tag_db.get_tag(&name, id).await?;
+	let mut message = CreateMessage::default().content(content);
 
-			if let Some(msg_ref) = referenced_message {
-				message = message.reference_message(msg_ref);
-			}
-
-			ctx.channel_id()
-				.send_message(ctx.serenity_context(), message)
-				.await?;
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed())).await?;
-		}
+	if let Some(msg_ref) = referenced_message {
+		message = message.reference_message(msg_ref);
 	}
 
+	ctx.channel_id()
+		.send_message(ctx.serenity_context(), message)
+		.await?;
+
 	Ok(())
 }
 
@@ -62,16 +55,9 @@ async fn create(
 ) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.create_tag(&name, &content, id).await {
-		| Ok(()) => {
-			ctx.send(CreateReply::default().content(format!("✅ Created tag `{}`", name)))
-				.await?
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed()))
-				.await?
-		},
-	};
+	data.tag_db.create_tag(&name, &content, id).await?;
+	ctx.send(CreateReply::default().content(format!("✅ Created tag `{}`", name)))
+		.await?;
 
 	Ok(())
 }
@@ -90,16 +76,9 @@ async fn delete(
 ) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.delete_tag(&name, id).await {
-		| Ok(name) => {
-			ctx.send(CreateReply::default().content(format!("✅ Deleted tag `{}`", name)))
-				.await?
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed()))
-				.await?
-		},
-	};
+	let name = data.tag_db.delete_tag(&name, id).await?;
+	ctx.send(CreateReply::default().content(format!("✅ Deleted tag `{}`", name)))
+		.await?;
 
 	Ok(())
 }
@@ -121,16 +100,9 @@ async fn edit(
 ) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.edit_tag(&name, &content, id).await {
-		| Ok(name) => {
-			ctx.send(CreateReply::default().content(format!("✅ Updated tag `{}`", name)))
-				.await?
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed()))
-				.await?
-		},
-	};
+	let name = data.tag_db.edit_tag(&name, &content, id).await?;
+	ctx.send(CreateReply::default().content(format!("✅ Updated tag `{}`", name)))
+		.await?;
 
 	Ok(())
 }
@@ -146,34 +118,24 @@ async fn edit(
 async fn list(ctx: Context<'_>) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.get_all_tags(id).await {
-		| Ok(tags) => {
-			let formatted_tags = if tags.is_empty() {
-				"No tags found. Try creating a tag with `/tag create`".to_string()
-			} else {
-				tags.join(", ")
-			};
+	let tags = data.tag_db.get_all_tags(id).await?;
+	let formatted_tags = if tags.is_empty() {
+		"No tags found. Try creating a tag with `/tag create`".to_string()
+	} else {
+		tags.join(", ")
+	};
 
-			ctx.send(
-				CreateReply::default()
-					.embed(
-						CreateEmbed::default()
-							.title("All Tags")
-							.description(formatted_tags),
-					)
-					.ephemeral(true),
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::default()
+					.title("All Tags")
+					.description(formatted_tags),
 			)
-			.await?
-		},
-		| Err(e) => {
-			ctx.send(
-				CreateReply::default()
-					.embed(e.to_embed())
-					.ephemeral(true),
-			)
-			.await?
-		},
-	};
+			.ephemeral(true),
+	)
+	.await?;
+
 	Ok(())
 }
 
@@ -185,16 +147,9 @@ async fn preview(
 ) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.get_tag(&name, id).await {
-		| Ok(content) => {
-			ctx.send(CreateReply::default().content(content).ephemeral(true))
-				.await?
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed()))
-				.await?
-		},
-	};
+	let content = data.tag_db.get_tag(&name, id).await?;
+	ctx.send(CreateReply::default().content(content).ephemeral(true))
+		.await?;
 
 	Ok(())
 }
@@ -207,23 +162,21 @@ async fn raw(
 ) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.get_tag(&name, id).await {
-		| Ok(content) => {
-			ctx.send(CreateReply::default().content(content.replace("`", "\\`")
-			.replace("*", "\\*")
-			.replace("_", "\\_")
-			.replace("~", "\\~")
-			.replace("#", "\\#")
-			.replace("<", "\\<")
-			.replace(">", "\\>")
-			.replace("|", "\\|")))
-				.await?
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed()))
-				.await?
-		},
-	};
+	let content = data.tag_db.get_tag(&name, id).await?;
+	ctx.send(
+		CreateReply::default().content(
+			content
+				.replace("`", "\\`")
+				.replace("*", "\\*")
+				.replace("_", "\\_")
+				.replace("~", "\\~")
+				.replace("#", "\\#")
+				.replace("<", "\\<")
+				.replace(">", "\\>")
+				.replace("|", "\\|"),
+		),
+	)
+	.await?;
 
 	Ok(())
 }
@@ -237,26 +190,10 @@ async fn alias(
 ) -> Result<(), Error> {
 	let (data, id) = get_data_and_id(ctx).await?;
 
-	match data.tag_db.get_tag(&name, id).await {
-		| Ok(content) => {
-			match data.tag_db.create_tag(&alias, &content, id).await {
-				| Ok(()) => {
-					ctx.send(
-						CreateReply::default().content(format!("✅ Created tag alias `{}`", alias)),
-					)
-					.await?
-				},
-				| Err(e) => {
-					ctx.send(CreateReply::default().embed(e.to_embed()))
-						.await?
-				},
-			}
-		},
-		| Err(e) => {
-			ctx.send(CreateReply::default().embed(e.to_embed()))
-				.await?
-		},
-	};
+	let content = data.tag_db.get_tag(&name, id).await?;
+	data.tag_db.create_tag(&alias, &content, id).await?;
+	ctx.send(CreateReply::default().content(format!("✅ Created tag alias `{}`", alias)))
+		.await?;
 
 	Ok(())
 }
@@ -0,0 +1,367 @@
+use std::env::var;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serenity::all::{ChannelId, RoleId};
+use shared::attachment_links;
+
+pub const DATA_DIR: &str = "data";
+pub const TAGS_DB_PATH: &str = "data/tags";
+pub const WARNS_DB_PATH: &str = "data/user_warns.db";
+pub const LOG_CHANNELS_DB_PATH: &str = "data/guild_settings/log_channels";
+pub const LOG_IGNORES_DB_PATH: &str = "data/guild_settings/log_ignores";
+pub const LOG_WEBHOOKS_DB_PATH: &str = "data/guild_settings/log_webhooks";
+pub const CONFIG_DB_PATH: &str = "data/guild_settings/config";
+pub const AUTOMOD_DB_PATH: &str = "data/guild_settings/automod";
+pub const CHANNEL_LOCKS_DB_PATH: &str = "data/guild_settings/channel_locks";
+pub const SLOWMODE_DB_PATH: &str = "data/guild_settings/slowmode";
+pub const MUTES_DB_PATH: &str = "data/mutes";
+pub const ATTACHMENTS_DIR: &str = "data/attachments";
+/// one domain per line, written from the bundled list on first start and reloaded when changed
+pub const PHISHING_DOMAINS_PATH: &str = "data/phishing_domains.txt";
+pub const BOT_STATUS_PATH: &str = "data/bot_status.json";
+pub const DEFAULT_PREFIX: &str = "-";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Everything read from the environment, validated once at startup by [`Config::load`]
+pub struct Config {
+	pub bot_token:                String,
+	pub report_channel_id:        Option<ChannelId>,
+	pub report_notification_role: Option<RoleId>,
+	pub muted_role_id:            Option<RoleId>,
+	pub error_channel_id:         Option<ChannelId>,
+	/// prefix for guilds that haven't set their own
+	pub prefix:                   String,
+	/// how many recent messages are kept per guild for edit and delete logs
+	pub message_cache_size:       usize,
+	pub message_cache_retention:  Duration,
+	/// signs attachment links, the api needs the same one. attachments aren't kept without it
+	pub attachment_secret:        Option<String>,
+	/// where the api can be reached from discord, used for attachment links
+	pub api_url:                  String,
+	/// in bytes, bigger attachments are only logged by name
+	pub attachment_max_size:      u64,
+	/// in bytes, the oldest attachments are deleted past this
+	pub attachment_quota:         u64,
+	pub attachment_link_ttl:      Duration,
+}
+
+/// The config loaded at startup, only call this after [`Config::load`] succeeded
+pub fn config() -> &'static Config {
+	CONFIG
+		.get()
+		.expect("config is loaded before anything else runs")
+}
+
+/// Problems found while loading the config, split so warnings don't stop the bot from starting
+#[derive(Default)]
+pub struct ConfigReport {
+	pub errors:   Vec<String>,
+	pub warnings: Vec<String>,
+}
+
+impl ConfigReport {
+	pub fn print(&self) {
+		for warning in &self.warnings {
+			println!("\x1b[33;1m[WARN]\x1b[0m {}", warning);
+		}
+
+		if !self.errors.is_empty() {
+			println!(
+				"\x1b[31;1m[ERROR] Invalid configuration, fix these in your .env file:\x1b[0m"
+			);
+			for error in &self.errors {
+				println!("  - {}", error);
+			}
+		}
+	}
+}
+
+impl Config {
+	/// Reads and validates every env var and data path, storing the config globally if there
+	/// were no errors
+	pub fn load() -> ConfigReport {
+		let mut report = ConfigReport::default();
+
+		let bot_token = required(&mut report, "BOT_TOKEN");
+		let report_channel_id = optional_id(
+			&mut report,
+			"REPORT_CHANNEL_ID",
+			"reports only work in servers that set a report channel with `/config set`",
+		)
+		.map(ChannelId::new);
+		let report_notification_role =
+			optional_id(&mut report, "REPORT_NOTIFICATION_ROLE", "").map(RoleId::new);
+		let muted_role_id = optional_id(&mut report, "MUTED_ROLE_ID", "").map(RoleId::new);
+		let error_channel_id = optional_id(&mut report, "ERROR_CHANNEL_ID", "").map(ChannelId::new);
+		let prefix = read("DEFAULT_PREFIX").unwrap_or_else(|| DEFAULT_PREFIX.to_string());
+		if prefix.contains(char::is_whitespace) {
+			report.errors.push(format!(
+				"`DEFAULT_PREFIX` can't contain spaces, got `{}`",
+				prefix
+			));
+		}
+
+		let message_cache_size = optional_number(&mut report, "MESSAGE_CACHE_SIZE", 1000);
+		let message_cache_retention =
+			optional_scaled(&mut report, "MESSAGE_CACHE_RETENTION_HOURS", 24, 3600);
+
+		let attachment_secret = match attachment_links::secret() {
+			| Ok(Some(secret)) => Some(secret),
+			| Ok(None) => {
+				report.warnings.push(format!(
+					"`{}` isn't set, attachments of deleted messages won't be kept",
+					attachment_links::SECRET_VAR
+				));
+				None
+			},
+			| Err(e) => {
+				report.errors.push(e);
+				None
+			},
+		};
+		let api_url = read("API_PUBLIC_URL").unwrap_or_else(|| {
+			format!(
+				"http://localhost:{}",
+				read("API_PORT").unwrap_or("3000".to_string())
+			)
+		});
+		let attachment_max_size =
+			optional_scaled(&mut report, "ATTACHMENT_MAX_SIZE_MB", 25, 1024 * 1024);
+		let attachment_quota =
+			optional_scaled(&mut report, "ATTACHMENT_QUOTA_MB", 1024, 1024 * 1024);
+		let attachment_link_ttl = optional_scaled(&mut report, "ATTACHMENT_LINK_HOURS", 168, 3600);
+
+		check_data_paths(&mut report);
+
+		if report.errors.is_empty() {
+			let _ = CONFIG.set(Config {
+				bot_token: bot_token.unwrap_or_default(),
+				report_channel_id,
+				report_notification_role,
+				muted_role_id,
+				error_channel_id,
+				prefix,
+				message_cache_size: message_cache_size as usize,
+				message_cache_retention: Duration::from_secs(message_cache_retention),
+				attachment_secret,
+				api_url,
+				attachment_max_size,
+				attachment_quota,
+				attachment_link_ttl: Duration::from_secs(attachment_link_ttl),
+			});
+		}
+
+		report
+	}
+}
+
+/// blank values count as unset, .env.example leaves every optional value blank
+fn read(key: &str) -> Option<String> {
+	var(key).ok().as_deref().and_then(non_blank)
+}
+
+fn non_blank(value: &str) -> Option<String> {
+	Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn required(
+	report: &mut ConfigReport,
+	key: &str,
+) -> Option<String> {
+	let value = read(key);
+	if value.is_none() {
+		report.errors.push(format!("`{}` is missing", key));
+	}
+	value
+}
+
+/// `unset_effect` is added as a warning when the var isn't set, leave it empty to stay quiet
+fn optional_id(
+	report: &mut ConfigReport,
+	key: &str,
+	unset_effect: &str,
+) -> Option<u64> {
+	let Some(value) = read(key) else {
+		if !unset_effect.is_empty() {
+			report
+				.warnings
+				.push(format!("`{}` isn't set, {}", key, unset_effect));
+		}
+		return None;
+	};
+
+	parse_id(key, &value)
+		.map_err(|e| report.errors.push(e))
+		.ok()
+}
+
+fn parse_id(
+	key: &str,
+	value: &str,
+) -> Result<u64, String> {
+	match value.parse::<u64>() {
+		| Ok(id) if id != 0 => Ok(id),
+		| _ => Err(format!(
+			"`{}` must be a discord id (a number), got `{}`",
+			key, value
+		)),
+	}
+}
+
+fn optional_number(
+	report: &mut ConfigReport,
+	key: &str,
+	default: u64,
+) -> u64 {
+	let Some(value) = read(key) else {
+		return default;
+	};
+
+	parse_number(key, &value).unwrap_or_else(|e| {
+		report.errors.push(e);
+		default
+	})
+}
+
+fn parse_number(
+	key: &str,
+	value: &str,
+) -> Result<u64, String> {
+	value
+		.parse::<u64>()
+		.map_err(|_| format!("`{}` must be a number, got `{}`", key, value))
+}
+
+/// [`optional_number`] in a smaller unit, like hours as seconds. Values too big to convert are
+/// an error
+fn optional_scaled(
+	report: &mut ConfigReport,
+	key: &str,
+	default: u64,
+	unit: u64,
+) -> u64 {
+	let number = optional_number(report, key, default);
+	scale(key, number, unit).unwrap_or_else(|e| {
+		report.errors.push(e);
+		default * unit
+	})
+}
+
+fn scale(
+	key: &str,
+	number: u64,
+	unit: u64,
+) -> Result<u64, String> {
+	number
+		.checked_mul(unit)
+		.ok_or(format!("`{}` is too big, got `{}`", key, number))
+}
+
+fn check_data_paths(report: &mut ConfigReport) {
+	let settings_dir = Path::new(LOG_CHANNELS_DB_PATH)
+		.parent()
+		.unwrap_or(Path::new(DATA_DIR));
+
+	if let Err(e) = std::fs::create_dir_all(settings_dir) {
+		report.errors.push(format!(
+			"couldn't create the data directory `{}`: {}",
+			settings_dir.display(),
+			e
+		));
+		return;
+	}
+
+	// creating the dir works even on read only mounts in some cases so actually write something
+	let probe = Path::new(DATA_DIR).join(".write_test");
+	if let Err(e) = std::fs::write(&probe, b"ok").and_then(|_| std::fs::remove_file(&probe)) {
+		report.errors.push(format!(
+			"the data directory `{}` isn't writable: {}",
+			DATA_DIR, e
+		));
+		return;
+	}
+
+	if let Err(e) = rusqlite::Connection::open(WARNS_DB_PATH) {
+		report
+			.errors
+			.push(format!("couldn't open `{}`: {}", WARNS_DB_PATH, e));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blank_values_are_unset() {
+		assert_eq!(non_blank(""), None);
+		assert_eq!(non_blank("  \t"), None);
+		assert_eq!(non_blank(" 42 "), Some("42".to_string()));
+	}
+
+	#[test]
+	fn ids_are_nonzero_numbers() {
+		assert_eq!(parse_id("KEY", "1234567890"), Ok(1234567890));
+		assert!(parse_id("KEY", "0").is_err());
+		assert!(parse_id("KEY", "-5").is_err());
+		assert!(parse_id("KEY", "id of the channel").is_err());
+	}
+
+	#[test]
+	fn numbers_and_units() {
+		assert_eq!(parse_number("KEY", "1000"), Ok(1000));
+		assert!(parse_number("KEY", "a lot").is_err());
+		assert_eq!(scale("KEY", 24, 3600), Ok(86400));
+		assert!(scale("KEY", u64::MAX, 3600).is_err());
+	}
+
+	/// copying .env.example as is has to give a config that loads
+	#[test]
+	fn env_example_values_parse() {
+		let ids = [
+			"REPORT_CHANNEL_ID",
+			"REPORT_NOTIFICATION_ROLE",
+			"MUTED_ROLE_ID",
+			"ERROR_CHANNEL_ID",
+		];
+		let numbers = [
+			"API_PORT",
+			"MESSAGE_CACHE_SIZE",
+			"MESSAGE_CACHE_RETENTION_HOURS",
+			"ATTACHMENT_MAX_SIZE_MB",
+			"ATTACHMENT_QUOTA_MB",
+			"ATTACHMENT_LINK_HOURS",
+		];
+
+		let example = include_str!("../../../.env.example");
+		let values: Vec<(&str, &str)> = example
+			.lines()
+			.filter(|line| !line.starts_with('#'))
+			.filter_map(|line| line.split_once('='))
+			.map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+			.collect();
+
+		for key in ids.iter().chain(&numbers) {
+			assert!(
+				values.iter().any(|(k, _)| k == key),
+				"{} is missing from .env.example",
+				key
+			);
+		}
+
+		for (key, value) in values {
+			let Some(value) = non_blank(value) else {
+				continue;
+			};
+			if ids.contains(&key) {
+				assert!(parse_id(key, &value).is_ok(), "{}", key);
+			}
+			if numbers.contains(&key) {
+				assert!(parse_number(key, &value).is_ok(), "{}", key);
+			}
+		}
+	}
+}
@@ -1,45 +1,251 @@
 mod background;
 mod commands;
+mod config;
 mod utils;
 
-use std::env::var;
 use std::sync::Arc;
 use std::time::Duration;
 
+use background::automod::Automod;
 use background::guild_logs::Handler;
+use background::guild_logs::log_queue::LogQueue;
+use background::guild_logs::member_cache::MemberCache;
+use background::guild_logs::message_cache::MessageCache;
+use background::guild_logs::structure_cache::StructureCache;
+use background::guild_logs::voice_cache::VoiceCache;
+use config::{Config, config};
 use dotenv::dotenv;
 use poise::serenity_prelude as serenity;
 use types::{ApplicationContext, Context, Error};
+use utils::embeds::ToEmbed;
+use utils::errors::BotError;
+use utils::identity::Identity;
+use utils::incidents::Incident;
+use utils::mod_actions::ModActions;
+use utils::prefix_cache::PrefixCache;
 use utils::tag_utils::TagDb;
 
 mod types {
-	pub type Error = Box<dyn std::error::Error + Send + Sync>;
+	pub type Error = crate::utils::errors::BotError;
 	pub type Context<'a> = poise::Context<'a, super::Data, Error>;
 	pub type ApplicationContext<'a> = poise::ApplicationContext<'a, super::Data, Error>;
 }
 
 pub struct Data {
-	pub tag_db: Arc<TagDb>,
+	pub tag_db:      Arc<TagDb>,
+	pub prefixes:    Arc<PrefixCache>,
+	/// moderation done through commands, read by the logs to credit the moderator
+	pub mod_actions: Arc<ModActions>,
+	/// the bot's own user, shared with the raw event handler
+	pub identity:    Arc<Identity>,
+	/// for commands that log what they did, same queue as the guild logs
+	pub log_queue:   Arc<LogQueue>,
 }
 
 async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
+	use poise::FrameworkError::*;
+
 	match error {
-		| poise::FrameworkError::Setup {
+		| Setup {
 			error, ..
 		} => {
 			panic!("\x1b[31;1m[ERROR] Failed to start bot:\x1b[0m {:?}", error)
 		},
-		| poise::FrameworkError::Command {
+		| Command {
 			error,
 			ctx,
 			..
 		} => {
-			println!(
-				"\x1b[31;1m[ERROR] in command '{}':\x1b[0m {:?}",
-				ctx.command().name,
-				error
+			let source = format!("command `{}`", ctx.command().qualified_name);
+			if error.is_internal() {
+				report_incident(ctx, Incident::new(source, format!("{:?}", error))).await;
+			} else {
+				reply_error(ctx, error).await;
+			}
+		},
+		| CommandPanic {
+			payload,
+			ctx,
+			..
+		} => {
+			let source = format!("command `{}` (panic)", ctx.command().qualified_name);
+			let details = payload.unwrap_or("no panic payload".into());
+			report_incident(ctx, Incident::new(source, details)).await;
+		},
+		| CommandCheckFailed {
+			error: Some(error),
+			ctx,
+			..
+		} => {
+			let source = format!("checks for `{}`", ctx.command().qualified_name);
+			report_incident(ctx, Incident::new(source, format!("{:?}", error))).await;
+		},
+		| CommandCheckFailed {
+			error: None,
+			ctx,
+			..
+		} => {
+			reply_error(
+				ctx,
+				BotError::PermissionDenied("You can't use this command here.".into()),
+			)
+			.await;
+		},
+		| CommandStructureMismatch {
+			description,
+			ctx,
+			..
+		} => {
+			let source = format!("arguments for `/{}`", ctx.command.qualified_name);
+			report_incident(
+				poise::Context::Application(ctx),
+				Incident::new(source, description),
+			)
+			.await;
+		},
+		| ArgumentParse {
+			error,
+			input,
+			ctx,
+			..
+		} => {
+			let usage = ctx
+				.command()
+				.help_text
+				.as_deref()
+				.unwrap_or("Check the command description for usage information.");
+			let message = match input {
+				| Some(input) => format!("Couldn't understand `{}`: {}\n{}", input, error, usage),
+				| None => format!("{}\n{}", error, usage),
+			};
+			reply_error(ctx, BotError::InvalidInput(message)).await;
+		},
+		| SubcommandRequired {
+			ctx,
+		} => {
+			let subcommands = ctx
+				.command()
+				.subcommands
+				.iter()
+				.map(|s| format!("`{}`", s.name))
+				.collect::<Vec<_>>()
+				.join(", ");
+			reply_error(
+				ctx,
+				BotError::InvalidInput(format!("Pick one of these subcommands: {}", subcommands)),
+			)
+			.await;
+		},
+		| CooldownHit {
+			remaining_cooldown,
+			ctx,
+			..
+		} => {
+			let message = format!(
+				"You're going too fast, try again in {} seconds.",
+				remaining_cooldown.as_secs().max(1)
+			);
+			reply_error(ctx, BotError::InvalidInput(message)).await;
+		},
+		| MissingBotPermissions {
+			missing_permissions,
+			ctx,
+			..
+		} => {
+			let message = format!(
+				"I'm missing these permissions to run this command: {}",
+				missing_permissions
 			);
+			reply_error(ctx, BotError::PermissionDenied(message)).await;
+		},
+		| MissingUserPermissions {
+			missing_permissions,
+			ctx,
+			..
+		} => {
+			let message = match missing_permissions {
+				| Some(permissions) => {
+					format!("You need these permissions to do that: {}", permissions)
+				},
+				| None => "Couldn't check your permissions, not running this to be safe.".into(),
+			};
+			reply_error(ctx, BotError::PermissionDenied(message)).await;
 		},
+		| NotAnOwner {
+			ctx, ..
+		} => {
+			reply_error(
+				ctx,
+				BotError::PermissionDenied("Only the bot owner can use this command.".into()),
+			)
+			.await;
+		},
+		| GuildOnly {
+			ctx, ..
+		} => {
+			reply_error(ctx, BotError::guild_only()).await;
+		},
+		| DmOnly {
+			ctx, ..
+		} => {
+			reply_error(
+				ctx,
+				BotError::InvalidInput("This command can only be used in DMs.".into()),
+			)
+			.await;
+		},
+		| NsfwOnly {
+			ctx, ..
+		} => {
+			reply_error(
+				ctx,
+				BotError::InvalidInput("This command can only be used in NSFW channels.".into()),
+			)
+			.await;
+		},
+		| EventHandler {
+			error,
+			ctx,
+			event,
+			..
+		} => {
+			let source = format!("event handler `{}`", event.snake_case_name());
+			Incident::new(source, format!("{:?}", error))
+				.report(&ctx.http)
+				.await;
+		},
+		| DynamicPrefix {
+			error,
+			ctx,
+			..
+		} => {
+			Incident::new("dynamic prefix", format!("{:?}", error))
+				.report(&ctx.serenity_context.http)
+				.await;
+		},
+		| UnknownInteraction {
+			ctx,
+			interaction,
+			..
+		} => {
+			let _ = interaction
+				.create_response(
+					ctx,
+					serenity::CreateInteractionResponse::Message(
+						serenity::CreateInteractionResponseMessage::new()
+							.embed(
+								BotError::NotFound("This command doesn't exist anymore.".into())
+									.to_embed(),
+							)
+							.ephemeral(true),
+					),
+				)
+				.await;
+		},
+		// prefix matched but no command did, usually just someone typing normally
+		| UnknownCommand {
+			..
+		} => {},
 		| error => {
 			if let Err(e) = poise::builtins::on_error(error).await {
 				println!("\x1b[31;1m[ERROR] while handling error:\x1b[0m {}", e)
@@ -48,30 +254,81 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
 	}
 }
 
-pub trait ExpectError<T> {
-	fn expect_error(
-		self,
-		msg: &str,
-	) -> T;
+async fn reply_error(
+	ctx: Context<'_>,
+	error: Error,
+) {
+	if let Err(e) = ctx
+		.send(
+			poise::CreateReply::default()
+				.embed(error.to_embed())
+				.ephemeral(true),
+		)
+		.await
+	{
+		println!("\x1b[31;1m[ERROR] while handling error:\x1b[0m {}", e)
+	}
 }
 
-impl<T, E: std::fmt::Debug> ExpectError<T> for Result<T, E> {
-	fn expect_error(
-		self,
-		msg: &str,
-	) -> T {
-		self.unwrap_or_else(|_| panic!("\x1b[31;1m[ERROR] {}\x1b[0m", msg))
-	}
+async fn report_incident(
+	ctx: Context<'_>,
+	incident: Incident,
+) {
+	incident.report(ctx.http()).await;
+
+	let _ = ctx
+		.send(
+			poise::CreateReply::default()
+				.embed((&incident).to_embed())
+				.ephemeral(true),
+		)
+		.await;
 }
 
 #[tokio::main]
 async fn main() {
 	dotenv().ok();
 
+	let report = Config::load();
+	report.print();
+	if !report.errors.is_empty() {
+		std::process::exit(1);
+	}
+
+	let tag_db = match TagDb::new() {
+		| Ok(db) => Arc::new(db),
+		| Err(e) => {
+			println!(
+				"\x1b[31;1m[ERROR] Couldn't open the tag database at `{}`:\x1b[0m {}",
+				config::TAGS_DB_PATH,
+				e
+			);
+			std::process::exit(1);
+		},
+	};
+
+	let mod_actions = Arc::new(ModActions::default());
+	let log_queue = Arc::new(LogQueue::default());
+	let prefixes = Arc::new(PrefixCache::default());
+	let identity = Arc::new(Identity::default());
+
+	let mut commands = commands::get_all_commands();
+	// built before the prefix only subcommands are added, they'd hide /mute's options otherwise
+	let slash_commands = poise::builtins::create_application_commands(&commands);
+	commands::moderation::mutes::add_prefix_subcommands(&mut commands);
+
 	let options = poise::FrameworkOptions {
-		commands: commands::get_all_commands(),
+		commands,
 		prefix_options: poise::PrefixFrameworkOptions {
-			prefix: Some("-".into()),
+			stripped_dynamic_prefix: Some(|_ctx, msg, data| {
+				Box::pin(async move {
+					Ok(data
+						.prefixes
+						.strip(msg.guild_id, data.identity.id(), &msg.content))
+				})
+			}),
+			// handled by the prefix cache so guilds can turn it off
+			mention_as_prefix: false,
 			edit_tracker: Some(Arc::new(poise::EditTracker::for_timespan(
 				Duration::from_secs(3600),
 			))),
@@ -89,36 +346,85 @@ async fn main() {
 			})
 		},
 		skip_checks_for_owners: false,
-		event_handler: |_ctx, event, _framework, _data| {
+		event_handler: |ctx, event, _framework, data| {
 			Box::pin(async move {
 				println!("[EVENT HANDLER] {:?}", event.snake_case_name());
+				if let serenity::FullEvent::GuildCreate {
+					guild,
+					is_new: Some(true),
+				} = event
+				{
+					background::welcome::send_setup_prompt(ctx, guild).await;
+				}
+				background::mute_enforcement::on_event(ctx, event, data).await;
 				Ok(())
 			})
 		},
 		..Default::default()
 	};
 
+	let handler = Handler {
+		messages:  Arc::new(MessageCache::from_config()),
+		members:   Arc::new(MemberCache::default()),
+		structure: Arc::new(StructureCache::default()),
+		voice:     Arc::new(VoiceCache::default()),
+		actions:   mod_actions.clone(),
+		queue:     log_queue.clone(),
+		prefixes:  prefixes.clone(),
+		identity:  identity.clone(),
+	};
+	let automod = Automod {
+		rules:    Default::default(),
+		links:    Default::default(),
+		spam:     Default::default(),
+		raids:    Default::default(),
+		queue:    log_queue.clone(),
+		actions:  mod_actions.clone(),
+		identity: identity.clone(),
+	};
+
 	let framework = poise::Framework::builder()
 		.setup(move |ctx, _ready, framework| {
 			Box::pin(async move {
 				println!("Logged in as {}", _ready.user.name);
-				poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+				serenity::Command::set_global_commands(ctx, slash_commands).await?;
+				tokio::spawn(background::heartbeat::run(
+					framework.shard_manager().clone(),
+				));
+				tokio::spawn(background::guild_logs::log_queue::run(
+					ctx.http.clone(),
+					log_queue.clone(),
+				));
+				tokio::spawn(background::guild_logs::attachment_store::run());
+				tokio::spawn(background::lock_expiry::run(ctx.http.clone()));
+				tokio::spawn(background::mute_expiry::run(ctx.http.clone()));
+				tokio::spawn(background::slowmode_schedules::run(
+					ctx.http.clone(),
+					log_queue.clone(),
+				));
+				tokio::spawn(background::api_changes::run(prefixes.clone()));
 				Ok(Data {
-					tag_db: Arc::new(TagDb::new()),
+					tag_db,
+					prefixes,
+					identity,
+					mod_actions,
+					log_queue,
 				})
 			})
 		})
 		.options(options)
 		.build();
 
-	let token = var("BOT_TOKEN")
-		.expect_error("Missing `BOT_TOKEN` env var, please include this in your .env file");
+	let token = config().bot_token.clone();
 
-	let intents =
-		serenity::GatewayIntents::non_privileged() | serenity::GatewayIntents::MESSAGE_CONTENT;
+	// members is needed for join, leave and member update logs
+	let intents = serenity::GatewayIntents::non_privileged()
+		| serenity::GatewayIntents::MESSAGE_CONTENT
+		| serenity::GatewayIntents::GUILD_MEMBERS;
 
 	let client = serenity::ClientBuilder::new(token, intents)
-		.raw_event_handler(Handler)
+		.raw_event_handler(handler)
+		.raw_event_handler(automod)
 		.framework(framework)
 		.await;
 
@@ -0,0 +1,392 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId, RoleId, VerificationLevel};
+
+use crate::config::AUTOMOD_DB_PATH;
+use crate::utils::db;
+use crate::utils::errors::BotError;
+
+const SETTINGS_KEY: &str = "SETTINGS";
+const RAID_KEY: &str = "RAID";
+
+/// bumped on every save, so anything caching the settings knows to reload them
+static VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// What automod does to a message that breaks a rule, every action is also logged
+#[derive(poise::ChoiceParameter, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AutomodAction {
+	#[name = "delete"]
+	Delete,
+	/// deletes the message too
+	#[name = "warn"]
+	Warn,
+	/// deletes the message too, times the member out for the guild's mute duration
+	#[name = "mute"]
+	Mute,
+	/// deletes the message too
+	#[name = "kick"]
+	Kick,
+	#[name = "log-only"]
+	Log,
+}
+
+impl AutomodAction {
+	pub fn label(self) -> &'static str {
+		match self {
+			| AutomodAction::Delete => "Delete",
+			| AutomodAction::Warn => "Delete and warn",
+			| AutomodAction::Mute => "Delete and mute",
+			| AutomodAction::Kick => "Delete and kick",
+			| AutomodAction::Log => "Log only",
+		}
+	}
+
+	pub fn deletes(self) -> bool {
+		self != AutomodAction::Log
+	}
+
+	/// when a message breaks several rules only the harshest action is taken
+	pub fn severity(self) -> u8 {
+		match self {
+			| AutomodAction::Log => 0,
+			| AutomodAction::Delete => 1,
+			| AutomodAction::Warn => 2,
+			| AutomodAction::Mute => 3,
+			| AutomodAction::Kick => 4,
+		}
+	}
+}
+
+#[derive(poise::ChoiceParameter, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilterKind {
+	/// whole words, `*` matches any letters. spacing, punctuation and leetspeak are ignored
+	#[name = "word"]
+	Word,
+	/// matched case insensitively against the message as written
+	#[name = "regex"]
+	Regex,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct FilterRule {
+	pub kind:    FilterKind,
+	pub pattern: String,
+	pub action:  AutomodAction,
+}
+
+/// Limits on how fast and how much a member can post. Breaking one is a strike, members are
+/// muted and then kicked after enough strikes
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SpamSettings {
+	pub enabled:        bool,
+	/// more messages than this within `per_seconds` is spam
+	pub max_messages:   u32,
+	pub per_seconds:    u64,
+	/// the same message sent more times than this within a minute
+	pub max_duplicates: u32,
+	/// per message, 0 turns the check off
+	pub max_mentions:   u32,
+	pub max_emojis:     u32,
+	pub max_newlines:   u32,
+	/// strikes within `strike_minutes` before a member is muted or kicked, 0 never does
+	pub mute_after:     u32,
+	pub kick_after:     u32,
+	pub strike_minutes: u64,
+}
+
+impl Default for SpamSettings {
+	fn default() -> Self {
+		SpamSettings {
+			enabled:        false,
+			max_messages:   5,
+			per_seconds:    5,
+			max_duplicates: 3,
+			max_mentions:   5,
+			max_emojis:     10,
+			max_newlines:   15,
+			mute_after:     2,
+			kick_after:     3,
+			strike_minutes: 60,
+		}
+	}
+}
+
+impl SpamSettings {
+	/// what to do about a member's `strikes`th strike
+	pub fn action(
+		&self,
+		strikes: usize,
+	) -> AutomodAction {
+		let reached = |limit: u32| limit != 0 && strikes >= limit as usize;
+		if reached(self.kick_after) {
+			AutomodAction::Kick
+		} else if reached(self.mute_after) {
+			AutomodAction::Mute
+		} else {
+			AutomodAction::Delete
+		}
+	}
+}
+
+/// Kinds of links automod can act on
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LinkRule {
+	/// invites to other servers, ones to this server are always fine
+	#[name = "invites"]
+	Invites,
+	/// domains on the bot's phishing list
+	#[name = "phishing"]
+	Phishing,
+	/// domains on the guild's deny list
+	#[name = "denied"]
+	Denied,
+	/// any domain not on the guild's allow list
+	#[name = "unlisted"]
+	Unlisted,
+}
+
+impl LinkRule {
+	pub const ALL: [LinkRule; 4] = [
+		LinkRule::Invites,
+		LinkRule::Phishing,
+		LinkRule::Denied,
+		LinkRule::Unlisted,
+	];
+
+	pub fn label(self) -> &'static str {
+		match self {
+			| LinkRule::Invites => "Invites to other servers",
+			| LinkRule::Phishing => "Phishing links",
+			| LinkRule::Denied => "Denied domains",
+			| LinkRule::Unlisted => "Domains not on the allow list",
+		}
+	}
+}
+
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DomainList {
+	#[name = "allow"]
+	Allow,
+	#[name = "deny"]
+	Deny,
+}
+
+/// What automod does about links, `None` leaves that kind of link alone
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LinkSettings {
+	pub invites:         Option<AutomodAction>,
+	pub phishing:        Option<AutomodAction>,
+	pub denied:          Option<AutomodAction>,
+	pub unlisted:        Option<AutomodAction>,
+	/// subdomains are included, so `example.com` also covers `cdn.example.com`
+	pub allowed_domains: Vec<String>,
+	pub denied_domains:  Vec<String>,
+}
+
+impl LinkSettings {
+	pub fn action(
+		&self,
+		rule: LinkRule,
+	) -> Option<AutomodAction> {
+		match rule {
+			| LinkRule::Invites => self.invites,
+			| LinkRule::Phishing => self.phishing,
+			| LinkRule::Denied => self.denied,
+			| LinkRule::Unlisted => self.unlisted,
+		}
+	}
+
+	pub fn set_action(
+		&mut self,
+		rule: LinkRule,
+		action: Option<AutomodAction>,
+	) {
+		match rule {
+			| LinkRule::Invites => self.invites = action,
+			| LinkRule::Phishing => self.phishing = action,
+			| LinkRule::Denied => self.denied = action,
+			| LinkRule::Unlisted => self.unlisted = action,
+		}
+	}
+
+	pub fn domains(
+		&mut self,
+		list: DomainList,
+	) -> &mut Vec<String> {
+		match list {
+			| DomainList::Allow => &mut self.allowed_domains,
+			| DomainList::Deny => &mut self.denied_domains,
+		}
+	}
+}
+
+/// true when `host` is `domain` or one of its subdomains
+pub fn domain_matches(
+	host: &str,
+	domain: &str,
+) -> bool {
+	host == domain
+		|| host
+			.strip_suffix(domain)
+			.is_some_and(|rest| rest.ends_with('.'))
+}
+
+/// What automod does when it spots a raid, moderators are alerted either way
+#[derive(poise::ChoiceParameter, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RaidResponse {
+	#[name = "alert-only"]
+	Alert,
+	/// raises the verification level so new members have to wait 10 minutes before talking
+	#[name = "verification"]
+	Verification,
+	/// stops @everyone from talking in every channel
+	#[name = "lock-channels"]
+	Lockdown,
+}
+
+impl RaidResponse {
+	pub fn label(self) -> &'static str {
+		match self {
+			| RaidResponse::Alert => "Alert moderators",
+			| RaidResponse::Verification => "Raise the verification level",
+			| RaidResponse::Lockdown => "Lock every channel",
+		}
+	}
+}
+
+/// When a burst of joins counts as a raid
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RaidSettings {
+	pub enabled:          bool,
+	/// more joins than this within `join_seconds` is a raid
+	pub max_joins:        u32,
+	pub join_seconds:     u64,
+	/// new accounts without an avatar, counted within the same time. 0 turns the check off
+	pub max_suspicious:   u32,
+	/// accounts younger than this count as new
+	pub new_account_days: u64,
+	pub response:         RaidResponse,
+}
+
+impl Default for RaidSettings {
+	fn default() -> Self {
+		RaidSettings {
+			enabled:          false,
+			max_joins:        10,
+			join_seconds:     30,
+			max_suspicious:   5,
+			new_account_days: 7,
+			response:         RaidResponse::Verification,
+		}
+	}
+}
+
+/// What an ongoing raid response changed, so `/raid end` can put it back
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RaidState {
+	/// unix timestamp
+	pub started_at:            i64,
+	pub trigger:               String,
+	/// `None` when the level wasn't changed
+	pub previous_verification: Option<VerificationLevel>,
+	pub locked_channels:       Vec<ChannelId>,
+}
+
+/// A guild's automod configuration, stored as json so the api can write the same format
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AutomodSettings {
+	pub filters:         Vec<FilterRule>,
+	pub exempt_channels: Vec<ChannelId>,
+	/// on top of the guild's moderator roles, which are always exempt
+	pub exempt_roles:    Vec<RoleId>,
+	/// how long the mute action times members out for, in minutes
+	pub mute_minutes:    u64,
+	pub spam:            SpamSettings,
+	pub links:           LinkSettings,
+	pub raid:            RaidSettings,
+}
+
+impl Default for AutomodSettings {
+	fn default() -> Self {
+		AutomodSettings {
+			filters:         vec![],
+			exempt_channels: vec![],
+			exempt_roles:    vec![],
+			mute_minutes:    10,
+			spam:            SpamSettings::default(),
+			links:           LinkSettings::default(),
+			raid:            RaidSettings::default(),
+		}
+	}
+}
+
+impl AutomodSettings {
+	pub fn mute_duration(&self) -> Duration {
+		Duration::from_secs(self.mute_minutes * 60)
+	}
+}
+
+pub fn version() -> u64 {
+	VERSION.load(Ordering::Acquire)
+}
+
+/// `None` when the guild never set up automod
+pub fn stored_settings(guild_id: GuildId) -> Result<Option<AutomodSettings>, BotError> {
+	let db = db::open(AUTOMOD_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match tree.get(SETTINGS_KEY)? {
+		| Some(ivec) => Ok(Some(serde_json::from_slice(&ivec)?)),
+		| None => Ok(None),
+	}
+}
+
+pub fn settings(guild_id: GuildId) -> Result<AutomodSettings, BotError> {
+	Ok(stored_settings(guild_id)?.unwrap_or_default())
+}
+
+pub fn set_settings(
+	guild_id: GuildId,
+	settings: &AutomodSettings,
+) -> Result<(), BotError> {
+	let db = db::open(AUTOMOD_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	tree.insert(SETTINGS_KEY, serde_json::to_vec(settings)?)?;
+	tree.flush()?;
+	VERSION.fetch_add(1, Ordering::Release);
+	Ok(())
+}
+
+/// The guild's ongoing raid response, if there is one
+pub fn raid_state(guild_id: GuildId) -> Result<Option<RaidState>, BotError> {
+	let db = db::open(AUTOMOD_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match tree.get(RAID_KEY)? {
+		| Some(ivec) => Ok(Some(serde_json::from_slice(&ivec)?)),
+		| None => Ok(None),
+	}
+}
+
+/// `None` once the raid is over
+pub fn set_raid_state(
+	guild_id: GuildId,
+	state: Option<&RaidState>,
+) -> Result<(), BotError> {
+	let db = db::open(AUTOMOD_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match state {
+		| Some(state) => {
+			tree.insert(RAID_KEY, serde_json::to_vec(state)?)?;
+		},
+		| None => {
+			tree.remove(RAID_KEY)?;
+		},
+	}
+	tree.flush()?;
+	Ok(())
+}
@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use serenity::all::{
+	ChannelId,
+	ChannelType,
+	CreateMessage,
+	GuildChannel,
+	GuildId,
+	Http,
+	Message,
+	PermissionOverwrite,
+	PermissionOverwriteType,
+	Permissions,
+	RoleId,
+	Timestamp,
+};
+
+use crate::config::CHANNEL_LOCKS_DB_PATH;
+use crate::utils::db;
+use crate::utils::errors::BotError;
+
+/// what a lock takes away from @everyone
+pub const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES
+	.union(Permissions::SEND_MESSAGES_IN_THREADS)
+	.union(Permissions::CREATE_PUBLIC_THREADS)
+	.union(Permissions::CREATE_PRIVATE_THREADS);
+
+/// An @everyone overwrite as bits, so it can be put back exactly
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Overwrite {
+	pub allow: u64,
+	pub deny:  u64,
+}
+
+/// A locked channel and what its @everyone overwrite was before
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChannelLock {
+	/// `None` when the channel had no @everyone overwrite
+	pub previous: Option<Overwrite>,
+	/// unix timestamp the lock is lifted at, `None` keeps it until someone unlocks it
+	#[serde(default)]
+	pub until:    Option<i64>,
+	/// locked as part of a server-wide lockdown, which `/lockdown end` lifts
+	#[serde(default)]
+	pub lockdown: bool,
+}
+
+/// channels people talk in, categories and announcement threads are left alone
+pub fn is_lockable(channel: &GuildChannel) -> bool {
+	matches!(
+		channel.kind,
+		ChannelType::Text
+			| ChannelType::News
+			| ChannelType::Forum
+			| ChannelType::Voice
+			| ChannelType::Stage
+	)
+}
+
+fn everyone(guild_id: GuildId) -> PermissionOverwriteType {
+	PermissionOverwriteType::Role(RoleId::new(guild_id.get()))
+}
+
+pub fn lock_of(
+	guild_id: GuildId,
+	channel_id: ChannelId,
+) -> Result<Option<ChannelLock>, BotError> {
+	let db = db::open(CHANNEL_LOCKS_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match tree.get(channel_id.get().to_be_bytes())? {
+		| Some(ivec) => Ok(Some(serde_json::from_slice(&ivec)?)),
+		| None => Ok(None),
+	}
+}
+
+/// Every locked channel in the guild
+pub fn locks(guild_id: GuildId) -> Result<Vec<(ChannelId, ChannelLock)>, BotError> {
+	let db = db::open(CHANNEL_LOCKS_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	Ok(tree
+		.iter()
+		.flatten()
+		.filter_map(|(key, value)| {
+			let id = ChannelId::new(u64::from_be_bytes((*key).try_into().ok()?));
+			Some((id, serde_json::from_slice(&value).ok()?))
+		})
+		.collect())
+}
+
+/// Timed locks in every guild that should have been lifted by now
+pub fn expired() -> Result<Vec<(GuildId, ChannelId)>, BotError> {
+	let db = db::open(CHANNEL_LOCKS_DB_PATH)?;
+	let now = Timestamp::now().unix_timestamp();
+
+	Ok(db
+		.tree_names()
+		.iter()
+		.filter_map(|name| {
+			std::str::from_utf8(name)
+				.ok()?
+				.parse()
+				.ok()
+				.map(GuildId::new)
+		})
+		.flat_map(|guild_id| {
+			let tree = db.open_tree(guild_id.to_string()).ok();
+			tree.into_iter()
+				.flat_map(|tree| tree.iter().flatten())
+				.filter_map(move |(key, value)| {
+					let lock: ChannelLock = serde_json::from_slice(&value).ok()?;
+					let id = ChannelId::new(u64::from_be_bytes((*key).try_into().ok()?));
+					lock.until
+						.is_some_and(|until| until <= now)
+						.then_some((guild_id, id))
+				})
+		})
+		.collect())
+}
+
+fn store(
+	guild_id: GuildId,
+	channel_id: ChannelId,
+	lock: Option<&ChannelLock>,
+) -> Result<(), BotError> {
+	let db = db::open(CHANNEL_LOCKS_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match lock {
+		| Some(lock) => {
+			tree.insert(channel_id.get().to_be_bytes(), serde_json::to_vec(lock)?)?;
+		},
+		| None => {
+			tree.remove(channel_id.get().to_be_bytes())?;
+		},
+	}
+	tree.flush()?;
+	Ok(())
+}
+
+/// Denies [`LOCKED_PERMISSIONS`] to @everyone, remembering the overwrite it replaced. Returns
+/// false if the channel was already locked.
+/// `notice` is posted before the channel is locked, since the bot can't talk in it afterwards
+/// unless it's an admin
+pub async fn lock(
+	http: &Http,
+	channel: &GuildChannel,
+	until: Option<i64>,
+	lockdown: bool,
+	notice: Option<String>,
+) -> Result<bool, BotError> {
+	let guild_id = channel.guild_id;
+	if lock_of(guild_id, channel.id)?.is_some() {
+		return Ok(false);
+	}
+
+	let current = channel
+		.permission_overwrites
+		.iter()
+		.find(|o| o.kind == everyone(guild_id));
+	let lock = ChannelLock {
+		previous: current.map(|o| Overwrite {
+			allow: o.allow.bits(),
+			deny:  o.deny.bits(),
+		}),
+		until,
+		lockdown,
+	};
+
+	// stored first so a restart halfway can still unlock it
+	store(guild_id, channel.id, Some(&lock))?;
+
+	let posted = match notice {
+		| Some(notice) => post(http, channel, notice).await,
+		| None => None,
+	};
+
+	let overwrite = PermissionOverwrite {
+		allow: current.map(|o| o.allow).unwrap_or_default() - LOCKED_PERMISSIONS,
+		deny:  current.map(|o| o.deny).unwrap_or_default() | LOCKED_PERMISSIONS,
+		kind:  everyone(guild_id),
+	};
+	if let Err(e) = channel.id.create_permission(http, overwrite).await {
+		// the notice would claim a lock that didn't happen
+		if let Some(posted) = posted {
+			let _ = posted.delete(http).await;
+		}
+		store(guild_id, channel.id, None)?;
+		return Err(BotError::from(e).with_permission_denied(format!(
+			"I can't lock <#{}>, make sure I have Manage Permissions there.",
+			channel.id
+		)));
+	}
+
+	Ok(true)
+}
+
+/// Puts back the @everyone overwrite from before the lock. Returns false if the channel wasn't
+/// locked
+pub async fn unlock(
+	http: &Http,
+	guild_id: GuildId,
+	channel_id: ChannelId,
+) -> Result<bool, BotError> {
+	let Some(lock) = lock_of(guild_id, channel_id)? else {
+		return Ok(false);
+	};
+
+	let restored = match lock.previous {
+		| Some(previous) => {
+			channel_id
+				.create_permission(http, PermissionOverwrite {
+					allow: Permissions::from_bits_truncate(previous.allow),
+					deny:  Permissions::from_bits_truncate(previous.deny),
+					kind:  everyone(guild_id),
+				})
+				.await
+		},
+		| None => channel_id.delete_permission(http, everyone(guild_id)).await,
+	};
+
+	if let Err(e) = restored {
+		match BotError::from(e) {
+			// the channel is gone, nothing left to unlock
+			| BotError::NotFound(_) => {},
+			| e => {
+				return Err(e.with_permission_denied(format!(
+					"I can't unlock <#{}>, make sure I have Manage Permissions there.",
+					channel_id
+				)));
+			},
+		}
+	}
+
+	store(guild_id, channel_id, None)?;
+	Ok(true)
+}
+
+/// The message a lock or unlock posts in the channel
+pub fn notice(
+	locked: bool,
+	until: Option<i64>,
+	reason: Option<&str>,
+) -> String {
+	let mut notice = match (locked, until) {
+		| (true, Some(until)) => format!("🔒 This channel is locked until <t:{}:f>.", until),
+		| (true, None) => "🔒 This channel is locked.".to_string(),
+		| (false, _) => "🔓 This channel is unlocked.".to_string(),
+	};
+	if let Some(reason) = reason {
+		notice += &format!("\n**Reason:** {}", reason);
+	}
+	notice
+}
+
+/// Posts the unlock notice, channels without a text chat are skipped
+pub async fn notify(
+	http: &Http,
+	channel: &GuildChannel,
+	reason: Option<&str>,
+) {
+	post(http, channel, notice(false, None, reason)).await;
+}
+
+async fn post(
+	http: &Http,
+	channel: &GuildChannel,
+	notice: String,
+) -> Option<Message> {
+	if channel.kind == ChannelType::Forum {
+		return None;
+	}
+
+	match channel
+		.id
+		.send_message(http, CreateMessage::new().content(notice))
+		.await
+	{
+		| Ok(message) => Some(message),
+		| Err(e) => {
+			println!(
+				"\x1b[33;1m[WARN]\x1b[0m Couldn't post the lock notice in {}: {}",
+				channel.id, e
+			);
+			None
+		},
+	}
+}
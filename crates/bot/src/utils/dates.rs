@@ -6,3 +6,64 @@ pub fn format_timestamp_ddmmyyyy(ts: &str) -> String {
 		.map(|date: NaiveDate| date.format("%d/%m/%Y").to_string())
 		.unwrap_or_else(|_| "Invalid date".into())
 }
+
+/// "3 days, 4 hours" style, only the two biggest units since more isn't useful in logs
+pub fn format_duration(secs: u64) -> String {
+	const UNITS: [(u64, &str); 5] = [
+		(365 * 86400, "year"),
+		(86400, "day"),
+		(3600, "hour"),
+		(60, "minute"),
+		(1, "second"),
+	];
+
+	let mut rest = secs;
+	let parts: Vec<String> = UNITS
+		.iter()
+		.filter_map(|(size, name)| {
+			let amount = rest / size;
+			rest %= size;
+			match amount {
+				| 0 => None,
+				| 1 => Some(format!("1 {}", name)),
+				| n => Some(format!("{} {}s", n, name)),
+			}
+		})
+		.take(2)
+		.collect();
+
+	match parts.is_empty() {
+		| true => "0 seconds".to_string(),
+		| false => parts.join(", "),
+	}
+}
+
+/// Reads durations like `30m`, `2h`, `1d12h` or `1w` as seconds. A bare number is minutes
+pub fn parse_duration(input: &str) -> Option<u64> {
+	let input = input.trim().to_lowercase();
+	if let Ok(minutes) = input.parse::<u64>() {
+		return minutes.checked_mul(60);
+	}
+
+	let mut total: u64 = 0;
+	let mut number = String::new();
+	for c in input.chars() {
+		if c.is_ascii_digit() {
+			number.push(c);
+			continue;
+		}
+		let size = match c {
+			| 's' => 1,
+			| 'm' => 60,
+			| 'h' => 3600,
+			| 'd' => 86400,
+			| 'w' => 7 * 86400,
+			| _ => return None,
+		};
+		let amount: u64 = number.parse().ok()?;
+		total = total.checked_add(amount.checked_mul(size)?)?;
+		number.clear();
+	}
+
+	(number.is_empty() && total > 0).then_some(total)
+}
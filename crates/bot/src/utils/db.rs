@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use sled::Db;
+
+use crate::utils::errors::BotError;
+
+static DBS: OnceLock<Mutex<HashMap<&'static str, Db>>> = OnceLock::new();
+
+/// The store at `path`, opened the first time it's needed and shared after that.
+/// sled locks a store for as long as a handle is open, so opening one per call fails whenever
+/// two calls overlap
+pub fn open(path: &'static str) -> Result<Db, BotError> {
+	let mut dbs = DBS
+		.get_or_init(Default::default)
+		.lock()
+		.map_err(|_| BotError::Storage(format!("the handle for `{}` was poisoned", path)))?;
+
+	if let Some(db) = dbs.get(path) {
+		return Ok(db.clone());
+	}
+
+	let db = sled::open(path)?;
+	dbs.insert(path, db.clone());
+	Ok(db)
+}
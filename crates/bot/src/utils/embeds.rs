@@ -1,25 +1,23 @@
 use serenity::all::{Color, CreateEmbed};
 
-use crate::types::Error;
-use crate::utils::tag_utils::TagError;
+use crate::utils::errors::BotError;
 
 pub trait ToEmbed {
 	fn to_embed(self) -> CreateEmbed;
 }
 
-impl ToEmbed for Error {
+impl ToEmbed for BotError {
 	fn to_embed(self) -> CreateEmbed {
-		CreateEmbed::default()
-			.title("Error")
-			.description(self.to_string())
-			.color(Color::RED)
-	}
-}
+		let title = match self {
+			| BotError::PermissionDenied(_) => "Missing Permissions",
+			| BotError::NotFound(_) => "Not Found",
+			| BotError::InvalidInput(_) => "Invalid Input",
+			| BotError::Hierarchy(_) => "Role Hierarchy",
+			| BotError::Discord(_) | BotError::Storage(_) => "Error",
+		};
 
-impl ToEmbed for TagError {
-	fn to_embed(self) -> CreateEmbed {
 		CreateEmbed::default()
-			.title("Error")
+			.title(title)
 			.description(self.to_string())
 			.color(Color::RED)
 	}
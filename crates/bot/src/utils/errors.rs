@@ -0,0 +1,131 @@
+use std::fmt;
+
+use serenity::all::{HttpError, ModelError, StatusCode};
+
+/// Error type returned by every command, shown to the user through [`ToEmbed`]
+///
+/// [`ToEmbed`]: crate::utils::embeds::ToEmbed
+#[derive(Debug)]
+pub enum BotError {
+	/// the user or the bot isn't allowed to do this
+	PermissionDenied(String),
+	NotFound(String),
+	InvalidInput(String),
+	/// a discord request failed for a reason the user can't fix
+	Discord(String),
+	Storage(String),
+	/// the target's highest role is above the moderator's or the bot's
+	Hierarchy(String),
+}
+
+impl BotError {
+	pub fn guild_only() -> Self {
+		BotError::InvalidInput("This command can only be used in servers.".into())
+	}
+
+	/// errors the user can't do anything about, these get an incident id instead of being shown
+	pub fn is_internal(&self) -> bool {
+		matches!(self, BotError::Discord(_) | BotError::Storage(_))
+	}
+
+	/// swaps the generic not found message for one that says what wasn't found
+	pub fn with_not_found(
+		self,
+		msg: impl Into<String>,
+	) -> Self {
+		match self {
+			| BotError::NotFound(_) => BotError::NotFound(msg.into()),
+			| e => e,
+		}
+	}
+
+	/// swaps the generic permission message for one that says what permission is missing
+	pub fn with_permission_denied(
+		self,
+		msg: impl Into<String>,
+	) -> Self {
+		match self {
+			| BotError::PermissionDenied(_) => BotError::PermissionDenied(msg.into()),
+			| e => e,
+		}
+	}
+}
+
+impl std::error::Error for BotError {}
+
+impl fmt::Display for BotError {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		match self {
+			| BotError::PermissionDenied(e) => write!(f, "❌ {}", e),
+			| BotError::NotFound(e) => write!(f, "❌ {}", e),
+			| BotError::InvalidInput(e) => write!(f, "❌ {}", e),
+			| BotError::Discord(e) => write!(f, "❌ Discord request failed: {}", e),
+			| BotError::Storage(e) => write!(f, "❌ Storage error: {}", e),
+			| BotError::Hierarchy(e) => write!(f, "❌ {}", e),
+		}
+	}
+}
+
+impl From<serenity::Error> for BotError {
+	fn from(err: serenity::Error) -> BotError {
+		match &err {
+			| serenity::Error::Http(HttpError::UnsuccessfulRequest(res)) => match res.status_code {
+				| StatusCode::FORBIDDEN => BotError::PermissionDenied(
+					"I don't have permission to do that, check my permissions and that my \
+						 highest role is above the target's."
+						.into(),
+				),
+				| StatusCode::NOT_FOUND => BotError::NotFound(
+					"Discord couldn't find that, it may have been deleted.".into(),
+				),
+				| _ => BotError::Discord(err.to_string()),
+			},
+			| serenity::Error::Model(ModelError::InvalidPermissions {
+				required, ..
+			}) => BotError::PermissionDenied(format!(
+				"I'm missing these permissions to do that: {}",
+				required
+			)),
+			| serenity::Error::Model(ModelError::Hierarchy) => BotError::Hierarchy(
+				"I can't do that because the target's highest role is above mine.".into(),
+			),
+			| serenity::Error::Model(ModelError::MemberNotFound) => {
+				BotError::NotFound("That user isn't in this server.".into())
+			},
+			| _ => BotError::Discord(err.to_string()),
+		}
+	}
+}
+
+impl From<sled::Error> for BotError {
+	fn from(err: sled::Error) -> BotError {
+		BotError::Storage(err.to_string())
+	}
+}
+
+impl From<rusqlite::Error> for BotError {
+	fn from(err: rusqlite::Error) -> BotError {
+		BotError::Storage(err.to_string())
+	}
+}
+
+impl From<serde_json::Error> for BotError {
+	fn from(err: serde_json::Error) -> BotError {
+		BotError::Storage(err.to_string())
+	}
+}
+
+impl From<std::str::Utf8Error> for BotError {
+	fn from(err: std::str::Utf8Error) -> BotError {
+		BotError::Storage(err.to_string())
+	}
+}
+
+impl From<std::io::Error> for BotError {
+	fn from(err: std::io::Error) -> BotError {
+		BotError::Storage(err.to_string())
+	}
+}
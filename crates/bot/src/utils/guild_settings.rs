@@ -0,0 +1,634 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::FixedOffset;
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId, RoleId, UserId, WebhookId};
+use shared::changes::LOG_TYPES;
+
+use crate::config::{
+	CONFIG_DB_PATH,
+	DEFAULT_PREFIX,
+	LOG_CHANNELS_DB_PATH,
+	LOG_IGNORES_DB_PATH,
+	LOG_WEBHOOKS_DB_PATH,
+	config,
+};
+use crate::utils::db;
+use crate::utils::errors::BotError;
+
+/// bumped whenever a setting changes, so anything caching them knows to reload
+static VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// Per guild settings, keys are stored under the same names as the env vars they replace
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Setting {
+	#[name = "report-channel"]
+	ReportChannel,
+	#[name = "report-notification-role"]
+	ReportNotificationRole,
+	#[name = "muted-role"]
+	MutedRole,
+	/// mutes use discord timeouts when they're short enough, the muted role otherwise
+	#[name = "timeout-mutes"]
+	TimeoutMutes,
+	#[name = "moderator-roles"]
+	ModeratorRoles,
+	#[name = "prefix"]
+	Prefix,
+	#[name = "mention-prefix"]
+	MentionPrefix,
+	/// used for anything scheduled by time of day. It's a fixed offset from utc that doesn't
+	/// follow daylight saving time, schedules move by an hour when the clocks change unless it's
+	/// updated
+	#[name = "timezone"]
+	Timezone,
+}
+
+pub enum SettingValue {
+	Channel(ChannelId),
+	Role(RoleId),
+	/// stored as the ids back to back
+	Roles(Vec<RoleId>),
+	/// stored space separated since prefixes can't contain spaces
+	Prefixes(Vec<String>),
+	Toggle(bool),
+	/// minutes from utc
+	Offset(i32),
+}
+
+impl SettingValue {
+	/// how the value is shown in `/config view`
+	pub fn display(&self) -> String {
+		match self {
+			| SettingValue::Channel(id) => format!("<#{}>", id),
+			| SettingValue::Role(id) => format!("<@&{}>", id),
+			| SettingValue::Roles(ids) if ids.is_empty() => "None".to_string(),
+			| SettingValue::Roles(ids) => ids
+				.iter()
+				.map(|id| format!("<@&{}>", id))
+				.collect::<Vec<_>>()
+				.join(" "),
+			| SettingValue::Prefixes(prefixes) => prefixes
+				.iter()
+				.map(|p| format!("`{}`", p))
+				.collect::<Vec<_>>()
+				.join(" "),
+			| SettingValue::Toggle(true) => "On".to_string(),
+			| SettingValue::Toggle(false) => "Off".to_string(),
+			| SettingValue::Offset(0) => "UTC".to_string(),
+			| SettingValue::Offset(minutes) => format!(
+				"UTC{}{:02}:{:02}",
+				if *minutes < 0 { '-' } else { '+' },
+				minutes.abs() / 60,
+				minutes.abs() % 60
+			),
+		}
+	}
+}
+
+impl Setting {
+	pub const ALL: [Setting; 8] = [
+		Setting::ReportChannel,
+		Setting::ReportNotificationRole,
+		Setting::MutedRole,
+		Setting::TimeoutMutes,
+		Setting::ModeratorRoles,
+		Setting::Prefix,
+		Setting::MentionPrefix,
+		Setting::Timezone,
+	];
+
+	pub fn key(self) -> &'static str {
+		match self {
+			| Setting::ReportChannel => "REPORT_CHANNEL_ID",
+			| Setting::ReportNotificationRole => "REPORT_NOTIFICATION_ROLE",
+			| Setting::MutedRole => "MUTED_ROLE_ID",
+			| Setting::TimeoutMutes => "TIMEOUT_MUTES",
+			| Setting::ModeratorRoles => "MODERATOR_ROLE_IDS",
+			| Setting::Prefix => "PREFIX",
+			| Setting::MentionPrefix => "MENTION_PREFIX",
+			| Setting::Timezone => "UTC_OFFSET",
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			| Setting::ReportChannel => "Report channel",
+			| Setting::ReportNotificationRole => "Report notification role",
+			| Setting::MutedRole => "Muted role",
+			| Setting::TimeoutMutes => "Mute with timeouts",
+			| Setting::ModeratorRoles => "Moderator roles",
+			| Setting::Prefix => "Prefixes",
+			| Setting::MentionPrefix => "Mention as prefix",
+			| Setting::Timezone => "Timezone",
+		}
+	}
+
+	/// the env var value, used when a guild hasn't set this
+	pub fn default_value(self) -> Option<SettingValue> {
+		match self {
+			| Setting::ReportChannel => config().report_channel_id.map(SettingValue::Channel),
+			| Setting::ReportNotificationRole => {
+				config().report_notification_role.map(SettingValue::Role)
+			},
+			| Setting::MutedRole => config().muted_role_id.map(SettingValue::Role),
+			| Setting::TimeoutMutes => Some(SettingValue::Toggle(false)),
+			| Setting::ModeratorRoles => None,
+			| Setting::Prefix => Some(SettingValue::Prefixes(vec![config().prefix.clone()])),
+			| Setting::MentionPrefix => Some(SettingValue::Toggle(true)),
+			| Setting::Timezone => Some(SettingValue::Offset(0)),
+		}
+	}
+
+	/// parses user input, accepting mentions or raw ids for channels and roles
+	pub fn parse(
+		self,
+		input: &str,
+	) -> Result<SettingValue, BotError> {
+		let input = input.trim();
+
+		match self {
+			| Setting::ReportChannel => parse_id(input, "<#", ">")
+				.map(|id| SettingValue::Channel(ChannelId::new(id)))
+				.ok_or(BotError::InvalidInput(format!(
+					"`{}` isn't a channel, mention it like #channel or use its id.",
+					input
+				))),
+			| Setting::ReportNotificationRole | Setting::MutedRole => parse_id(input, "<@&", ">")
+				.map(|id| SettingValue::Role(RoleId::new(id)))
+				.ok_or(BotError::InvalidInput(format!(
+					"`{}` isn't a role, mention it like @role or use its id.",
+					input
+				))),
+			| Setting::ModeratorRoles => input
+				.split_whitespace()
+				.map(|role| {
+					parse_id(role, "<@&", ">")
+						.map(RoleId::new)
+						.ok_or(BotError::InvalidInput(format!(
+							"`{}` isn't a role, mention it like @role or use its id.",
+							role
+						)))
+				})
+				.collect::<Result<Vec<_>, _>>()
+				.map(SettingValue::Roles),
+			| Setting::Prefix => {
+				let prefixes: Vec<String> = input.split_whitespace().map(String::from).collect();
+				validate_prefixes(&prefixes)?;
+				Ok(SettingValue::Prefixes(prefixes))
+			},
+			| Setting::MentionPrefix | Setting::TimeoutMutes => match input.to_lowercase().as_str()
+			{
+				| "on" | "true" | "yes" | "enable" => Ok(SettingValue::Toggle(true)),
+				| "off" | "false" | "no" | "disable" => Ok(SettingValue::Toggle(false)),
+				| _ => Err(BotError::InvalidInput(format!(
+					"`{}` isn't on or off.",
+					input
+				))),
+			},
+			| Setting::Timezone => {
+				parse_offset(input)
+					.map(SettingValue::Offset)
+					.ok_or(BotError::InvalidInput(format!(
+						"`{}` isn't a utc offset, use something like `UTC+2` or `-05:30`.",
+						input
+					)))
+			},
+		}
+	}
+
+	fn decode(
+		self,
+		bytes: &[u8],
+	) -> Option<SettingValue> {
+		match self {
+			| Setting::ReportChannel => {
+				Some(SettingValue::Channel(ChannelId::new(decode_id(bytes)?)))
+			},
+			| Setting::ReportNotificationRole | Setting::MutedRole => {
+				Some(SettingValue::Role(RoleId::new(decode_id(bytes)?)))
+			},
+			| Setting::ModeratorRoles => Some(SettingValue::Roles(
+				bytes
+					.chunks_exact(8)
+					.filter_map(decode_id)
+					.map(RoleId::new)
+					.collect(),
+			)),
+			| Setting::Prefix => {
+				let prefixes: Vec<String> = str::from_utf8(bytes)
+					.ok()?
+					.split_whitespace()
+					.map(String::from)
+					.collect();
+				(!prefixes.is_empty()).then_some(SettingValue::Prefixes(prefixes))
+			},
+			| Setting::MentionPrefix | Setting::TimeoutMutes => {
+				Some(SettingValue::Toggle(*bytes.first()? != 0))
+			},
+			| Setting::Timezone => Some(SettingValue::Offset(i32::from_be_bytes(
+				bytes.try_into().ok()?,
+			))),
+		}
+	}
+}
+
+/// checked the same way by the api, see [`shared::prefixes::validate`]
+pub fn validate_prefixes(prefixes: &[String]) -> Result<(), BotError> {
+	shared::prefixes::validate(prefixes).map_err(BotError::InvalidInput)
+}
+
+/// `UTC+2`, `+02:00`, `-5:30` or just `utc`, as minutes from utc
+fn parse_offset(input: &str) -> Option<i32> {
+	let lower = input.to_lowercase();
+	let offset = lower
+		.strip_prefix("utc")
+		.or_else(|| lower.strip_prefix("gmt"))
+		.unwrap_or(&lower)
+		.trim();
+	if offset.is_empty() {
+		return Some(0);
+	}
+
+	let (sign, rest) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+		| (Some(rest), _) => (1, rest),
+		| (_, Some(rest)) => (-1, rest),
+		| _ => (1, offset),
+	};
+	let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+	let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+
+	(hours <= 14 && minutes < 60).then_some(sign * (hours * 60 + minutes))
+}
+
+fn parse_id(
+	input: &str,
+	mention_start: &str,
+	mention_end: &str,
+) -> Option<u64> {
+	let id = input
+		.strip_prefix(mention_start)
+		.and_then(|s| s.strip_suffix(mention_end))
+		.unwrap_or(input);
+	id.parse::<u64>().ok().filter(|id| *id != 0)
+}
+
+/// ids are stored as big endian u64s, same as the log channels
+fn decode_id(bytes: &[u8]) -> Option<u64> {
+	Some(u64::from_be_bytes(bytes.try_into().ok()?)).filter(|id| *id != 0)
+}
+
+fn encode(value: &SettingValue) -> Vec<u8> {
+	match value {
+		| SettingValue::Channel(id) => id.get().to_be_bytes().to_vec(),
+		| SettingValue::Role(id) => id.get().to_be_bytes().to_vec(),
+		| SettingValue::Roles(ids) => ids.iter().flat_map(|id| id.get().to_be_bytes()).collect(),
+		| SettingValue::Prefixes(prefixes) => prefixes.join(" ").into_bytes(),
+		| SettingValue::Toggle(on) => vec![*on as u8],
+		| SettingValue::Offset(minutes) => minutes.to_be_bytes().to_vec(),
+	}
+}
+
+pub fn version() -> u64 {
+	VERSION.load(Ordering::Acquire)
+}
+
+/// The value this guild set, ignoring the env var default
+pub fn get(
+	guild_id: GuildId,
+	setting: Setting,
+) -> Result<Option<SettingValue>, BotError> {
+	let db = db::open(CONFIG_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	Ok(tree
+		.get(setting.key().as_bytes())?
+		.and_then(|bytes| setting.decode(&bytes)))
+}
+
+/// The value this guild set, or the env var default if it hasn't set one
+pub fn get_or_default(
+	guild_id: Option<GuildId>,
+	setting: Setting,
+) -> Result<Option<SettingValue>, BotError> {
+	let set = match guild_id {
+		| Some(guild_id) => get(guild_id, setting)?,
+		| None => None,
+	};
+	Ok(set.or_else(|| setting.default_value()))
+}
+
+pub fn set(
+	guild_id: GuildId,
+	setting: Setting,
+	value: &SettingValue,
+) -> Result<(), BotError> {
+	let db = db::open(CONFIG_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	tree.insert(setting.key().as_bytes(), encode(value))?;
+	tree.flush()?;
+	VERSION.fetch_add(1, Ordering::Release);
+	Ok(())
+}
+
+pub fn reset(
+	guild_id: GuildId,
+	setting: Setting,
+) -> Result<(), BotError> {
+	let db = db::open(CONFIG_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	tree.remove(setting.key().as_bytes())?;
+	tree.flush()?;
+	VERSION.fetch_add(1, Ordering::Release);
+	Ok(())
+}
+
+pub fn report_channel(guild_id: Option<GuildId>) -> Result<Option<ChannelId>, BotError> {
+	match get_or_default(guild_id, Setting::ReportChannel)? {
+		| Some(SettingValue::Channel(id)) => Ok(Some(id)),
+		| _ => Ok(None),
+	}
+}
+
+pub fn report_notification_role(guild_id: Option<GuildId>) -> Result<Option<RoleId>, BotError> {
+	match get_or_default(guild_id, Setting::ReportNotificationRole)? {
+		| Some(SettingValue::Role(id)) => Ok(Some(id)),
+		| _ => Ok(None),
+	}
+}
+
+pub fn muted_role(guild_id: GuildId) -> Result<Option<RoleId>, BotError> {
+	match get_or_default(Some(guild_id), Setting::MutedRole)? {
+		| Some(SettingValue::Role(id)) => Ok(Some(id)),
+		| _ => Ok(None),
+	}
+}
+
+pub fn timeout_mutes(guild_id: GuildId) -> Result<bool, BotError> {
+	Ok(matches!(
+		get_or_default(Some(guild_id), Setting::TimeoutMutes)?,
+		Some(SettingValue::Toggle(true))
+	))
+}
+
+pub fn moderator_roles(guild_id: GuildId) -> Result<Vec<RoleId>, BotError> {
+	match get_or_default(Some(guild_id), Setting::ModeratorRoles)? {
+		| Some(SettingValue::Roles(ids)) => Ok(ids),
+		| _ => Ok(vec![]),
+	}
+}
+
+pub fn prefixes(guild_id: Option<GuildId>) -> Result<Vec<String>, BotError> {
+	match get_or_default(guild_id, Setting::Prefix)? {
+		| Some(SettingValue::Prefixes(prefixes)) => Ok(prefixes),
+		| _ => Ok(vec![DEFAULT_PREFIX.to_string()]),
+	}
+}
+
+pub fn mention_prefix(guild_id: Option<GuildId>) -> Result<bool, BotError> {
+	Ok(!matches!(
+		get_or_default(guild_id, Setting::MentionPrefix)?,
+		Some(SettingValue::Toggle(false))
+	))
+}
+
+/// The guild's timezone as an offset from utc
+pub fn utc_offset(guild_id: GuildId) -> Result<FixedOffset, BotError> {
+	let minutes = match get_or_default(Some(guild_id), Setting::Timezone)? {
+		| Some(SettingValue::Offset(minutes)) => minutes,
+		| _ => 0,
+	};
+	Ok(FixedOffset::east_opt(minutes * 60).unwrap_or(FixedOffset::east_opt(0).expect("utc")))
+}
+
+/// Every kind of log, each can be sent to its own channel. New ones go at the end, along with their
+/// key in [`LOG_TYPES`]
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogType {
+	#[name = "message-sent"]
+	MessageSent,
+	#[name = "message-edit"]
+	MessageEdit,
+	#[name = "message-delete"]
+	MessageDelete,
+	#[name = "message-bulk-delete"]
+	MessageBulkDelete,
+	#[name = "member-join"]
+	MemberJoin,
+	#[name = "member-leave"]
+	MemberLeave,
+	#[name = "nickname-change"]
+	NicknameChange,
+	#[name = "member-role-change"]
+	RoleChange,
+	#[name = "avatar-change"]
+	AvatarChange,
+	#[name = "channel-changes"]
+	ChannelChanges,
+	#[name = "role-changes"]
+	RoleChanges,
+	#[name = "emoji-changes"]
+	EmojiChanges,
+	#[name = "server-changes"]
+	ServerChanges,
+	#[name = "voice"]
+	Voice,
+	#[name = "bans"]
+	Ban,
+	#[name = "automod"]
+	Automod,
+}
+
+const _: () = assert!(LOG_TYPES.len() == LogType::ALL.len());
+
+impl LogType {
+	pub const ALL: [LogType; 16] = [
+		LogType::MessageSent,
+		LogType::MessageEdit,
+		LogType::MessageDelete,
+		LogType::MessageBulkDelete,
+		LogType::MemberJoin,
+		LogType::MemberLeave,
+		LogType::NicknameChange,
+		LogType::RoleChange,
+		LogType::AvatarChange,
+		LogType::ChannelChanges,
+		LogType::RoleChanges,
+		LogType::EmojiChanges,
+		LogType::ServerChanges,
+		LogType::Voice,
+		LogType::Ban,
+		LogType::Automod,
+	];
+
+	/// key in the log channels store, also what the api's `log_type` expects. the keys are listed
+	/// in shared so the api checks against the same ones, in the order of [`LogType::ALL`]
+	pub fn key(self) -> &'static str {
+		LOG_TYPES[self as usize]
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			| LogType::MessageSent => "Sent messages",
+			| LogType::MessageEdit => "Edited messages",
+			| LogType::MessageDelete => "Deleted messages",
+			| LogType::MessageBulkDelete => "Bulk deleted messages",
+			| LogType::MemberJoin => "Member joins",
+			| LogType::MemberLeave => "Member leaves",
+			| LogType::NicknameChange => "Nickname changes",
+			| LogType::RoleChange => "Member role changes",
+			| LogType::AvatarChange => "Avatar changes",
+			| LogType::ChannelChanges => "Channel changes",
+			| LogType::RoleChanges => "Role changes",
+			| LogType::EmojiChanges => "Emoji and sticker changes",
+			| LogType::ServerChanges => "Server setting changes",
+			| LogType::Voice => "Voice activity",
+			| LogType::Ban => "Bans, kicks and timeouts",
+			| LogType::Automod => "Automod actions",
+		}
+	}
+
+	pub fn from_key(key: &str) -> Option<LogType> {
+		LogType::ALL.into_iter().find(|t| t.key() == key)
+	}
+}
+
+/// Channel a log type is sent to, set through `/setup` or the api
+pub fn log_channel(
+	guild_id: GuildId,
+	log_type: LogType,
+) -> Result<Option<ChannelId>, BotError> {
+	let db = db::open(LOG_CHANNELS_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	Ok(tree
+		.get(log_type.key().as_bytes())?
+		.and_then(|ivec| decode_id(&ivec))
+		.map(ChannelId::new))
+}
+
+pub fn set_log_channels(
+	guild_id: GuildId,
+	channels: &[(LogType, ChannelId)],
+) -> Result<(), BotError> {
+	let db = db::open(LOG_CHANNELS_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	for (log_type, channel_id) in channels {
+		tree.insert(log_type.key().as_bytes(), &channel_id.get().to_be_bytes())?;
+	}
+	tree.flush()?;
+	Ok(())
+}
+
+/// stops sending these log types anywhere
+pub fn remove_log_channels(
+	guild_id: GuildId,
+	log_types: &[LogType],
+) -> Result<(), BotError> {
+	let db = db::open(LOG_CHANNELS_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	for log_type in log_types {
+		tree.remove(log_type.key().as_bytes())?;
+	}
+	tree.flush()?;
+	Ok(())
+}
+
+/// What a log type skips, events matching any rule aren't logged
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IgnoreRules {
+	pub channels:   Vec<ChannelId>,
+	/// every channel inside these
+	pub categories: Vec<ChannelId>,
+	pub users:      Vec<UserId>,
+	pub roles:      Vec<RoleId>,
+	pub bots:       bool,
+	/// messages starting with one of the guild's prefixes
+	pub commands:   bool,
+}
+
+impl Default for IgnoreRules {
+	fn default() -> Self {
+		IgnoreRules {
+			channels:   vec![],
+			categories: vec![],
+			users:      vec![],
+			roles:      vec![],
+			bots:       false,
+			commands:   true,
+		}
+	}
+}
+
+/// stored as json under the log type's key
+pub fn ignore_rules(
+	guild_id: GuildId,
+	log_type: LogType,
+) -> Result<IgnoreRules, BotError> {
+	let db = db::open(LOG_IGNORES_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match tree.get(log_type.key().as_bytes())? {
+		| Some(ivec) => Ok(serde_json::from_slice(&ivec)?),
+		| None => Ok(IgnoreRules::default()),
+	}
+}
+
+pub fn set_ignore_rules(
+	guild_id: GuildId,
+	log_type: LogType,
+	rules: &IgnoreRules,
+) -> Result<(), BotError> {
+	let db = db::open(LOG_IGNORES_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	tree.insert(log_type.key().as_bytes(), serde_json::to_vec(rules)?)?;
+	tree.flush()?;
+	Ok(())
+}
+
+/// A webhook logs in a channel are sent through instead of as the bot
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LogWebhook {
+	pub id:         WebhookId,
+	pub token:      String,
+	pub name:       Option<String>,
+	pub avatar_url: Option<String>,
+}
+
+/// channel ids are unique across guilds, so webhooks are keyed by channel alone
+pub fn log_webhook(channel_id: ChannelId) -> Result<Option<LogWebhook>, BotError> {
+	let db = db::open(LOG_WEBHOOKS_DB_PATH)?;
+	match db.get(channel_id.get().to_be_bytes())? {
+		| Some(ivec) => Ok(Some(serde_json::from_slice(&ivec)?)),
+		| None => Ok(None),
+	}
+}
+
+/// `None` goes back to sending logs in this channel as the bot
+pub fn set_log_webhook(
+	channel_id: ChannelId,
+	webhook: Option<&LogWebhook>,
+) -> Result<(), BotError> {
+	let db = db::open(LOG_WEBHOOKS_DB_PATH)?;
+	match webhook {
+		| Some(webhook) => {
+			db.insert(channel_id.get().to_be_bytes(), serde_json::to_vec(webhook)?)?;
+		},
+		| None => {
+			db.remove(channel_id.get().to_be_bytes())?;
+		},
+	}
+	db.flush()?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn log_type_keys_follow_the_variants() {
+		for (i, log_type) in LogType::ALL.into_iter().enumerate() {
+			assert_eq!(log_type as usize, i);
+			assert_eq!(LogType::from_key(log_type.key()), Some(log_type));
+		}
+	}
+}
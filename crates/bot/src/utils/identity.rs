@@ -0,0 +1,35 @@
+use std::sync::RwLock;
+
+use serenity::all::{CurrentUser, UserId};
+
+use crate::utils::errors::BotError;
+
+/// The bot's own user, filled in from every `Ready` so it never has to be configured
+#[derive(Default)]
+pub struct Identity {
+	user: RwLock<Option<CurrentUser>>,
+}
+
+impl Identity {
+	pub fn set(
+		&self,
+		user: &CurrentUser,
+	) {
+		if let Ok(mut current) = self.user.write() {
+			*current = Some(user.clone());
+		}
+	}
+
+	/// `None` only before discord sent `Ready`
+	pub fn id(&self) -> Option<UserId> {
+		self.user.read().ok()?.as_ref().map(|user| user.id)
+	}
+
+	/// for commands, which can't run before `Ready`
+	pub fn require_id(&self) -> Result<UserId, BotError> {
+		// not worth an incident, it only happens in the moments after starting
+		self.id().ok_or(BotError::NotFound(
+			"The bot hasn't finished connecting to discord yet, try again in a moment.".to_string(),
+		))
+	}
+}
@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serenity::all::{Color, CreateEmbed, CreateEmbedFooter, CreateMessage, Http};
+
+use crate::config::config;
+use crate::utils::embeds::ToEmbed;
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// An unexpected error that users get an id for so it can be found in the logs later
+pub struct Incident {
+	pub id:      String,
+	/// where it happened, e.g. "command `warn`"
+	pub source:  String,
+	/// full error details, only ever logged or sent to the owner's error channel
+	pub details: String,
+}
+
+impl Incident {
+	pub fn new(
+		source: impl Into<String>,
+		details: impl Into<String>,
+	) -> Self {
+		Incident {
+			id:      new_incident_id(),
+			source:  source.into(),
+			details: details.into(),
+		}
+	}
+
+	/// logs the full details and forwards them to `ERROR_CHANNEL_ID` if the owner set one
+	pub async fn report(
+		&self,
+		http: &Http,
+	) {
+		println!(
+			"\x1b[31;1m[ERROR] incident {} in {}:\x1b[0m {}",
+			self.id, self.source, self.details
+		);
+
+		let Some(channel_id) = config().error_channel_id else {
+			return;
+		};
+
+		// discord caps embed descriptions at 4096 chars
+		let details: String = self.details.chars().take(4000).collect();
+		let embed = CreateEmbed::new()
+			.title(format!("Incident `{}`", self.id))
+			.field("Source", &self.source, false)
+			.description(format!("```\n{}\n```", details))
+			.color(Color::RED)
+			.timestamp(serenity::model::Timestamp::now());
+
+		if let Err(e) = channel_id
+			.send_message(http, CreateMessage::new().embed(embed))
+			.await
+		{
+			println!(
+				"\x1b[31;1m[ERROR] Failed to forward incident {} to the error channel:\x1b[0m {}",
+				self.id, e
+			);
+		}
+	}
+}
+
+impl ToEmbed for &Incident {
+	fn to_embed(self) -> CreateEmbed {
+		CreateEmbed::default()
+			.title("Something went wrong")
+			.description(
+				"An unexpected error happened while running this. It has been logged, if it keeps \
+				 happening give the bot owner the incident id below.",
+			)
+			.footer(CreateEmbedFooter::new(format!("Incident ID: {}", self.id)))
+			.color(Color::RED)
+	}
+}
+
+/// short id that is unique enough to grep the logs for
+pub fn new_incident_id() -> String {
+	let millis = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or(0);
+	let count = COUNTER.fetch_add(1, Ordering::Relaxed) as u64;
+
+	// low byte is a counter so incidents in the same millisecond still differ
+	format!("{:010X}", ((millis << 8) | (count & 0xff)) & 0xff_ffff_ffff)
+}
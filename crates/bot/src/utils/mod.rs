@@ -1,5 +1,17 @@
+pub mod automod_settings;
+pub mod channel_locks;
 pub mod dates;
+pub mod db;
 pub mod dm_notifier_utils;
 pub mod embeds;
+pub mod errors;
+pub mod guild_settings;
+pub mod identity;
+pub mod incidents;
 pub mod mention;
+pub mod mod_actions;
+pub mod moderation_utils;
+pub mod mutes;
+pub mod prefix_cache;
+pub mod slowmode;
 pub mod tag_utils;
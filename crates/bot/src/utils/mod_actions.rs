@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serenity::all::{GuildId, UserId};
+
+/// how long a recorded action waits for its gateway event before it's dropped
+const TTL: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ModActionKind {
+	Ban,
+	Unban,
+	Kick,
+	Timeout,
+	RoleUpdate,
+}
+
+/// Who is behind a moderation action and why
+#[derive(Clone)]
+pub struct ModAction {
+	pub moderator: UserId,
+	pub reason:    Option<String>,
+	recorded_at:   Instant,
+}
+
+/// Moderation actions done through bot commands, the audit log only shows the bot for those so
+/// logs look here first to find the moderator that ran the command
+#[derive(Default)]
+pub struct ModActions {
+	actions: Mutex<HashMap<(GuildId, UserId, ModActionKind), ModAction>>,
+}
+
+impl ModActions {
+	/// call before doing the action, the gateway event can arrive before the http response
+	pub fn record(
+		&self,
+		guild_id: GuildId,
+		target: UserId,
+		kind: ModActionKind,
+		moderator: UserId,
+		reason: Option<&str>,
+	) {
+		let Ok(mut actions) = self.actions.lock() else {
+			return;
+		};
+		actions.retain(|_, action| action.recorded_at.elapsed() < TTL);
+		actions.insert((guild_id, target, kind), ModAction {
+			moderator,
+			reason: reason.map(str::to_string),
+			recorded_at: Instant::now(),
+		});
+	}
+
+	/// drops a recorded action that failed so it isn't matched to a later one
+	pub fn forget(
+		&self,
+		guild_id: GuildId,
+		target: UserId,
+		kind: ModActionKind,
+	) {
+		if let Ok(mut actions) = self.actions.lock() {
+			actions.remove(&(guild_id, target, kind));
+		}
+	}
+
+	pub fn take(
+		&self,
+		guild_id: GuildId,
+		target: UserId,
+		kind: ModActionKind,
+	) -> Option<ModAction> {
+		self.actions
+			.lock()
+			.ok()?
+			.remove(&(guild_id, target, kind))
+			.filter(|action| action.recorded_at.elapsed() < TTL)
+	}
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use serenity::all::{Member, Role, RoleId, User};
+
+use crate::utils::errors::BotError;
+use crate::{Context, Error};
+
+pub fn highest_position(
+	member: &Member,
+	roles: &HashMap<RoleId, Role>,
+) -> u16 {
+	member
+		.roles
+		.iter()
+		.filter_map(|id| roles.get(id))
+		.map(|role| role.position)
+		.max()
+		.unwrap_or(0)
+}
+
+/// Makes sure both the moderator and the bot are above `target` in the role hierarchy.
+///
+/// Users that aren't in the server (e.g. banning someone who already left) always pass.
+pub async fn check_hierarchy(
+	ctx: Context<'_>,
+	target: &User,
+) -> Result<(), Error> {
+	let guild_id = ctx.guild_id().ok_or(BotError::guild_only())?;
+
+	let Ok(target_member) = guild_id.member(ctx.serenity_context(), target.id).await else {
+		return Ok(());
+	};
+
+	let guild = ctx
+		.partial_guild()
+		.await
+		.ok_or(BotError::NotFound("Couldn't load this server.".into()))?;
+
+	if target.id == guild.owner_id {
+		return Err(BotError::Hierarchy(
+			"The server owner can't be moderated.".into(),
+		));
+	}
+
+	let target_position = highest_position(&target_member, &guild.roles);
+
+	if ctx.author().id != guild.owner_id {
+		let author_member = ctx
+			.author_member()
+			.await
+			.ok_or(BotError::NotFound("Couldn't load your member info.".into()))?;
+
+		if highest_position(&author_member, &guild.roles) <= target_position {
+			return Err(BotError::Hierarchy(format!(
+				"You can't moderate {} because their highest role is the same as or above yours.",
+				target.name
+			)));
+		}
+	}
+
+	let bot_id = ctx.data().identity.require_id()?;
+	let bot_member = guild_id.member(ctx.serenity_context(), bot_id).await?;
+
+	if highest_position(&bot_member, &guild.roles) <= target_position {
+		return Err(BotError::Hierarchy(format!(
+			"I can't moderate {} because their highest role is the same as or above mine, move \
+			 my role higher in the server settings.",
+			target.name
+		)));
+	}
+
+	Ok(())
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use serenity::all::{GuildId, Timestamp, UserId};
+
+use crate::config::MUTES_DB_PATH;
+use crate::utils::db;
+use crate::utils::errors::BotError;
+
+/// discord's longest timeout, longer mutes use the muted role
+pub const MAX_TIMEOUT_SECS: u64 = 28 * 86400;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MuteKind {
+	/// discord's communication disabled until, lifted by discord
+	Timeout,
+	/// the guild's muted role, taken away by the bot once it runs out
+	Role,
+}
+
+/// An active mute and who is behind it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Mute {
+	pub kind:      MuteKind,
+	pub moderator: UserId,
+	pub reason:    String,
+	/// unix timestamps
+	pub since:     i64,
+	/// `None` lasts until someone unmutes them
+	pub until:     Option<i64>,
+}
+
+impl Mute {
+	/// how long is left, `None` for mutes without an end
+	pub fn remaining(&self) -> Option<u64> {
+		self.until
+			.map(|until| (until - Timestamp::now().unix_timestamp()).max(0) as u64)
+	}
+}
+
+pub fn mute_of(
+	guild_id: GuildId,
+	user_id: UserId,
+) -> Result<Option<Mute>, BotError> {
+	let db = db::open(MUTES_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match tree.get(user_id.get().to_be_bytes())? {
+		| Some(ivec) => Ok(Some(serde_json::from_slice(&ivec)?)),
+		| None => Ok(None),
+	}
+}
+
+/// `None` once the member is unmuted
+pub fn set_mute(
+	guild_id: GuildId,
+	user_id: UserId,
+	mute: Option<&Mute>,
+) -> Result<(), BotError> {
+	let db = db::open(MUTES_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match mute {
+		| Some(mute) => {
+			tree.insert(user_id.get().to_be_bytes(), serde_json::to_vec(mute)?)?;
+		},
+		| None => {
+			tree.remove(user_id.get().to_be_bytes())?;
+		},
+	}
+	tree.flush()?;
+	Ok(())
+}
+
+/// Every active mute in the guild
+pub fn mutes(guild_id: GuildId) -> Result<Vec<(UserId, Mute)>, BotError> {
+	mutes_in(&db::open(MUTES_DB_PATH)?, guild_id)
+}
+
+fn mutes_in(
+	db: &sled::Db,
+	guild_id: GuildId,
+) -> Result<Vec<(UserId, Mute)>, BotError> {
+	Ok(db
+		.open_tree(guild_id.to_string())?
+		.iter()
+		.flatten()
+		.filter_map(|(key, value)| {
+			let id = UserId::new(u64::from_be_bytes((*key).try_into().ok()?));
+			Some((id, serde_json::from_slice(&value).ok()?))
+		})
+		.collect())
+}
+
+/// Mutes in every guild that have run out
+pub fn expired() -> Result<Vec<(GuildId, UserId, Mute)>, BotError> {
+	let db = db::open(MUTES_DB_PATH)?;
+	let now = Timestamp::now().unix_timestamp();
+
+	let mut expired = vec![];
+	for guild_id in db.tree_names().iter().filter_map(|name| {
+		std::str::from_utf8(name)
+			.ok()?
+			.parse()
+			.ok()
+			.map(GuildId::new)
+	}) {
+		for (user_id, mute) in mutes_in(&db, guild_id)? {
+			if mute.until.is_some_and(|until| until <= now) {
+				expired.push((guild_id, user_id, mute));
+			}
+		}
+	}
+	Ok(expired)
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use serenity::all::{GuildId, UserId};
+
+use crate::config::config;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings;
+
+#[derive(Clone)]
+struct GuildPrefixes {
+	prefixes: Vec<String>,
+	mention:  bool,
+}
+
+/// Prefixes per guild, kept in memory since they are checked on every message. Changes made
+/// through commands or the api invalidate the guild's entry
+#[derive(Default)]
+pub struct PrefixCache {
+	entries: RwLock<HashMap<Option<GuildId>, GuildPrefixes>>,
+}
+
+fn read(guild_id: Option<GuildId>) -> Result<GuildPrefixes, BotError> {
+	let mut prefixes = guild_settings::prefixes(guild_id)?;
+	// longest first so `!!` isn't matched as `!` followed by `!command`
+	prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+	Ok(GuildPrefixes {
+		prefixes,
+		mention: guild_settings::mention_prefix(guild_id)?,
+	})
+}
+
+impl PrefixCache {
+	fn load(
+		&self,
+		guild_id: Option<GuildId>,
+	) -> GuildPrefixes {
+		if let Some(entry) = self
+			.entries
+			.read()
+			.ok()
+			.and_then(|entries| entries.get(&guild_id).cloned())
+		{
+			return entry;
+		}
+
+		let entry = match read(guild_id) {
+			| Ok(entry) => entry,
+			| Err(e) => {
+				// not cached, so the next message tries the store again
+				println!(
+					"\x1b[33;1m[WARN]\x1b[0m Couldn't read the prefixes of {:?}, using the \
+					 default: {}",
+					guild_id, e
+				);
+				return GuildPrefixes {
+					prefixes: vec![config().prefix.clone()],
+					mention:  true,
+				};
+			},
+		};
+
+		if let Ok(mut entries) = self.entries.write() {
+			entries.insert(guild_id, entry.clone());
+		}
+
+		entry
+	}
+
+	/// drops the cached prefixes so the next message reloads them from the store
+	pub fn invalidate(
+		&self,
+		guild_id: GuildId,
+	) {
+		if let Ok(mut entries) = self.entries.write() {
+			entries.remove(&Some(guild_id));
+		}
+	}
+
+	/// Splits `content` into the prefix it starts with and the rest, if it starts with one
+	pub fn strip<'a>(
+		&self,
+		guild_id: Option<GuildId>,
+		bot_id: Option<UserId>,
+		content: &'a str,
+	) -> Option<(&'a str, &'a str)> {
+		let entry = self.load(guild_id);
+
+		if let Some(prefix) = entry
+			.prefixes
+			.iter()
+			.find(|p| content.starts_with(p.as_str()))
+		{
+			return Some(content.split_at(prefix.len()));
+		}
+
+		if entry.mention
+			&& let Some(bot_id) = bot_id
+		{
+			// mentions are either <@id> or <@!id>
+			let rest = content
+				.strip_prefix("<@")?
+				.trim_start_matches('!')
+				.strip_prefix(&bot_id.to_string())?
+				.strip_prefix('>')?;
+			return Some(content.split_at(content.len() - rest.trim_start().len()));
+		}
+
+		None
+	}
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, Color, EditChannel, GuildId, Http};
+
+use crate::background::guild_logs::ignore::Subject;
+use crate::background::guild_logs::log_queue::LogQueue;
+use crate::background::guild_logs::{Log, log_channel};
+use crate::config::SLOWMODE_DB_PATH;
+use crate::utils::dates::{format_duration, parse_duration};
+use crate::utils::db;
+use crate::utils::errors::BotError;
+use crate::utils::guild_settings::LogType;
+
+/// discord's longest slowmode, 6 hours
+pub const MAX_SLOWMODE: u64 = 6 * 3600;
+
+/// Slowmode every day between two times, in the guild's timezone
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SlowmodeSchedule {
+	/// minutes after midnight
+	pub start:    u16,
+	pub end:      u16,
+	pub seconds:  u16,
+	/// the slowmode from before the window started, put back when it ends. `None` outside the
+	/// window
+	pub previous: Option<u16>,
+}
+
+impl SlowmodeSchedule {
+	/// windows can go past midnight, `22:00-02:00` covers both sides of it
+	pub fn contains(
+		&self,
+		minute: u16,
+	) -> bool {
+		match self.start <= self.end {
+			| true => self.start <= minute && minute < self.end,
+			| false => minute >= self.start || minute < self.end,
+		}
+	}
+
+	pub fn window(&self) -> String {
+		let time = |m: u16| format!("{:02}:{:02}", m / 60, m % 60);
+		format!("{}–{}", time(self.start), time(self.end))
+	}
+}
+
+/// `18:00` or `6:30` as minutes after midnight
+pub fn parse_time(input: &str) -> Option<u16> {
+	let (hours, minutes) = input.trim().split_once(':')?;
+	let (hours, minutes): (u16, u16) = (hours.parse().ok()?, minutes.parse().ok()?);
+	(hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// `off`, a number of seconds or a duration like `2m`, as seconds
+pub fn parse_slowmode(input: &str) -> Result<u64, BotError> {
+	let input = input.trim();
+	let seconds = match input.to_lowercase().as_str() {
+		| "off" | "0" => Some(0),
+		| _ => input.parse().ok().or_else(|| parse_duration(input)),
+	};
+
+	match seconds {
+		| Some(seconds) if seconds <= MAX_SLOWMODE => Ok(seconds),
+		| Some(_) => Err(BotError::InvalidInput(
+			"Slowmode can be at most 6 hours.".into(),
+		)),
+		| None => Err(BotError::InvalidInput(
+			"Slowmode looks like `10s`, `2m`, `1h` or `off`.".into(),
+		)),
+	}
+}
+
+pub fn show_slowmode(seconds: u64) -> String {
+	match seconds {
+		| 0 => "Off".to_string(),
+		| seconds => format_duration(seconds),
+	}
+}
+
+/// Every schedule in the guild
+pub fn schedules(guild_id: GuildId) -> Result<Vec<(ChannelId, SlowmodeSchedule)>, BotError> {
+	schedules_in(&db::open(SLOWMODE_DB_PATH)?, guild_id)
+}
+
+/// Every schedule in every guild, for the scheduler
+pub fn all_schedules() -> Result<Vec<(GuildId, ChannelId, SlowmodeSchedule)>, BotError> {
+	let db = db::open(SLOWMODE_DB_PATH)?;
+
+	let mut all = vec![];
+	for guild_id in db.tree_names().iter().filter_map(|name| {
+		std::str::from_utf8(name)
+			.ok()?
+			.parse()
+			.ok()
+			.map(GuildId::new)
+	}) {
+		for (channel_id, schedule) in schedules_in(&db, guild_id)? {
+			all.push((guild_id, channel_id, schedule));
+		}
+	}
+	Ok(all)
+}
+
+fn schedules_in(
+	db: &sled::Db,
+	guild_id: GuildId,
+) -> Result<Vec<(ChannelId, SlowmodeSchedule)>, BotError> {
+	Ok(db
+		.open_tree(guild_id.to_string())?
+		.iter()
+		.flatten()
+		.filter_map(|(key, value)| {
+			let id = ChannelId::new(u64::from_be_bytes((*key).try_into().ok()?));
+			Some((id, serde_json::from_slice(&value).ok()?))
+		})
+		.collect())
+}
+
+pub fn schedule_of(
+	guild_id: GuildId,
+	channel_id: ChannelId,
+) -> Result<Option<SlowmodeSchedule>, BotError> {
+	let db = db::open(SLOWMODE_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match tree.get(channel_id.get().to_be_bytes())? {
+		| Some(ivec) => Ok(Some(serde_json::from_slice(&ivec)?)),
+		| None => Ok(None),
+	}
+}
+
+/// `None` removes the channel's schedule
+pub fn set_schedule(
+	guild_id: GuildId,
+	channel_id: ChannelId,
+	schedule: Option<&SlowmodeSchedule>,
+) -> Result<(), BotError> {
+	let db = db::open(SLOWMODE_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	match schedule {
+		| Some(schedule) => {
+			tree.insert(
+				channel_id.get().to_be_bytes(),
+				serde_json::to_vec(schedule)?,
+			)?;
+		},
+		| None => {
+			tree.remove(channel_id.get().to_be_bytes())?;
+		},
+	}
+	tree.flush()?;
+	Ok(())
+}
+
+/// Records the slowmode a running window has to put back, on the schedule as it's stored now. The
+/// schedule may have been changed or removed while the slowmode was being set, only `previous` is
+/// written and a removed schedule stays removed
+pub fn set_previous(
+	guild_id: GuildId,
+	channel_id: ChannelId,
+	previous: Option<u16>,
+) -> Result<(), BotError> {
+	let db = db::open(SLOWMODE_DB_PATH)?;
+	let tree = db.open_tree(guild_id.to_string())?;
+	tree.fetch_and_update(channel_id.get().to_be_bytes(), |stored| {
+		let stored = stored?;
+		match serde_json::from_slice::<SlowmodeSchedule>(stored) {
+			| Ok(mut schedule) => {
+				schedule.previous = previous;
+				serde_json::to_vec(&schedule).ok().or(Some(stored.to_vec()))
+			},
+			| Err(_) => Some(stored.to_vec()),
+		}
+	})?;
+	tree.flush()?;
+	Ok(())
+}
+
+/// Changes the slowmode and logs who changed it, the channel update log can't tell
+pub async fn set_slowmode(
+	http: &Http,
+	queue: &LogQueue,
+	guild_id: GuildId,
+	channel_id: ChannelId,
+	seconds: u64,
+	by: String,
+) -> Result<(), BotError> {
+	channel_id
+		.edit(
+			http,
+			EditChannel::new()
+				.rate_limit_per_user(seconds as u16)
+				.audit_log_reason(&by),
+		)
+		.await
+		.map_err(|e| {
+			BotError::from(e)
+				.with_not_found(format!("<#{}> doesn't exist anymore.", channel_id))
+				.with_permission_denied(format!(
+					"I can't change the slowmode in <#{}>, make sure I have Manage Channels there.",
+					channel_id
+				))
+		})?;
+
+	if let Some(log) = log_channel(guild_id, LogType::ChannelChanges) {
+		let subject = Subject::new(guild_id, LogType::ChannelChanges).channel(channel_id);
+		queue.push(
+			Log::new(log, "Slowmode Changed", Color::GOLD, vec![
+				("Channel", format!("<#{}>", channel_id), true),
+				("Slowmode", show_slowmode(seconds), true),
+				("By", by, true),
+			])
+			.about(subject),
+		);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn schedule(
+		start: &str,
+		end: &str,
+	) -> SlowmodeSchedule {
+		SlowmodeSchedule {
+			start:    parse_time(start).unwrap(),
+			end:      parse_time(end).unwrap(),
+			seconds:  30,
+			previous: None,
+		}
+	}
+
+	#[test]
+	fn windows_can_cross_midnight() {
+		let day = schedule("9:00", "17:00");
+		assert!(day.contains(parse_time("9:00").unwrap()));
+		assert!(day.contains(parse_time("16:59").unwrap()));
+		assert!(!day.contains(parse_time("17:00").unwrap()));
+		assert!(!day.contains(parse_time("3:00").unwrap()));
+
+		let night = schedule("22:00", "2:00");
+		assert!(night.contains(parse_time("22:00").unwrap()));
+		assert!(night.contains(parse_time("23:59").unwrap()));
+		assert!(night.contains(parse_time("0:00").unwrap()));
+		assert!(night.contains(parse_time("1:59").unwrap()));
+		assert!(!night.contains(parse_time("2:00").unwrap()));
+		assert!(!night.contains(parse_time("12:00").unwrap()));
+	}
+
+	#[test]
+	fn parse_slowmode_inputs() {
+		assert_eq!(parse_slowmode("off").unwrap(), 0);
+		assert_eq!(parse_slowmode("OFF").unwrap(), 0);
+		assert_eq!(parse_slowmode("0").unwrap(), 0);
+		// plain numbers are seconds here, unlike other durations
+		assert_eq!(parse_slowmode("45").unwrap(), 45);
+		assert_eq!(parse_slowmode("2m").unwrap(), 120);
+		assert_eq!(parse_slowmode("6h").unwrap(), MAX_SLOWMODE);
+		assert!(parse_slowmode("6h1s").is_err());
+		assert!(parse_slowmode("soon").is_err());
+	}
+}
@@ -1,21 +1,28 @@
-use std::fmt;
-
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use sled::Db;
 use strsim::jaro_winkler;
 
 use crate::Data;
+use crate::config::TAGS_DB_PATH;
 use crate::types::Context;
+use crate::utils::errors::BotError;
+
+fn not_found(name: &str) -> BotError {
+	BotError::NotFound(format!(
+		"Tag `{}` doesn't exist! Use `/tag list` to see all tags.",
+		name
+	))
+}
 
 pub struct TagDb {
 	db: Db,
 }
 
 impl TagDb {
-	pub fn new() -> Self {
-		TagDb {
-			db: sled::open("data/tags").unwrap(),
-		}
+	pub fn new() -> Result<Self, BotError> {
+		Ok(TagDb {
+			db: sled::open(TAGS_DB_PATH)?,
+		})
 	}
 
 	pub async fn create_tag(
@@ -23,11 +30,14 @@ impl TagDb {
 		name: &str,
 		content: &str,
 		guild_id: u64,
-	) -> Result<(), TagError> {
+	) -> Result<(), BotError> {
 		let tree = self.db.open_tree(guild_id.to_string())?;
 
 		if let Some(_value) = tree.get(name.as_bytes())? {
-			return Err(TagError::AlreadyExists(name.to_string()));
+			return Err(BotError::InvalidInput(format!(
+				"Tag `{}` already exists!",
+				name
+			)));
 		}
 
 		tree.insert(name.as_bytes(), content.as_bytes())?;
@@ -38,7 +48,7 @@ impl TagDb {
 		&self,
 		name: &str,
 		guild_id: u64,
-	) -> Result<String, TagError> {
+	) -> Result<String, BotError> {
 		let tree = self.db.open_tree(guild_id.to_string())?;
 		let fixed_name = self.fix_typos(name, guild_id).await?;
 
@@ -47,7 +57,7 @@ impl TagDb {
 			return Ok(fixed_name);
 		}
 
-		return Err(TagError::DoesntExist(name.to_string()));
+		Err(not_found(name))
 	}
 
 	pub async fn edit_tag(
@@ -55,7 +65,7 @@ impl TagDb {
 		name: &str,
 		content: &str,
 		guild_id: u64,
-	) -> Result<String, TagError> {
+	) -> Result<String, BotError> {
 		let tree = self.db.open_tree(guild_id.to_string())?;
 		let fixed_name = self.fix_typos(name, guild_id).await?;
 
@@ -64,37 +74,37 @@ impl TagDb {
 			return Ok(fixed_name);
 		}
 
-		Err(TagError::DoesntExist(fixed_name.to_string()))
+		Err(not_found(&fixed_name))
 	}
 
 	async fn get_tag_exact(
 		&self,
 		name: &str,
 		guild_id: u64,
-	) -> Result<String, TagError> {
+	) -> Result<String, BotError> {
 		let tree = self.db.open_tree(guild_id.to_string())?;
 
 		if let Some(value) = tree.get(name.as_bytes())? {
 			return Ok(str::from_utf8(&value)?.to_owned());
 		}
 
-		Err(TagError::DoesntExist(name.to_string()))
+		Err(not_found(name))
 	}
 
 	pub async fn get_tag(
 		&self,
 		name: &str,
 		guild_id: u64,
-	) -> Result<String, TagError> {
+	) -> Result<String, BotError> {
 		let fixed_name = self.fix_typos(name, guild_id).await?;
 
-		return Ok(self.get_tag_exact(&fixed_name, guild_id).await?);
+		self.get_tag_exact(&fixed_name, guild_id).await
 	}
 
 	pub async fn get_all_tags(
 		&self,
 		guild_id: u64,
-	) -> Result<Vec<String>, TagError> {
+	) -> Result<Vec<String>, BotError> {
 		let tree = self.db.open_tree(guild_id.to_string())?;
 
 		let mut tags = Vec::<String>::new();
@@ -112,7 +122,7 @@ impl TagDb {
 		&self,
 		name: &str,
 		guild_id: u64,
-	) -> Result<String, TagError> {
+	) -> Result<String, BotError> {
 		let all_tags = self.get_all_tags(guild_id).await?;
 		if all_tags.is_empty() {
 			return Ok(name.to_owned());
@@ -135,58 +145,13 @@ impl TagDb {
 	}
 }
 
-#[derive(Debug)]
-pub enum TagError {
-	DoesntExist(String),
-	AlreadyExists(String),
-	NotGuild(),
-	Serenity(String),
-	Database(String),
-	Utf8Error(String),
-}
-
-impl std::error::Error for TagError {}
-impl From<std::str::Utf8Error> for TagError {
-	fn from(err: std::str::Utf8Error) -> TagError {
-		TagError::Utf8Error(err.to_string())
-	}
-}
-
-impl From<serenity::Error> for TagError {
-	fn from(err: serenity::Error) -> TagError {
-		TagError::Serenity(err.to_string())
-	}
-}
-
-impl From<sled::Error> for TagError {
-	fn from(err: sled::Error) -> TagError {
-		TagError::Database(err.to_string())
-	}
-}
-
-impl std::fmt::Display for TagError {
-	fn fmt(
-		&self,
-		f: &mut fmt::Formatter<'_>,
-	) -> fmt::Result {
-		match self {
-			| TagError::DoesntExist(tag) => write!(f, "❌ Tag `{}` doesn't exist!", tag),
-			| TagError::AlreadyExists(tag) => write!(f, "❌ Tag `{}` already exists!", tag),
-			| TagError::NotGuild() => write!(f, "❌ This command can only be run in servers."),
-			| TagError::Serenity(e) => write!(f, "❌ {}", e),
-			| TagError::Database(e) => write!(f, "❌ {}", e),
-			| TagError::Utf8Error(e) => write!(f, "❌ {}", e),
-		}
-	}
-}
-
-pub async fn get_data_and_id(ctx: Context<'_>) -> Result<(&Data, u64), TagError> {
+pub async fn get_data_and_id(ctx: Context<'_>) -> Result<(&Data, u64), BotError> {
 	let data = ctx.data();
 
 	let id = match ctx.guild_id() {
 		| Some(id) => id.get(),
 		| None => {
-			return Err(TagError::NotGuild());
+			return Err(BotError::guild_only());
 		},
 	};
 
@@ -0,0 +1,51 @@
+use std::env::var;
+
+/// the bot signs attachment links with this and the api checks them with it
+pub const SECRET_VAR: &str = "ATTACHMENT_URL_SECRET";
+/// in characters, anything shorter is too easy to guess
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// The secret attachment links are signed with, `Ok(None)` when it's blank or unset. Values with
+/// spaces are refused too, those are descriptions pasted in rather than a random string. The
+/// error is a sentence that can be shown as is
+pub fn secret() -> Result<Option<String>, String> {
+	let Ok(value) = var(SECRET_VAR) else {
+		return Ok(None);
+	};
+	check_secret(value.trim())
+}
+
+fn check_secret(secret: &str) -> Result<Option<String>, String> {
+	if secret.is_empty() {
+		return Ok(None);
+	}
+	if secret.contains(char::is_whitespace) || secret.chars().count() < MIN_SECRET_LEN {
+		return Err(format!(
+			"`{}` has to be a random string of at least {} characters without spaces",
+			SECRET_VAR, MIN_SECRET_LEN
+		));
+	}
+	Ok(Some(secret.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blank_secret_is_unset() {
+		assert_eq!(check_secret(""), Ok(None));
+	}
+
+	#[test]
+	fn placeholders_are_refused() {
+		assert!(check_secret("random string used to sign attachment links").is_err());
+		assert!(check_secret("secret").is_err());
+	}
+
+	#[test]
+	fn random_secret_is_kept() {
+		let secret = "d1f0c9a4b7e25c3a8f6e";
+		assert_eq!(check_secret(secret), Ok(Some(secret.to_string())));
+	}
+}
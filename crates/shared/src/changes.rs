@@ -0,0 +1,174 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prefixes;
+
+/// sled only lets one process open a store, so the api leaves settings changes here and the bot
+/// applies them
+pub const CHANGES_DIR: &str = "data/api_changes";
+
+/// key of each of the bot's `LogType`s in the order of its variants, changes refer to log types by
+/// these
+pub const LOG_TYPES: &[&str] = &[
+	"MESSAGE_SENT_CHANNEL_ID",
+	"MESSAGE_EDIT_CHANNEL_ID",
+	"MESSAGE_DELETE_CHANNEL_ID",
+	"MESSAGE_BULK_DELETE_CHANNEL_ID",
+	"MEMBER_JOIN_CHANNEL_ID",
+	"MEMBER_LEAVE_CHANNEL_ID",
+	"NICKNAME_CHANGE_CHANNEL_ID",
+	"MEMBER_ROLE_CHANGE_CHANNEL_ID",
+	"AVATAR_CHANGE_CHANNEL_ID",
+	"CHANNEL_CHANGES_CHANNEL_ID",
+	"ROLE_CHANGES_CHANNEL_ID",
+	"EMOJI_CHANGES_CHANNEL_ID",
+	"SERVER_CHANGES_CHANNEL_ID",
+	"VOICE_CHANNEL_ID",
+	"BAN_CHANNEL_ID",
+	"AUTOMOD_CHANNEL_ID",
+];
+
+/// A settings change made through the api, one file each in [`CHANGES_DIR`]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingsChange {
+	Prefix {
+		guild_id: u64,
+		prefixes: Vec<String>,
+		/// left as it is when `None`
+		mention:  Option<bool>,
+	},
+	LogChannel {
+		guild_id:   u64,
+		/// one of [`LOG_TYPES`]
+		log_type:   String,
+		channel_id: u64,
+	},
+	LogIgnore {
+		guild_id: u64,
+		log_type: String,
+		rules:    LogIgnoreRules,
+	},
+}
+
+/// replaces every ignore rule of one log type, the bot's `IgnoreRules` with plain ids
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LogIgnoreRules {
+	pub channels:   Vec<u64>,
+	pub categories: Vec<u64>,
+	pub users:      Vec<u64>,
+	pub roles:      Vec<u64>,
+	pub bots:       bool,
+	pub commands:   bool,
+}
+
+impl SettingsChange {
+	/// Everything the bot checks before applying the change, so the api can refuse a change the
+	/// bot would drop. The error is a sentence that can be shown as is
+	pub fn validate(&self) -> Result<(), String> {
+		match self {
+			| SettingsChange::Prefix {
+				guild_id,
+				prefixes: new,
+				..
+			} => {
+				id(*guild_id, "guild")?;
+				prefixes::validate(new)
+			},
+			| SettingsChange::LogChannel {
+				guild_id,
+				log_type,
+				channel_id,
+			} => {
+				id(*guild_id, "guild")?;
+				known_log_type(log_type)?;
+				id(*channel_id, "channel")
+			},
+			| SettingsChange::LogIgnore {
+				guild_id,
+				log_type,
+				rules,
+			} => {
+				id(*guild_id, "guild")?;
+				known_log_type(log_type)?;
+				let ids = [
+					&rules.channels,
+					&rules.categories,
+					&rules.users,
+					&rules.roles,
+				];
+				ids.into_iter()
+					.flatten()
+					.try_for_each(|rule_id| id(*rule_id, "discord"))
+			},
+		}
+	}
+}
+
+/// discord ids are never 0
+fn id(
+	id: u64,
+	kind: &str,
+) -> Result<(), String> {
+	match id {
+		| 0 => Err(format!("`0` isn't a {} id", kind)),
+		| _ => Ok(()),
+	}
+}
+
+fn known_log_type(log_type: &str) -> Result<(), String> {
+	match LOG_TYPES.contains(&log_type) {
+		| true => Ok(()),
+		| false => Err(format!(
+			"unknown log type `{}`, expected one of: {}",
+			log_type,
+			LOG_TYPES.join(", ")
+		)),
+	}
+}
+
+static SUBMITTED: AtomicU64 = AtomicU64::new(0);
+
+/// Leaves the change for the bot. Written under a temporary name first so the bot never reads
+/// half a file
+pub fn submit(change: &SettingsChange) -> io::Result<()> {
+	fs::create_dir_all(CHANGES_DIR)?;
+
+	let nanos = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_nanos())
+		.unwrap_or_default();
+	// padded so sorting the names sorts them by when they were made
+	let name = format!(
+		"{:024}-{:06}",
+		nanos,
+		SUBMITTED.fetch_add(1, Ordering::Relaxed) % 1_000_000
+	);
+
+	let temp = Path::new(CHANGES_DIR).join(format!("{}.tmp", name));
+	fs::write(&temp, serde_json::to_vec(change)?)?;
+	fs::rename(&temp, Path::new(CHANGES_DIR).join(format!("{}.json", name)))
+}
+
+/// Changes waiting to be applied, oldest first
+pub fn pending() -> io::Result<Vec<PathBuf>> {
+	let mut paths: Vec<PathBuf> = match fs::read_dir(CHANGES_DIR) {
+		| Ok(entries) => entries
+			.flatten()
+			.map(|entry| entry.path())
+			.filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+			.collect(),
+		| Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+		| Err(e) => return Err(e),
+	};
+	paths.sort();
+	Ok(paths)
+}
+
+pub fn read(path: &Path) -> io::Result<SettingsChange> {
+	Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
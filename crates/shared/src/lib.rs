@@ -0,0 +1,5 @@
+//! What the bot and the api have to agree on, they run as separate processes
+
+pub mod attachment_links;
+pub mod changes;
+pub mod prefixes;
@@ -0,0 +1,24 @@
+pub const MAX_PREFIXES: usize = 5;
+/// in characters
+pub const MAX_PREFIX_LEN: usize = 5;
+
+/// At most [`MAX_PREFIXES`] prefixes of 1 to [`MAX_PREFIX_LEN`] characters without spaces. The
+/// error is a sentence that can be shown as is
+pub fn validate(prefixes: &[String]) -> Result<(), String> {
+	if prefixes.is_empty() || prefixes.len() > MAX_PREFIXES {
+		return Err(format!(
+			"Set between 1 and {} prefixes, separated by spaces.",
+			MAX_PREFIXES
+		));
+	}
+
+	match prefixes.iter().find(|p| {
+		p.is_empty() || p.chars().count() > MAX_PREFIX_LEN || p.contains(char::is_whitespace)
+	}) {
+		| Some(prefix) => Err(format!(
+			"`{}` can't be a prefix, prefixes are 1 to {} characters without spaces.",
+			prefix, MAX_PREFIX_LEN
+		)),
+		| None => Ok(()),
+	}
+}